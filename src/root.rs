@@ -1,20 +1,37 @@
+use std::collections::HashMap;
+use std::env;
 use std::fs;
 use std::fs::File;
 use std::io::ErrorKind;
 use std::path::PathBuf;
+use std::panic;
+use std::process;
+use std::thread;
 use std::time::Instant;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use color_print::cprintln;
 use num_format::{Locale, ToFormattedString};
 
-use shared::common::ByteSize;
+use shared::common::{ByteSize, FunctionID};
 
 use crate::root::compiler::compile::compile;
+use crate::root::compiler::global_tracker::RuntimeChecks;
+use crate::root::docs::generate_docs;
+use crate::root::errors::parser_errors::ParseError;
+use crate::root::errors::WErr;
+use crate::root::formatter::format_files;
+use crate::root::golden::check_golden;
+use crate::root::interpreter::{Program, INTERPRETER_STACK_SIZE};
+use crate::root::lsp::run_server;
+use crate::root::name_resolver::name_resolvers::GlobalTable;
 use crate::root::name_resolver::resolve::resolve;
 use crate::root::parser::parse::parse;
+use crate::root::parser::parse_function::FunctionToken;
 use crate::root::parser::path_storage::PathStorage;
+use crate::root::repl::run_repl;
 use crate::root::runner::{assemble, link_gcc, run};
+use crate::root::watch::watch;
 use crate::time;
 
 #[cfg(debug_assertions)]
@@ -23,76 +40,403 @@ pub const DEBUG_ON_ERROR: bool = false;
 pub mod assembler;
 pub mod builtin;
 pub mod compiler;
+pub mod docs;
 pub mod errors;
+pub mod formatter;
+pub mod golden;
+pub mod interpreter;
+pub mod lsp;
 pub mod name_resolver;
 mod ob;
 pub mod parser;
+pub mod repl;
 pub mod runner;
 pub mod shared;
 mod unrandom;
 pub mod utils;
+pub mod watch;
 
 pub const POINTER_SIZE: ByteSize = ByteSize(8);
+pub const DEFAULT_PRELUDE: &str = "std/prelude.why";
 
 /// Compiler for Whython files (.why)
-#[derive(Parser)]
+#[derive(Parser, Clone)]
 #[command(version, about, long_about = None)]
 pub struct Args {
     /// Main input file
-    #[arg(short, long, default_value = "main.why")]
+    #[arg(short, long, default_value = "main.why", global = true)]
     pub input: String,
     /// Output files name without extension
-    #[arg(short, long, default_value = "build/out")]
+    #[arg(short, long, default_value = "build/out", global = true)]
     pub output: String,
     /// Only build - don't run
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub build: bool,
+    /// Additional folder to search for imported files. Searched in order before the folders in the
+    /// `WHYTHON_PATH` environment variable
+    #[arg(short = 'L', long = "library-path", global = true)]
+    pub library_paths: Vec<String>,
+    /// File whose contents are implicitly available in every file. Defaults to `std/prelude.why`
+    /// if it exists
+    #[arg(long, global = true)]
+    pub prelude: Option<String>,
+    /// Don't use a prelude file
+    #[arg(long, conflicts_with = "prelude", global = true)]
+    pub no_prelude: bool,
+    /// Don't check array indices at runtime. Implied by `--release`
+    #[arg(long, global = true)]
+    pub no_bounds_checks: bool,
+    /// Build in release mode - integer overflow, division by zero and array indices aren't checked
+    /// at runtime
+    #[arg(short, long, global = true)]
+    pub release: bool,
+    /// Track heap allocations at runtime to detect double frees and frees of non-heap memory, and
+    /// report leaked memory when `main` returns
+    #[arg(long, global = true)]
+    pub track_heap: bool,
+    /// After building (and running), rebuild and rerun from scratch whenever a source file used
+    /// changes
+    #[arg(short, long, global = true)]
+    pub watch: bool,
+    /// Run the program with the built-in interpreter instead of building an executable, so NASM
+    /// and gcc aren't needed
+    #[arg(long, global = true)]
+    pub interpret: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Alternatives to building and running `main`
+#[derive(Subcommand, Clone)]
+pub enum Command {
+    /// Build and run the functions marked `#[test]`, each in its own process. Exits with a
+    /// non-zero code if any test fails
+    Test,
+    /// Build and run (or interpret) every `.why` file in a directory, checking their output and
+    /// exit code against their `// expect-stdout:`, `// expect-stderr:`, `// expect-exit:` and
+    /// `// expect-error:` comments. `// flags:` comments give runtime check flags to build with
+    Golden {
+        /// Directory containing the sample programs
+        directory: String,
+    },
+    /// Write a Markdown reference of the types and functions available to the input file, including
+    /// doc comments, to the output name with a `.md` extension
+    Doc,
+    /// Format `.why` files with canonical indentation, spacing and blank lines
+    Fmt {
+        /// Files or directories to format. Defaults to the input file
+        paths: Vec<String>,
+        /// Don't write changes - exit with a non-zero code if any file isn't formatted
+        #[arg(long)]
+        check: bool,
+    },
+    /// Run a Language Server Protocol server over stdin and stdout for editor integration. Paths
+    /// are resolved relative to the working directory, as when building
+    Lsp,
+    /// Start an interactive session where struct, impl and function definitions, `use` lines and
+    /// statements are entered line by line and run with the interpreter
+    Repl,
+}
+
+impl Args {
+    /// Returns the folders to search for imported files - those given by `-L`, followed by those in
+    /// `WHYTHON_PATH`
+    pub fn search_paths(&self) -> Vec<String> {
+        let mut search_paths = self.library_paths.clone();
+        if let Some(whython_path) = env::var_os("WHYTHON_PATH") {
+            search_paths.extend(
+                env::split_paths(&whython_path)
+                    .filter(|p| !p.as_os_str().is_empty())
+                    .map(|p| p.to_string_lossy().to_string()),
+            );
+        }
+        search_paths
+    }
+
+    /// Returns the runtime checks enabled by the options. Release builds check neither arithmetic
+    /// nor array indices
+    pub fn runtime_checks(&self) -> RuntimeChecks {
+        RuntimeChecks::new(
+            !self.no_bounds_checks && !self.release,
+            !self.release,
+            self.track_heap,
+        )
+    }
 }
 
 pub fn main() {
     let args = Args::parse();
-    if let Err(e) = main_args(args) {
+    let result = if args.interpret || matches!(args.command, Some(Command::Repl)) {
+        let handle = thread::Builder::new()
+            .stack_size(INTERPRETER_STACK_SIZE)
+            .spawn(|| main_args(args))
+            .unwrap();
+        handle
+            .join()
+            .unwrap_or_else(|e| panic::resume_unwind(e))
+    } else {
+        main_args(args)
+    };
+    if let Err(e) = result {
         println!("\n{e}");
     }
 }
 
 pub fn main_args(args: Args) -> Result<(), String> {
-    if let Some(path) = PathBuf::from(&args.output).parent() {
-        if let Err(e) = fs::create_dir_all(path) {
-            if !matches!(e.kind(), ErrorKind::AlreadyExists) {
-                cprintln!("<r,bold>Failed to create directories for output files</>");
-                panic!();
+    if args.watch && !matches!(args.command, None | Some(Command::Test)) {
+        return Err("`--watch` can only be used when building, running or testing".to_string());
+    }
+    if args.interpret
+        && !matches!(
+            args.command,
+            None | Some(Command::Test) | Some(Command::Golden { .. })
+        )
+    {
+        return Err(
+            "`--interpret` can only be used when running, testing or checking samples".to_string(),
+        );
+    }
+    if let Some(Command::Golden { directory }) = &args.command {
+        return check_golden(&args, directory);
+    }
+    if let Some(Command::Doc) = &args.command {
+        return document(&args);
+    }
+    if let Some(Command::Fmt { paths, check }) = &args.command {
+        return format_files(&args, paths, *check);
+    }
+    if let Some(Command::Lsp) = &args.command {
+        return run_server(&args);
+    }
+    if let Some(Command::Repl) = &args.command {
+        let code = run_repl(&args)?;
+        if code != 0 {
+            process::exit(code);
+        }
+        return Ok(());
+    }
+    let test = matches!(args.command, Some(Command::Test));
+    if args.watch {
+        return watch(&args, test);
+    }
+
+    let built = build_or_check(&args, test, &mut Vec::new())?;
+
+    if let Some(code) = execute(&args, built) {
+        // Failing tests should fail the command e.g. in CI
+        if test {
+            process::exit(code);
+        }
+    }
+
+    cprintln!("<g,bold>Done!</>");
+    Ok(())
+}
+
+/// What building produced
+pub enum Built {
+    /// An executable at the output path
+    Executable,
+    /// A checked program to run with the interpreter
+    Interpreted(Box<Program>),
+}
+
+/// Builds the input file into an executable, or only checks it if interpreting. `files` is set to
+/// the paths of the source files used, even if building fails
+pub fn build_or_check(args: &Args, test: bool, files: &mut Vec<String>) -> Result<Built, String> {
+    if args.interpret {
+        check(args, &args.input, test, files).map(|p| Built::Interpreted(Box::new(p)))
+    } else {
+        build(args, &args.input, &args.output, test, files).map(|()| Built::Executable)
+    }
+}
+
+/// Runs what was built unless only building. Returns the code to exit with if it ran and failed
+pub fn execute(args: &Args, built: Built) -> Option<i32> {
+    if args.build {
+        println!("Skipping execution");
+        return None;
+    }
+
+    let code = match built {
+        Built::Executable => {
+            #[cfg(target_os = "linux")]
+            {
+                print_executing();
+                run(&args.output)
+            }
+            #[cfg(not(target_os = "linux"))]
+            {
+                println!("Not executing as OS is not Linux");
+                return None;
             }
         }
+        Built::Interpreted(mut program) => {
+            print_executing();
+            program.run()
+        }
+    };
+    match code {
+        Some(0) => None,
+        code => Some(code.unwrap_or(1)),
+    }
+}
+
+/// Prints the banner shown before running a program
+fn print_executing() {
+    let termsize::Size { rows: _, cols } = termsize::get().unwrap();
+    const EXECUTING: &str = "Executing";
+    if cols < EXECUTING.len() as u16 || cols > 300 {
+        cprintln!("<s><b>Executing...</>");
+    } else {
+        let padl = (cols - EXECUTING.len() as u16) / 2;
+        let padr = if ((cols - EXECUTING.len() as u16) % 2) == 1 {
+            padl + 1
+        } else {
+            padl
+        };
+        cprintln!(
+            "<s><b>{}{}{}</>",
+            "-".repeat(padl as usize),
+            EXECUTING,
+            "-".repeat(padr as usize)
+        );
     }
+}
+
+/// Writes a Markdown reference of everything available to the input file
+fn document(args: &Args) -> Result<(), String> {
+    let (global_table, unprocessed_functions, path_storage) =
+        parse_and_resolve(args, &args.input, false, &mut Vec::new())?;
+
+    print!("Writing Documentation... ");
+    time!(
+        let path = PathBuf::from(format!("{}.md", &args.output));
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(&path, generate_docs(&global_table, &unprocessed_functions, &path_storage))
+            .map_err(|e| e.to_string())?;
+    );
 
+    cprintln!("<g,bold>Done!</> Written to {}", path.display());
+    Ok(())
+}
+
+/// Parses `input` and the files it imports, then resolves their names. A main function is only
+/// required if `require_main` is set
+/// `files` is set to the paths of the files found, even if parsing fails
+pub fn parse_and_resolve(
+    args: &Args,
+    input: &str,
+    require_main: bool,
+    files: &mut Vec<String>,
+) -> Result<(GlobalTable, HashMap<FunctionID, FunctionToken>, PathStorage), String> {
     print!("Parsing files... ");
     time!(
-        let mut path_storage = PathStorage::new(&args.input).unwrap(); // TODO:
-        let toplevel_tokens = parse(&mut path_storage)
+        let mut path_storage = PathStorage::new(input, &args.search_paths()).unwrap(); // TODO:
+        *files = path_storage.file_paths();
+        if !args.no_prelude {
+            let prelude = args.prelude.as_deref().unwrap_or(DEFAULT_PRELUDE);
+            if let Err(tried) = path_storage.set_prelude(prelude) {
+                // Only error if the prelude was explicitly specified
+                if args.prelude.is_some() {
+                    return Err(WErr::locationless(ParseError::FailedToOpenFile(tried.join(", ")))
+                        .with_context(&path_storage)
+                        .to_string());
+                }
+            }
+        }
+        let toplevel_tokens = parse(&mut path_storage);
+        *files = path_storage.file_paths();
+        let toplevel_tokens = toplevel_tokens
             .map_err(|e| e.with_context(&path_storage).to_string())?;
     );
 
     print!("Resolving Names... ");
     time!(
-        let (global_table, unprocessed_functions) = resolve(toplevel_tokens, &path_storage)
+        let (global_table, unprocessed_functions) = resolve(toplevel_tokens, &path_storage, require_main)
         .map_err(|e| e.with_context(&path_storage).to_string())?;
     );
 
+    Ok((global_table, unprocessed_functions, path_storage))
+}
+
+/// Checks `input` by compiling it without writing any output, returning the program ready to be
+/// interpreted. `files` is set to the paths of the source files used, even if checking fails
+pub fn check(
+    args: &Args,
+    input: &str,
+    test: bool,
+    files: &mut Vec<String>,
+) -> Result<Program, String> {
+    let (mut global_table, unprocessed_functions, path_storage) =
+        parse_and_resolve(args, input, !test, files)?;
+    let runtime_checks = args.runtime_checks();
+
+    print!("Checking... ");
+    time!(
+        compile(
+            &mut global_table,
+            unprocessed_functions.clone(),
+            &path_storage,
+            runtime_checks,
+            test,
+        )
+            .map_err(|e| e.with_context(&path_storage).to_string())?;
+    );
+
+    Ok(Program::new(
+        global_table,
+        unprocessed_functions,
+        path_storage,
+        runtime_checks,
+        test,
+    ))
+}
+
+/// Builds `input` into an executable at `output` (without extension) using the options in `args`.
+/// Returns the formatted error if any step fails. `files` is set to the paths of the source files
+/// used, even if building fails
+pub fn build(
+    args: &Args,
+    input: &str,
+    output: &str,
+    test: bool,
+    files: &mut Vec<String>,
+) -> Result<(), String> {
+    if let Some(path) = PathBuf::from(output).parent() {
+        if let Err(e) = fs::create_dir_all(path) {
+            if !matches!(e.kind(), ErrorKind::AlreadyExists) {
+                cprintln!("<r,bold>Failed to create directories for output files</>");
+                panic!();
+            }
+        }
+    }
+
+    let (mut global_table, unprocessed_functions, path_storage) =
+        parse_and_resolve(args, input, !test, files)?;
+
     print!("Compiling... ");
     time!(
-        let assembly = compile(global_table, unprocessed_functions, &path_storage)
+        let assembly = compile(
+            &mut global_table,
+            unprocessed_functions,
+            &path_storage,
+            args.runtime_checks(),
+            test,
+        )
             .map_err(|e| e.with_context(&path_storage).to_string())?;
     );
 
     print!("Writing Assembly... ");
     time!(
-        fs::write(PathBuf::from(format!("{}.asm", &args.output)), assembly.as_bytes()).unwrap();
+        fs::write(PathBuf::from(format!("{}.asm", output)), assembly.as_bytes()).unwrap();
     );
 
     print!("Assembling (NASM)... ");
     time!(
-        assemble(&args.output).unwrap();
+        assemble(output).map_err(|_| "Assembling failed".to_string())?;
     );
 
     #[cfg(target_os = "linux")]
@@ -100,9 +444,9 @@ pub fn main_args(args: Args) -> Result<(), String> {
         print!("Linking (gcc)... ");
 
         let t = Instant::now();
-        link_gcc(&args.output).unwrap();
+        link_gcc(output).map_err(|_| "Linking failed".to_string())?;
         let end = t.elapsed();
-        let size = File::open(format!("{}.out", args.output))
+        let size = File::open(format!("{}.out", output))
             .unwrap()
             .metadata()
             .unwrap()
@@ -110,36 +454,11 @@ pub fn main_args(args: Args) -> Result<(), String> {
             .to_formatted_string(&Locale::en);
 
         cprintln!("<g,bold>Completed [{:?}] - {} bytes</>", end, size);
-
-        if args.build {
-            println!("Skipping execution")
-        } else {
-            let termsize::Size { rows: _, cols } = termsize::get().unwrap();
-            const EXECUTING: &str = "Executing";
-            if cols < EXECUTING.len() as u16 || cols > 300 {
-                cprintln!("<s><b>Executing...</>");
-            } else {
-                let padl = (cols - EXECUTING.len() as u16) / 2;
-                let padr = if ((cols - EXECUTING.len() as u16) % 2) == 1 {
-                    padl + 1
-                } else {
-                    padl
-                };
-                cprintln!(
-                    "<s><b>{}{}{}</>",
-                    "-".repeat(padl as usize),
-                    EXECUTING,
-                    "-".repeat(padr as usize)
-                );
-            }
-            run(&args.output);
-        }
     }
     #[cfg(not(target_os = "linux"))]
     {
-        println!("Not linking / executing as OS is not Linux");
+        println!("Not linking as OS is not Linux");
     }
 
-    cprintln!("<g,bold>Done!</>");
     Ok(())
 }
@@ -0,0 +1,68 @@
+use crate::root::assembler::assembly_builder::Assembly;
+use crate::root::builtin::types::bool::BoolType;
+use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator, InterpretFn};
+use crate::root::compiler::assembly::panic::panic_at;
+use crate::root::name_resolver::resolve_function_signatures::FunctionSignature;
+use crate::root::parser::parse_parameters::SelfType;
+use crate::root::shared::common::{FunctionID, LocalAddress, TypeID};
+use unique_type_id::UniqueTypeId;
+
+/// `assert` function panicking if its condition is false
+#[derive(UniqueTypeId)]
+#[UniqueTypeIdType = "u16"]
+pub struct AssertFunction;
+
+impl AssertFunction {
+    pub const fn id() -> FunctionID {
+        f_id(AssertFunction::unique_type_id().0)
+    }
+}
+
+impl BuiltinInlineFunction for AssertFunction {
+    fn id(&self) -> FunctionID {
+        Self::id()
+    }
+
+    fn name(&self) -> &'static str {
+        "assert"
+    }
+
+    fn signature(&self) -> FunctionSignature {
+        FunctionSignature::new_inline_builtin(
+            SelfType::None,
+            &[("condition", BoolType::id().immediate_single())],
+            None,
+        )
+    }
+
+    fn inline(&self) -> InlineFnGenerator {
+        |args: &[LocalAddress], _, gt, _| -> Assembly {
+            let condition = &args[0];
+            let jmp_passed = gt.get_unique_tag(Self::id());
+
+            let location = gt.call_location().clone();
+            let panic = panic_at(&location, "assertion failed", gt);
+
+            format!(
+                "    cmp byte {condition}, 0
+    jnz {jmp_passed}
+{panic}    {jmp_passed}:
+"
+            )
+        }
+    }
+
+    fn interpret(&self) -> InterpretFn {
+        |args, _, interpreter| {
+            if interpreter.bool(args[0])? {
+                Ok(())
+            } else {
+                Err(interpreter.panic("assertion failed"))
+            }
+        }
+    }
+
+    fn parent_type(&self) -> Option<TypeID> {
+        None
+    }
+}
@@ -1,6 +1,7 @@
 use crate::root::assembler::assembly_builder::Assembly;
 use crate::root::builtin::types::int::IntType;
-use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator};
+use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator, InterpretFn};
+use crate::root::interpreter::Halt;
 use crate::root::name_resolver::resolve_function_signatures::FunctionSignature;
 use crate::root::parser::parse_parameters::SelfType;
 use crate::root::shared::common::{FunctionID, LocalAddress, TypeID};
@@ -47,7 +48,15 @@ impl BuiltinInlineFunction for ExitFunction {
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |args, _, interpreter| Err(Halt::Exit(interpreter.int(args[0])?))
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         None
     }
+
+    fn diverges(&self) -> bool {
+        true
+    }
 }
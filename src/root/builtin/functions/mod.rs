@@ -1,7 +1,11 @@
+mod assert;
 mod exit;
+mod panic;
 mod printnl;
 
+use crate::root::builtin::functions::assert::AssertFunction;
 use crate::root::builtin::functions::exit::ExitFunction;
+use crate::root::builtin::functions::panic::PanicFunction;
 use crate::root::builtin::functions::printnl::PrintNL;
 use crate::root::name_resolver::name_resolvers::GlobalTable;
 
@@ -9,4 +13,6 @@ use crate::root::name_resolver::name_resolvers::GlobalTable;
 pub fn register_functions(global_table: &mut GlobalTable) {
     global_table.register_inline_function(&ExitFunction);
     global_table.register_inline_function(&PrintNL);
+    global_table.register_inline_function(&PanicFunction);
+    global_table.register_inline_function(&AssertFunction);
 }
@@ -0,0 +1,51 @@
+use crate::root::assembler::assembly_builder::Assembly;
+use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator, InterpretFn};
+use crate::root::compiler::assembly::panic::panic_at;
+use crate::root::name_resolver::resolve_function_signatures::FunctionSignature;
+use crate::root::parser::parse_parameters::SelfType;
+use crate::root::shared::common::{FunctionID, LocalAddress, TypeID};
+use unique_type_id::UniqueTypeId;
+
+/// `panic` function printing the location it was called from to stderr and exiting
+#[derive(UniqueTypeId)]
+#[UniqueTypeIdType = "u16"]
+pub struct PanicFunction;
+
+impl PanicFunction {
+    pub const fn id() -> FunctionID {
+        f_id(PanicFunction::unique_type_id().0)
+    }
+}
+
+impl BuiltinInlineFunction for PanicFunction {
+    fn id(&self) -> FunctionID {
+        Self::id()
+    }
+
+    fn name(&self) -> &'static str {
+        "panic"
+    }
+
+    fn signature(&self) -> FunctionSignature {
+        FunctionSignature::new_inline_builtin(SelfType::None, &[], None)
+    }
+
+    fn inline(&self) -> InlineFnGenerator {
+        |_: &[LocalAddress], _, gt, _| -> Assembly {
+            let location = gt.call_location().clone();
+            panic_at(&location, "explicit panic", gt)
+        }
+    }
+
+    fn interpret(&self) -> InterpretFn {
+        |_, _, interpreter| Err(interpreter.panic("explicit panic"))
+    }
+
+    fn parent_type(&self) -> Option<TypeID> {
+        None
+    }
+
+    fn diverges(&self) -> bool {
+        true
+    }
+}
@@ -1,5 +1,5 @@
 use crate::root::assembler::assembly_builder::Assembly;
-use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator};
+use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator, InterpretFn};
 use crate::root::name_resolver::resolve_function_signatures::FunctionSignature;
 use crate::root::parser::parse_parameters::SelfType;
 use crate::root::shared::common::{FunctionID, TypeID};
@@ -47,6 +47,13 @@ impl BuiltinInlineFunction for PrintNL {
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |_, _, interpreter| {
+            interpreter.print("\n");
+            Ok(())
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         None
     }
@@ -7,6 +7,7 @@ use crate::root::builtin::functions::register_functions;
 use crate::root::builtin::types::bool::register_bool;
 use crate::root::builtin::types::int::register_int;
 use crate::root::compiler::global_tracker::GlobalTracker;
+use crate::root::interpreter::{Halt, Interpreter};
 use crate::root::name_resolver::name_resolvers::GlobalTable;
 use crate::root::name_resolver::resolve_function_signatures::FunctionSignature;
 use crate::root::shared::common::{ByteSize, FunctionID, LocalAddress, TypeID};
@@ -22,6 +23,11 @@ pub fn register_builtin(global_table: &mut GlobalTable) {
 pub type InlineFnGenerator =
     fn(&[LocalAddress], Option<LocalAddress>, &mut GlobalTracker, ByteSize) -> Assembly;
 
+/// Function that executes a builtin within the interpreter, reading its arguments from and writing
+/// its return value to the current stack frame
+pub type InterpretFn =
+    fn(&[LocalAddress], Option<LocalAddress>, &mut Interpreter<'_>) -> Result<(), Halt>;
+
 /// Converts a u16 unique ID to a non-zero, negative `FunctionID`
 pub const fn f_id(id: u16) -> FunctionID {
     FunctionID(-(id as isize) - 1)
@@ -42,6 +48,12 @@ pub trait BuiltinInlineFunction {
     fn signature(&self) -> FunctionSignature;
     /// Generator for inline assembly code
     fn inline(&self) -> InlineFnGenerator;
+    /// Implementation used by the interpreter
+    fn interpret(&self) -> InterpretFn;
     /// Parent type
     fn parent_type(&self) -> Option<TypeID>;
+    /// Whether the function never returns e.g. `exit`
+    fn diverges(&self) -> bool {
+        false
+    }
 }
@@ -1,7 +1,7 @@
 use crate::root::assembler::assembly_builder::Assembly;
 use crate::root::builtin::types::bool::print_bool::PrintBool;
 use crate::root::builtin::types::bool::{boolean_signature, BoolType};
-use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator};
+use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator, InterpretFn};
 use crate::root::name_resolver::resolve_function_signatures::FunctionSignature;
 use crate::root::parser::parse_parameters::SelfType;
 use crate::root::shared::common::{FunctionID, LocalAddress, TypeID};
@@ -48,6 +48,14 @@ impl BuiltinInlineFunction for BoolAnd {
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |args, return_into, interpreter| {
+            let lhs = interpreter.bool(args[0])?;
+            let rhs = interpreter.bool(args[1])?;
+            interpreter.set_bool(return_into.unwrap(), lhs && rhs)
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         Some(BoolType::id())
     }
@@ -68,9 +76,9 @@ impl BuiltinInlineFunction for BoolAssignAnd {
 
     fn signature(&self) -> FunctionSignature {
         FunctionSignature::new_inline_builtin(
-            SelfType::RefSelf,
+            SelfType::MutRefSelf,
             &[
-                ("lhs", BoolType::id().with_indirection_single(1)),
+                ("lhs", BoolType::id().mutable_reference_single()),
                 ("rhs", BoolType::id().immediate_single()),
             ],
             None,
@@ -95,6 +103,15 @@ impl BuiltinInlineFunction for BoolAssignAnd {
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |args, _, interpreter| {
+            let lhs = interpreter.pointer(args[0])?;
+            let value = interpreter.bool_at(lhs)?;
+            let rhs = interpreter.bool(args[1])?;
+            interpreter.set_bool_at(lhs, value && rhs)
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         Some(BoolType::id())
     }
@@ -1,6 +1,6 @@
 use crate::root::assembler::assembly_builder::Assembly;
 use crate::root::builtin::types::bool::{boolean_signature, BoolType};
-use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator};
+use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator, InterpretFn};
 use crate::root::name_resolver::resolve_function_signatures::FunctionSignature;
 use crate::root::shared::common::{FunctionID, LocalAddress, TypeID};
 use unique_type_id::UniqueTypeId;
@@ -56,6 +56,14 @@ impl BuiltinInlineFunction for BoolEqual {
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |args, return_into, interpreter| {
+            let lhs = interpreter.bool(args[0])?;
+            let rhs = interpreter.bool(args[1])?;
+            interpreter.set_bool(return_into.unwrap(), lhs == rhs)
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         Some(BoolType::id())
     }
@@ -112,6 +120,14 @@ impl BuiltinInlineFunction for BoolNotEqual {
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |args, return_into, interpreter| {
+            let lhs = interpreter.bool(args[0])?;
+            let rhs = interpreter.bool(args[1])?;
+            interpreter.set_bool(return_into.unwrap(), lhs != rhs)
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         Some(BoolType::id())
     }
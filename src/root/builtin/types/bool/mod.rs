@@ -5,6 +5,7 @@ use crate::root::builtin::types::bool::comparators::{BoolEqual, BoolNotEqual};
 use crate::root::builtin::types::bool::not::BoolNot;
 use crate::root::builtin::types::bool::or::{BoolAssignOr, BoolOr};
 use crate::root::builtin::types::bool::print_bool::PrintBool;
+use crate::root::errors::evaluable_errors::EvalErrs;
 use crate::root::errors::WErr;
 use crate::root::name_resolver::name_resolvers::GlobalTable;
 use crate::root::name_resolver::resolve_function_signatures::FunctionSignature;
@@ -78,21 +79,28 @@ impl Type for BoolType {
         location: &LocalAddress,
         literal: &LiteralToken,
     ) -> Result<Assembly, WErr> {
-        Ok(match literal.literal() {
-            LiteralTokens::Bool(value) => {
-                if *value {
-                    format!("    mov byte {location}, 1\n")
-                } else {
-                    format!("    mov byte {location}, 0\n")
-                }
-            }
-            LiteralTokens::Int(value) => {
-                if *value == 0 {
-                    format!("    mov byte {location}, 0\n")
-                } else {
-                    format!("    mov byte {location}, 1\n")
-                }
-            }
+        Ok(if literal_value(literal)? {
+            format!("    mov byte {location}, 1\n")
+        } else {
+            format!("    mov byte {location}, 0\n")
         })
     }
+
+    fn literal_bytes(&self, literal: &LiteralToken) -> Result<Vec<u8>, WErr> {
+        Ok(vec![literal_value(literal)? as u8])
+    }
+}
+
+/// Returns the value of a boolean created from a literal
+fn literal_value(literal: &LiteralToken) -> Result<bool, WErr> {
+    Ok(match literal.literal() {
+        LiteralTokens::Bool(value) => *value,
+        LiteralTokens::Int(value) => *value != 0,
+        LiteralTokens::Null => {
+            return WErr::ne(
+                EvalErrs::NullNotReference(BoolType.name().to_string()),
+                literal.location().clone(),
+            );
+        }
+    })
 }
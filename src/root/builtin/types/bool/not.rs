@@ -1,7 +1,7 @@
 use crate::root::assembler::assembly_builder::Assembly;
 use crate::root::builtin::types::bool::print_bool::PrintBool;
 use crate::root::builtin::types::bool::BoolType;
-use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator};
+use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator, InterpretFn};
 use crate::root::name_resolver::resolve_function_signatures::FunctionSignature;
 use crate::root::parser::parse_parameters::SelfType;
 use crate::root::shared::common::{FunctionID, LocalAddress, TypeID};
@@ -50,6 +50,13 @@ impl BuiltinInlineFunction for BoolNot {
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |args, return_into, interpreter| {
+            let lhs = interpreter.bool(args[0])?;
+            interpreter.set_bool(return_into.unwrap(), !lhs)
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         Some(BoolType::id())
     }
@@ -1,6 +1,6 @@
 use crate::root::assembler::assembly_builder::Assembly;
 use crate::root::builtin::types::bool::BoolType;
-use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator};
+use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator, InterpretFn};
 use crate::root::name_resolver::resolve_function_signatures::FunctionSignature;
 use crate::root::parser::parse_parameters::SelfType;
 use crate::root::shared::common::{FunctionID, LocalAddress, TypeID};
@@ -69,6 +69,18 @@ impl BuiltinInlineFunction for PrintBool {
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |args, _, interpreter| {
+            let text = if interpreter.bool(args[0])? {
+                "Boolean: True\n"
+            } else {
+                "Boolean: False\n"
+            };
+            interpreter.print(text);
+            Ok(())
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         None
     }
@@ -1,6 +1,7 @@
 use crate::root::assembler::assembly_builder::Assembly;
 use crate::root::builtin::types::int::IntType;
-use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator};
+use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator, InterpretFn};
+use crate::root::compiler::assembly::panic::overflow_check;
 use crate::root::name_resolver::resolve_function_signatures::FunctionSignature;
 use crate::root::parser::parse_parameters::SelfType;
 use crate::root::shared::common::{FunctionID, LocalAddress, TypeID};
@@ -32,18 +33,28 @@ impl BuiltinInlineFunction for IntAddition {
     }
 
     fn inline(&self) -> InlineFnGenerator {
-        |args: &[LocalAddress], return_into: Option<LocalAddress>, _, _| -> Assembly {
+        |args: &[LocalAddress], return_into: Option<LocalAddress>, gt, _| -> Assembly {
             let lhs = args[0];
             let rhs = args[1];
             let return_into = return_into.unwrap();
+            let check = overflow_check("add", gt);
             format!(
                 "    mov rax, qword {lhs}
     add rax, qword {rhs}
-    mov qword {return_into}, rax\n"
+{check}    mov qword {return_into}, rax\n"
             )
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |args, return_into, interpreter| {
+            let lhs = interpreter.int(args[0])?;
+            let rhs = interpreter.int(args[1])?;
+            let result = interpreter.arithmetic("add", lhs.overflowing_add(rhs))?;
+            interpreter.set_int(return_into.unwrap(), result)
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         Some(IntType::id())
     }
@@ -65,9 +76,9 @@ impl BuiltinInlineFunction for IntAssignAddition {
 
     fn signature(&self) -> FunctionSignature {
         FunctionSignature::new_inline_builtin(
-            SelfType::RefSelf,
+            SelfType::MutRefSelf,
             &[
-                ("lhs", IntType::id().with_indirection_single(1)),
+                ("lhs", IntType::id().mutable_reference_single()),
                 ("rhs", IntType::id().immediate_single()),
             ],
             None,
@@ -75,17 +86,29 @@ impl BuiltinInlineFunction for IntAssignAddition {
     }
 
     fn inline(&self) -> InlineFnGenerator {
-        |args: &[LocalAddress], _, _, _| -> Assembly {
+        |args: &[LocalAddress], _, gt, _| -> Assembly {
             let lhs = args[0];
             let rhs = args[1];
+            let check = overflow_check("add", gt);
             format!(
                 "    mov rax, qword {lhs}
     mov rdx, qword {rhs}
-    add qword [rax], rdx\n"
+    add qword [rax], rdx
+{check}"
             )
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |args, _, interpreter| {
+            let lhs = interpreter.pointer(args[0])?;
+            let value = interpreter.int_at(lhs)?;
+            let rhs = interpreter.int(args[1])?;
+            let result = interpreter.arithmetic("add", value.overflowing_add(rhs))?;
+            interpreter.set_int_at(lhs, result)
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         Some(IntType::id())
     }
@@ -1,7 +1,7 @@
 use crate::root::assembler::assembly_builder::Assembly;
 use crate::root::builtin::types::bool::BoolType;
 use crate::root::builtin::types::int::IntType;
-use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator};
+use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator, InterpretFn};
 use crate::root::name_resolver::resolve_function_signatures::FunctionSignature;
 use crate::root::parser::parse_parameters::SelfType;
 use crate::root::shared::common::{FunctionID, LocalAddress, TypeID};
@@ -59,6 +59,14 @@ impl BuiltinInlineFunction for IntEqual {
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |args, return_into, interpreter| {
+            let lhs = interpreter.int(args[0])?;
+            let rhs = interpreter.int(args[1])?;
+            interpreter.set_bool(return_into.unwrap(), lhs == rhs)
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         Some(IntType::id())
     }
@@ -116,6 +124,14 @@ impl BuiltinInlineFunction for IntNotEqual {
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |args, return_into, interpreter| {
+            let lhs = interpreter.int(args[0])?;
+            let rhs = interpreter.int(args[1])?;
+            interpreter.set_bool(return_into.unwrap(), lhs != rhs)
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         Some(IntType::id())
     }
@@ -173,6 +189,14 @@ impl BuiltinInlineFunction for IntGreaterThan {
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |args, return_into, interpreter| {
+            let lhs = interpreter.int(args[0])?;
+            let rhs = interpreter.int(args[1])?;
+            interpreter.set_bool(return_into.unwrap(), lhs > rhs)
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         Some(IntType::id())
     }
@@ -230,6 +254,14 @@ impl BuiltinInlineFunction for IntLessThan {
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |args, return_into, interpreter| {
+            let lhs = interpreter.int(args[0])?;
+            let rhs = interpreter.int(args[1])?;
+            interpreter.set_bool(return_into.unwrap(), lhs < rhs)
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         Some(IntType::id())
     }
@@ -287,6 +319,14 @@ impl BuiltinInlineFunction for IntGreaterThanEqual {
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |args, return_into, interpreter| {
+            let lhs = interpreter.int(args[0])?;
+            let rhs = interpreter.int(args[1])?;
+            interpreter.set_bool(return_into.unwrap(), lhs >= rhs)
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         Some(IntType::id())
     }
@@ -344,6 +384,14 @@ impl BuiltinInlineFunction for IntLessThanEqual {
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |args, return_into, interpreter| {
+            let lhs = interpreter.int(args[0])?;
+            let rhs = interpreter.int(args[1])?;
+            interpreter.set_bool(return_into.unwrap(), lhs <= rhs)
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         Some(IntType::id())
     }
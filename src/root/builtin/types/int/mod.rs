@@ -13,7 +13,9 @@ use crate::root::builtin::types::int::print_int::PrintInt;
 use crate::root::builtin::types::int::subtract::IntSubtract;
 use crate::root::compiler::assembly::utils::write_64bit_int;
 use crate::root::errors::compiler_errors::CompErrs;
+use crate::root::errors::evaluable_errors::EvalErrs;
 use crate::root::errors::WErr;
+use crate::root::interpreter::{Halt, Interpreter};
 use crate::root::name_resolver::name_resolvers::GlobalTable;
 use crate::root::parser::parse_function::parse_literal::{LiteralToken, LiteralTokens};
 use crate::root::shared::common::{ByteSize, LocalAddress, TypeID};
@@ -92,32 +94,64 @@ impl Type for IntType {
         location: &LocalAddress,
         literal: &LiteralToken,
     ) -> Result<Assembly, WErr> {
-        Ok(match literal.literal() {
-            LiteralTokens::Bool(value) => {
-                if *value {
-                    format!("    mov qword {location}, 0\n")
-                } else {
-                    format!("    mov qword {location}, 1\n")
-                }
-            }
-            LiteralTokens::Int(value) => {
-                if *value > i64::MAX as i128 {
-                    return WErr::ne(
-                        CompErrs::IntLiteralExceedsMax(*value, i64::MAX as i128),
-                        literal.location().clone(),
-                    );
-                }
-                if *value < i64::MIN as i128 {
-                    return WErr::ne(
-                        CompErrs::IntLiteralBelowMin(*value, i64::MAX as i128),
-                        literal.location().clone(),
-                    );
-                }
+        Ok(write_64bit_int(literal_value(literal)?, location))
+    }
 
-                let value = *value as i64;
+    fn literal_bytes(&self, literal: &LiteralToken) -> Result<Vec<u8>, WErr> {
+        Ok(literal_value(literal)?.to_le_bytes().to_vec())
+    }
+}
 
-                write_64bit_int(value, location)
+/// Returns the value of an integer created from a literal
+fn literal_value(literal: &LiteralToken) -> Result<i64, WErr> {
+    Ok(match literal.literal() {
+        LiteralTokens::Bool(value) => {
+            if *value {
+                0
+            } else {
+                1
+            }
+        }
+        LiteralTokens::Int(value) => {
+            if *value > i64::MAX as i128 {
+                return WErr::ne(
+                    CompErrs::IntLiteralExceedsMax(*value, i64::MAX as i128),
+                    literal.location().clone(),
+                );
             }
-        })
+            if *value < i64::MIN as i128 {
+                return WErr::ne(
+                    CompErrs::IntLiteralBelowMin(*value, i64::MAX as i128),
+                    literal.location().clone(),
+                );
+            }
+
+            *value as i64
+        }
+        LiteralTokens::Null => {
+            return WErr::ne(
+                EvalErrs::NullNotReference(IntType.name().to_string()),
+                literal.location().clone(),
+            );
+        }
+    })
+}
+
+/// Divides `lhs` by `rhs` using `divide` e.g. `i64::overflowing_div` within the interpreter.
+/// Division by zero and overflow always panic, even without arithmetic checks, as the processor
+/// traps on both
+pub fn interpret_division(
+    interpreter: &Interpreter,
+    operation: &str,
+    lhs: i64,
+    rhs: i64,
+    divide: fn(i64, i64) -> (i64, bool),
+) -> Result<i64, Halt> {
+    if rhs == 0 {
+        return Err(interpreter.panic(&format!("attempt to {operation} by zero")));
+    }
+    match divide(lhs, rhs) {
+        (_, true) => Err(interpreter.panic(&format!("attempt to {operation} with overflow"))),
+        (result, false) => Ok(result),
     }
 }
@@ -1,6 +1,7 @@
 use crate::root::assembler::assembly_builder::Assembly;
-use crate::root::builtin::types::int::IntType;
-use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator};
+use crate::root::builtin::types::int::{interpret_division, IntType};
+use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator, InterpretFn};
+use crate::root::compiler::assembly::panic::division_check;
 use crate::root::name_resolver::resolve_function_signatures::FunctionSignature;
 use crate::root::parser::parse_parameters::SelfType;
 use crate::root::shared::common::{FunctionID, LocalAddress, TypeID};
@@ -32,20 +33,30 @@ impl BuiltinInlineFunction for IntModulo {
     }
 
     fn inline(&self) -> InlineFnGenerator {
-        |args: &[LocalAddress], return_into: Option<LocalAddress>, _, _| -> Assembly {
+        |args: &[LocalAddress], return_into: Option<LocalAddress>, gt, _| -> Assembly {
             let lhs = args[0];
             let rhs = args[1];
             let return_into = return_into.unwrap();
+            let check = division_check("rbx", "calculate the remainder", gt);
             format!(
                 "    mov rax, qword {lhs}
-    mov rdx, 0
+    cqo
     mov rbx, qword {rhs}
-    idiv rbx
+{check}    idiv rbx
     mov qword {return_into}, rdx\n"
             )
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |args, return_into, interpreter| {
+            let lhs = interpreter.int(args[0])?;
+            let rhs = interpreter.int(args[1])?;
+            let result = interpret_division(interpreter, "calculate the remainder", lhs, rhs, i64::overflowing_rem)?;
+            interpreter.set_int(return_into.unwrap(), result)
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         Some(IntType::id())
     }
@@ -67,9 +78,9 @@ impl BuiltinInlineFunction for IntAssignModulo {
 
     fn signature(&self) -> FunctionSignature {
         FunctionSignature::new_inline_builtin(
-            SelfType::RefSelf,
+            SelfType::MutRefSelf,
             &[
-                ("lhs", IntType::id().with_indirection_single(1)),
+                ("lhs", IntType::id().mutable_reference_single()),
                 ("rhs", IntType::id().immediate_single()),
             ],
             None,
@@ -77,20 +88,31 @@ impl BuiltinInlineFunction for IntAssignModulo {
     }
 
     fn inline(&self) -> InlineFnGenerator {
-        |args: &[LocalAddress], _, _, _| -> Assembly {
+        |args: &[LocalAddress], _, gt, _| -> Assembly {
             let lhs = args[0];
             let rhs = args[1];
+            let check = division_check("rbx", "calculate the remainder", gt);
             format!(
                 "    mov rcx, qword {lhs}
     mov rax, qword [rcx]
-    mov rdx, 0
+    cqo
     mov rbx, qword {rhs}
-    idiv rbx
+{check}    idiv rbx
     mov qword [rcx], rdx\n"
             )
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |args, _, interpreter| {
+            let lhs = interpreter.pointer(args[0])?;
+            let value = interpreter.int_at(lhs)?;
+            let rhs = interpreter.int(args[1])?;
+            let result = interpret_division(interpreter, "calculate the remainder", value, rhs, i64::overflowing_rem)?;
+            interpreter.set_int_at(lhs, result)
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         Some(IntType::id())
     }
@@ -1,6 +1,7 @@
 use crate::root::assembler::assembly_builder::Assembly;
 use crate::root::builtin::types::int::IntType;
-use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator};
+use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator, InterpretFn};
+use crate::root::compiler::assembly::panic::overflow_check;
 use crate::root::name_resolver::resolve_function_signatures::FunctionSignature;
 use crate::root::parser::parse_parameters::SelfType;
 use crate::root::shared::common::{FunctionID, LocalAddress, TypeID};
@@ -32,19 +33,29 @@ impl BuiltinInlineFunction for IntMultiply {
     }
 
     fn inline(&self) -> InlineFnGenerator {
-        |args: &[LocalAddress], return_into: Option<LocalAddress>, _, _| -> Assembly {
+        |args: &[LocalAddress], return_into: Option<LocalAddress>, gt, _| -> Assembly {
             let lhs = args[0];
             let rhs = args[1];
             let return_into = return_into.unwrap();
+            let check = overflow_check("multiply", gt);
             format!(
                 "    mov rax, qword {lhs}
     mov rdx, qword {rhs}
     imul rdx
-    mov qword {return_into}, rax\n"
+{check}    mov qword {return_into}, rax\n"
             )
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |args, return_into, interpreter| {
+            let lhs = interpreter.int(args[0])?;
+            let rhs = interpreter.int(args[1])?;
+            let result = interpreter.arithmetic("multiply", lhs.overflowing_mul(rhs))?;
+            interpreter.set_int(return_into.unwrap(), result)
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         Some(IntType::id())
     }
@@ -66,9 +77,9 @@ impl BuiltinInlineFunction for IntAssignMultiply {
 
     fn signature(&self) -> FunctionSignature {
         FunctionSignature::new_inline_builtin(
-            SelfType::RefSelf,
+            SelfType::MutRefSelf,
             &[
-                ("lhs", IntType::id().with_indirection_single(1)),
+                ("lhs", IntType::id().mutable_reference_single()),
                 ("rhs", IntType::id().immediate_single()),
             ],
             None,
@@ -76,19 +87,30 @@ impl BuiltinInlineFunction for IntAssignMultiply {
     }
 
     fn inline(&self) -> InlineFnGenerator {
-        |args: &[LocalAddress], _, _, _| -> Assembly {
+        |args: &[LocalAddress], _, gt, _| -> Assembly {
             let lhs = args[0];
             let rhs = args[1];
+            let check = overflow_check("multiply", gt);
             format!(
                 "    mov rcx, qword {lhs}
     mov rax, qword [rcx]
     mov rdx, qword {rhs}
     imul rdx
-    mov qword [rcx], rax\n"
+{check}    mov qword [rcx], rax\n"
             )
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |args, _, interpreter| {
+            let lhs = interpreter.pointer(args[0])?;
+            let value = interpreter.int_at(lhs)?;
+            let rhs = interpreter.int(args[1])?;
+            let result = interpreter.arithmetic("multiply", value.overflowing_mul(rhs))?;
+            interpreter.set_int_at(lhs, result)
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         Some(IntType::id())
     }
@@ -1,6 +1,6 @@
 use crate::root::assembler::assembly_builder::Assembly;
 use crate::root::builtin::types::int::IntType;
-use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator};
+use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator, InterpretFn};
 use crate::root::name_resolver::resolve_function_signatures::FunctionSignature;
 use crate::root::parser::parse_parameters::SelfType;
 use crate::root::shared::common::{FunctionID, LocalAddress, TypeID};
@@ -56,6 +56,14 @@ impl BuiltinInlineFunction for PrintInt {
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |args, _, interpreter| {
+            let lhs = interpreter.int(args[0])?;
+            interpreter.print(&format!("Integer: {lhs}\n"));
+            Ok(())
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         None
     }
@@ -0,0 +1,221 @@
+use crate::root::compiler::global_tracker::GlobalTracker;
+use crate::root::parser::location::Location;
+use crate::root::parser::parse_function::parse_evaluable::{EvaluableToken, EvaluableTokens};
+use crate::root::parser::parse_function::parse_line::LineTokens;
+use crate::root::parser::parse_function::parse_literal::LiteralTokens;
+use crate::root::utils::warn;
+
+/// A node in a `ControlFlowGraph` - a line or a condition
+struct CfgNode {
+    location: Option<Location>,
+    successors: Vec<usize>,
+}
+
+/// Control-flow graph over the lines of a function body
+pub struct ControlFlowGraph {
+    nodes: Vec<CfgNode>,
+    /// The nodes of the lines in each block, in order
+    blocks: Vec<Vec<usize>>,
+    reachable: Vec<bool>,
+}
+
+/// Entry node of the function
+const ENTRY: usize = 0;
+/// Node reached by falling off the end of the function without returning
+const END: usize = 1;
+
+impl ControlFlowGraph {
+    /// Builds a `ControlFlowGraph` from the lines of a function. `diverges` should return whether
+    /// evaluating the evaluable never returns e.g. a call to `exit`
+    pub fn new(
+        lines: &[LineTokens],
+        mut diverges: impl FnMut(&EvaluableToken) -> bool,
+    ) -> ControlFlowGraph {
+        let mut cfg = ControlFlowGraph {
+            nodes: Vec::new(),
+            blocks: Vec::new(),
+            reachable: Vec::new(),
+        };
+        cfg.add_node(None);
+        cfg.add_node(None);
+
+        let ends = cfg.build_lines(lines, vec![ENTRY], &mut None, &mut diverges);
+        cfg.connect(&ends, END);
+
+        cfg.reachable = cfg.find_reachable();
+        cfg
+    }
+
+    fn add_node(&mut self, location: Option<Location>) -> usize {
+        self.nodes.push(CfgNode {
+            location,
+            successors: Vec::new(),
+        });
+        self.nodes.len() - 1
+    }
+
+    fn connect(&mut self, from: &[usize], to: usize) {
+        for f in from {
+            self.nodes[*f].successors.push(to);
+        }
+    }
+
+    /// Adds nodes for `lines` following from the nodes in `preds`. Returns the nodes that flow on to
+    /// whatever follows the lines. `breaks` collects nodes that break out of the innermost loop
+    fn build_lines(
+        &mut self,
+        lines: &[LineTokens],
+        preds: Vec<usize>,
+        breaks: &mut Option<Vec<usize>>,
+        diverges: &mut impl FnMut(&EvaluableToken) -> bool,
+    ) -> Vec<usize> {
+        let mut preds = preds;
+        let mut block = Vec::with_capacity(lines.len());
+
+        for line in lines {
+            let node = self.add_node(line_location(line));
+            self.connect(&preds, node);
+            block.push(node);
+
+            preds = match line {
+                LineTokens::Initialisation(_) => vec![node],
+                LineTokens::NoOp(et) => {
+                    if diverges(et) {
+                        vec![]
+                    } else {
+                        vec![node]
+                    }
+                }
+                LineTokens::Return(_) => vec![],
+                LineTokens::Break(_) => {
+                    // Break outside of a loop is reported during compilation
+                    if let Some(breaks) = breaks {
+                        breaks.push(node);
+                    }
+                    vec![]
+                }
+                LineTokens::If(if_token) => {
+                    let mut ends =
+                        self.build_lines(if_token.if_contents(), vec![node], breaks, diverges);
+
+                    // Each condition is only evaluated if the previous one failed
+                    let mut condition = node;
+                    for (elif_condition, elif_contents) in if_token.elif_condition_contents() {
+                        let elif_node = self.add_node(Some(elif_condition.location().clone()));
+                        self.connect(&[condition], elif_node);
+                        condition = elif_node;
+                        ends.extend(self.build_lines(
+                            elif_contents,
+                            vec![elif_node],
+                            breaks,
+                            diverges,
+                        ));
+                    }
+
+                    if let Some(else_contents) = if_token.else_contents() {
+                        ends.extend(self.build_lines(
+                            else_contents,
+                            vec![condition],
+                            breaks,
+                            diverges,
+                        ));
+                    } else {
+                        ends.push(condition);
+                    }
+                    ends
+                }
+                LineTokens::While(while_token) => {
+                    let mut loop_breaks = Some(Vec::new());
+                    let body_ends = self.build_lines(
+                        while_token.contents(),
+                        vec![node],
+                        &mut loop_breaks,
+                        diverges,
+                    );
+                    // Loop back to the condition
+                    self.connect(&body_ends, node);
+
+                    let mut ends = loop_breaks.unwrap();
+                    if !is_literal_true(while_token.condition()) {
+                        ends.push(node);
+                    }
+                    ends
+                }
+                #[cfg(debug_assertions)]
+                LineTokens::Marker(_) => vec![node],
+            };
+        }
+
+        self.blocks.push(block);
+        preds
+    }
+
+    fn find_reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.nodes.len()];
+        let mut to_visit = vec![ENTRY];
+        while let Some(node) = to_visit.pop() {
+            if reachable[node] {
+                continue;
+            }
+            reachable[node] = true;
+            to_visit.extend(self.nodes[node].successors.iter().copied());
+        }
+        reachable
+    }
+
+    /// Whether every path through the function ends in a return (or never ends)
+    pub fn all_paths_return(&self) -> bool {
+        !self.reachable[END]
+    }
+
+    /// Returns the location of the first line of each section of code that can never be run
+    pub fn unreachable_lines(&self) -> Vec<Location> {
+        let mut unreachable = Vec::new();
+        for block in &self.blocks {
+            // Lines are only reported where the preceding line is reachable so that each
+            // unreachable section is reported once
+            for (previous, node) in block.iter().zip(block.iter().skip(1)) {
+                if self.reachable[*previous] && !self.reachable[*node] {
+                    if let Some(location) = &self.nodes[*node].location {
+                        unreachable.push(location.clone());
+                    }
+                }
+            }
+        }
+        unreachable
+    }
+
+    /// Prints a warning for each section of code that can never be run
+    pub fn warn_unreachable(&self, global_tracker: &GlobalTracker) {
+        for location in self.unreachable_lines() {
+            warn(&format!(
+                "Unreachable code. This line and those following it in the block will never be run.\n{}",
+                location
+                    .into_warning()
+                    .with_context(global_tracker.path_storage())
+            ));
+        }
+    }
+}
+
+/// Returns the location of a line, if it has one
+fn line_location(line: &LineTokens) -> Option<Location> {
+    Some(
+        match line {
+            LineTokens::Initialisation(it) => it.location(),
+            LineTokens::If(it) => it.location(),
+            LineTokens::While(wt) => wt.location(),
+            LineTokens::Return(rt) => rt.location(),
+            LineTokens::Break(bt) => bt.location(),
+            LineTokens::NoOp(et) => et.location(),
+            #[cfg(debug_assertions)]
+            LineTokens::Marker(_) => return None,
+        }
+        .clone(),
+    )
+}
+
+/// Whether an evaluable is the literal `true`
+pub fn is_literal_true(et: &EvaluableToken) -> bool {
+    matches!(et.token(), EvaluableTokens::Literal(literal) if matches!(literal.literal(), LiteralTokens::Bool(true)))
+}
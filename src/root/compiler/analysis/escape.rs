@@ -0,0 +1,446 @@
+use std::collections::HashMap;
+
+use crate::root::compiler::evaluation::mutability::root_variable;
+use crate::root::errors::compiler_errors::CompErrs;
+use crate::root::errors::WErr;
+use crate::root::name_resolver::name_resolvers::GlobalTable;
+use crate::root::parser::location::Location;
+use crate::root::parser::parse_function::parse_block::BlockToken;
+use crate::root::parser::parse_function::parse_evaluable::{EvaluableToken, EvaluableTokens};
+use crate::root::parser::parse_function::parse_line::LineTokens;
+use crate::root::parser::parse_function::parse_operator::OperatorTokens;
+use crate::root::parser::parse_parameters::Parameters;
+use crate::root::shared::common::TypeRef;
+
+/// The references to values on the stack a value may hold, identified by where each reference is
+/// created
+#[derive(Clone, Default)]
+struct Taint {
+    /// References the value is, or contains
+    refs: Vec<Location>,
+    /// References reachable by dereferencing the value once
+    deref: Vec<Location>,
+}
+
+impl Taint {
+    /// Adds the references in `other`. Returns whether any were new
+    fn union(&mut self, other: &Taint) -> bool {
+        let refs = add_all(&mut self.refs, &other.refs);
+        add_all(&mut self.deref, &other.deref) || refs
+    }
+}
+
+/// Adds locations not already present in `into`. Returns whether any were new
+fn add_all(into: &mut Vec<Location>, from: &[Location]) -> bool {
+    let mut changed = false;
+    for location in from {
+        if !into.contains(location) {
+            into.push(location.clone());
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// A local variable or parameter
+struct Variable {
+    type_ref: Option<TypeRef>,
+    taint: Taint,
+}
+
+/// How a reference to a value on the stack outlives its function
+enum Escape {
+    Returned,
+    Heap,
+    Outside,
+}
+
+impl Escape {
+    fn describe(&self) -> &'static str {
+        match self {
+            Escape::Returned => "The reference escapes the function here by being returned",
+            Escape::Heap => "The reference escapes the function here by being stored on the heap",
+            Escape::Outside => {
+                "The reference escapes the function here by being stored outside of its stack frame"
+            }
+        }
+    }
+}
+
+/// Flow-insensitive pass tracking where references to the stack (created with `&` or by
+/// accessing the attribute of a value on the stack) flow within a function
+struct EscapeChecker<'a> {
+    global_table: &'a mut GlobalTable,
+    variables: Vec<Variable>,
+    scopes: Vec<HashMap<String, usize>>,
+    /// Number of variables declared so far in the current pass
+    declared: usize,
+    /// Whether any variable gained a reference in the current pass
+    changed: bool,
+    /// The first escape found in the current pass - the reference, where it escapes and how
+    escape: Option<(Location, Location, Escape)>,
+    /// Whether the function's return type can hold a reference
+    return_holds_reference: bool,
+}
+
+/// Errors if a reference to a value in the function's stack frame may be returned or stored
+/// somewhere that outlives the frame (e.g. the heap or memory belonging to the caller)
+pub fn check_escapes(
+    parameters: &Parameters,
+    return_type: Option<&TypeRef>,
+    lines: &[LineTokens],
+    global_table: &mut GlobalTable,
+) -> Result<(), WErr> {
+    let return_holds_reference = return_type.is_some_and(|t| may_hold_reference(t, global_table));
+
+    let mut checker = EscapeChecker {
+        global_table,
+        variables: Vec::new(),
+        scopes: Vec::new(),
+        declared: 0,
+        changed: false,
+        escape: None,
+        return_holds_reference,
+    };
+
+    // References may flow between variables in any order (e.g. in loops) so propagate until the
+    // references held by variables stop changing
+    loop {
+        checker.changed = false;
+        checker.declared = 0;
+        checker.escape = None;
+        checker.scopes = vec![HashMap::new()];
+
+        for (name, type_name) in parameters {
+            let type_ref = checker.global_table.resolve_to_type_ref(type_name, None)?;
+            checker.declare(name.name(), Some(type_ref));
+        }
+        checker.check_lines(lines)?;
+
+        if !checker.changed {
+            break;
+        }
+    }
+
+    if let Some((reference, escape, how)) = checker.escape {
+        return Err(
+            WErr::n(CompErrs::ReferenceEscapes, reference).with_note(how.describe(), escape)
+        );
+    }
+    Ok(())
+}
+
+/// Whether a value of a type can contain a reference
+fn may_hold_reference(type_ref: &TypeRef, global_table: &GlobalTable) -> bool {
+    if type_ref.indirection().has_indirection() {
+        return true;
+    }
+    global_table
+        .get_type(*type_ref.type_id())
+        .get_attributes(&Location::builtin())
+        .is_ok_and(|attributes| {
+            attributes
+                .iter()
+                .any(|(_, _, t)| may_hold_reference(t, global_table))
+        })
+}
+
+impl EscapeChecker<'_> {
+    /// Declares a variable in the current scope, reusing its state from previous passes
+    fn declare(&mut self, name: &str, type_ref: Option<TypeRef>) -> usize {
+        let index = self.declared;
+        self.declared += 1;
+        if index == self.variables.len() {
+            self.variables.push(Variable {
+                type_ref,
+                taint: Taint::default(),
+            });
+        }
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(name.to_string(), index);
+        index
+    }
+
+    /// Finds the variable a name refers to
+    fn lookup(&self, name: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .copied()
+    }
+
+    fn add_taint(&mut self, variable: usize, taint: &Taint) {
+        if self.variables[variable].taint.union(taint) {
+            self.changed = true;
+        }
+    }
+
+    fn record_escape(&mut self, reference: &Location, location: &Location, how: Escape) {
+        if self.escape.is_none() {
+            self.escape = Some((reference.clone(), location.clone(), how));
+        }
+    }
+
+    fn check_lines(&mut self, lines: &[LineTokens]) -> Result<(), WErr> {
+        self.scopes.push(HashMap::new());
+        let result = self.check_lines_in_scope(lines);
+        self.scopes.pop();
+        result
+    }
+
+    fn check_lines_in_scope(&mut self, lines: &[LineTokens]) -> Result<(), WErr> {
+        for line in lines {
+            match line {
+                LineTokens::Initialisation(it) => {
+                    let taint = if let Some(value) = it.value() {
+                        self.taint(value)?
+                    } else {
+                        Taint::default()
+                    };
+                    let type_ref = self
+                        .global_table
+                        .resolve_to_type_ref(it.type_name(), None)?;
+                    let variable = self.declare(it.name().name(), Some(type_ref));
+                    self.add_taint(variable, &taint);
+                }
+                LineTokens::If(if_token) => {
+                    self.taint(if_token.if_condition())?;
+                    self.check_lines(if_token.if_contents())?;
+                    for (elif_condition, elif_contents) in if_token.elif_condition_contents() {
+                        self.taint(elif_condition)?;
+                        self.check_lines(elif_contents)?;
+                    }
+                    if let Some(else_contents) = if_token.else_contents() {
+                        self.check_lines(else_contents)?;
+                    }
+                }
+                LineTokens::While(while_token) => {
+                    self.taint(while_token.condition())?;
+                    self.check_lines(while_token.contents())?;
+                }
+                LineTokens::Return(rt) => {
+                    if let Some(value) = rt.return_value() {
+                        let taint = self.taint(value)?;
+                        if self.return_holds_reference {
+                            if let Some(reference) = taint.refs.first() {
+                                self.record_escape(reference, rt.location(), Escape::Returned);
+                            }
+                        }
+                    }
+                }
+                LineTokens::Break(_) => {}
+                LineTokens::NoOp(et) => {
+                    self.taint(et)?;
+                }
+                #[cfg(debug_assertions)]
+                LineTokens::Marker(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn block_taint(&mut self, block: &BlockToken) -> Result<Taint, WErr> {
+        self.scopes.push(HashMap::new());
+        let result = self
+            .check_lines_in_scope(block.lines())
+            .and_then(|_| self.taint(block.value()));
+        self.scopes.pop();
+        result
+    }
+
+    /// Returns the stack references an evaluable may hold, recording assignments to variables
+    /// and escapes along the way
+    fn taint(&mut self, et: &EvaluableToken) -> Result<Taint, WErr> {
+        Ok(match et.token() {
+            EvaluableTokens::Name(name, _) => self
+                .lookup(name.name())
+                .map(|variable| self.variables[variable].taint.clone())
+                .unwrap_or_default(),
+            EvaluableTokens::StaticAccess { .. }
+            | EvaluableTokens::Literal(_)
+            | EvaluableTokens::None => Taint::default(),
+            EvaluableTokens::DynamicAccess { parent, section } => {
+                let parent_taint = self.taint(parent)?;
+                // Attributes are accessed through a reference to the parent, dereferencing it if
+                // it is a reference to a reference
+                match self.type_of(parent).map(|t| t.indirection().0) {
+                    Some(0) => Taint {
+                        refs: vec![section.location().clone()],
+                        deref: parent_taint.refs,
+                    },
+                    Some(1) | None => parent_taint,
+                    Some(2) => Taint {
+                        refs: parent_taint.deref,
+                        deref: Vec::new(),
+                    },
+                    _ => Taint::default(),
+                }
+            }
+            EvaluableTokens::FunctionCall { function, args } => {
+                // References aren't tracked between functions so the result may hold any
+                // reference passed to the function, or reachable from one
+                let mut passed = Taint::default();
+                if let EvaluableTokens::DynamicAccess { parent, section: _ } = function.token() {
+                    let parent_taint = self.taint(parent)?;
+                    // Methods may take a reference to a value on the stack as `self`
+                    match self.type_of(parent).map(|t| t.indirection().0) {
+                        Some(0) => passed.union(&Taint {
+                            refs: vec![parent.location().clone()],
+                            deref: parent_taint.refs,
+                        }),
+                        _ => passed.union(&parent_taint),
+                    };
+                }
+                for arg in args {
+                    let arg_taint = self.taint(arg)?;
+                    passed.union(&arg_taint);
+                }
+                let mut refs = passed.refs;
+                add_all(&mut refs, &passed.deref);
+                Taint {
+                    deref: refs.clone(),
+                    refs,
+                }
+            }
+            EvaluableTokens::StructInitialiser(struct_init) => {
+                let mut taint = Taint::default();
+                for (_, value) in struct_init.contents() {
+                    let value_taint = self.taint(value)?;
+                    if *struct_init.heap_alloc() {
+                        if let Some(reference) = value_taint.refs.first() {
+                            self.record_escape(reference, et.location(), Escape::Heap);
+                        }
+                    } else {
+                        taint.union(&value_taint);
+                    }
+                }
+                taint
+            }
+            EvaluableTokens::InfixOperator(lhs, op, rhs) => {
+                if op.operator() == &OperatorTokens::Assign {
+                    self.assign(lhs, rhs, et.location())?;
+                } else {
+                    self.taint(lhs)?;
+                    self.taint(rhs)?;
+                }
+                Taint::default()
+            }
+            EvaluableTokens::PrefixOperator(op, operand) => {
+                let operand_taint = self.taint(operand)?;
+                match op.operator() {
+                    OperatorTokens::Reference => Taint {
+                        refs: vec![et.location().clone()],
+                        deref: operand_taint.refs,
+                    },
+                    OperatorTokens::Multiply => Taint {
+                        refs: operand_taint.deref,
+                        deref: Vec::new(),
+                    },
+                    _ => Taint::default(),
+                }
+            }
+            EvaluableTokens::Block(block) => self.block_taint(block)?,
+            EvaluableTokens::If(if_expression) => {
+                self.taint(if_expression.if_condition())?;
+                let mut taint = self.block_taint(if_expression.if_block())?;
+                for (elif_condition, elif_block) in if_expression.elif_condition_blocks() {
+                    self.taint(elif_condition)?;
+                    let elif_taint = self.block_taint(elif_block)?;
+                    taint.union(&elif_taint);
+                }
+                let else_taint = self.block_taint(if_expression.else_block())?;
+                taint.union(&else_taint);
+                taint
+            }
+            EvaluableTokens::HeapArray(heap_array) => {
+                self.taint(heap_array.length())?;
+                if let Some(from) = heap_array.from() {
+                    self.taint(from)?;
+                }
+                Taint::default()
+            }
+            // Indexing moves within the allocation the reference points into
+            EvaluableTokens::Index { parent, index } => {
+                self.taint(index)?;
+                self.taint(parent)?
+            }
+            EvaluableTokens::Delete(pointer) => {
+                self.taint(pointer)?;
+                Taint::default()
+            }
+        })
+    }
+
+    /// Handles `lhs = rhs` where `lhs` evaluates to a reference to the assigned location
+    fn assign(
+        &mut self,
+        lhs: &EvaluableToken,
+        rhs: &EvaluableToken,
+        location: &Location,
+    ) -> Result<(), WErr> {
+        let value = self.taint(rhs)?;
+        let target = self.taint(lhs)?;
+        let Some(variable) = root_variable(lhs).and_then(|name| self.lookup(name.name())) else {
+            return Ok(());
+        };
+
+        if !target.refs.is_empty() {
+            // Assigning to the stack - the variable may now hold the references
+            self.add_taint(
+                variable,
+                &Taint {
+                    refs: value.refs.clone(),
+                    deref: value.refs,
+                },
+            );
+        } else if let Some(reference) = value.refs.first() {
+            // Assigning through a reference that doesn't point to the stack
+            self.record_escape(reference, location, Escape::Outside);
+        }
+        Ok(())
+    }
+
+    /// Returns the type of an evaluable, if it can be found without compiling it
+    fn type_of(&mut self, et: &EvaluableToken) -> Option<TypeRef> {
+        match et.token() {
+            EvaluableTokens::Name(name, _) => self
+                .lookup(name.name())
+                .and_then(|variable| self.variables[variable].type_ref.clone()),
+            EvaluableTokens::DynamicAccess { parent, section } => {
+                let parent_type = self.type_of(parent)?;
+                self.global_table
+                    .get_type(*parent_type.type_id())
+                    .get_attributes(section.location())
+                    .ok()?
+                    .iter()
+                    .find(|(_, name, _)| name.name() == section.name())
+                    .map(|(_, _, t)| t.plus_one_indirect())
+            }
+            EvaluableTokens::PrefixOperator(op, operand) => match op.operator() {
+                OperatorTokens::Reference => Some(self.type_of(operand)?.plus_one_indirect()),
+                OperatorTokens::Multiply => {
+                    let t = self.type_of(operand)?;
+                    t.indirection()
+                        .has_indirection()
+                        .then(|| t.minus_one_indirect())
+                }
+                _ => None,
+            },
+            EvaluableTokens::StructInitialiser(struct_init) => {
+                let t = self
+                    .global_table
+                    .resolve_to_type_ref(struct_init.name(), None)
+                    .ok()?;
+                if *struct_init.heap_alloc() {
+                    Some(t.plus_one_indirect())
+                } else {
+                    Some(t)
+                }
+            }
+            _ => None,
+        }
+    }
+}
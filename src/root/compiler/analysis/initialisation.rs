@@ -0,0 +1,416 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::root::compiler::analysis::control_flow::is_literal_true;
+use crate::root::errors::compiler_errors::CompErrs;
+use crate::root::errors::WErr;
+use crate::root::name_resolver::name_resolvers::GlobalTable;
+use crate::root::parser::location::Location;
+use crate::root::parser::parse_function::parse_block::BlockToken;
+use crate::root::parser::parse_function::parse_evaluable::{EvaluableToken, EvaluableTokens};
+use crate::root::parser::parse_function::parse_line::LineTokens;
+use crate::root::parser::parse_function::parse_operator::OperatorTokens;
+use crate::root::shared::common::TypeRef;
+
+/// A tracked variable and a path to one of its fields e.g. `a.b.c` is `(a, ["b", "c"])`
+type Path = (usize, Vec<String>);
+
+/// The paths initialised at a point in a function
+#[derive(Clone, Default)]
+struct Paths {
+    /// Paths initialised on every path of execution reaching the point
+    definitely: HashSet<Path>,
+    /// Paths initialised on any path of execution reaching the point
+    maybe: HashSet<Path>,
+}
+
+impl Paths {
+    fn insert(&mut self, path: Path) {
+        self.definitely.insert(path.clone());
+        self.maybe.insert(path);
+    }
+
+    /// Whether a path, a path containing it or a path within it may have been initialised
+    fn may_be_initialised(&self, path: &Path) -> bool {
+        let (var, fields) = path;
+        self.maybe
+            .iter()
+            .any(|(v, f)| v == var && (f.starts_with(fields) || fields.starts_with(f)))
+    }
+}
+
+/// The initialised paths at a point in a function. `None` if the point can't be reached
+type State = Option<Paths>;
+
+/// Combines the states of two paths of execution meeting
+fn meet(a: State, b: State) -> State {
+    match (a, b) {
+        (None, b) => b,
+        (a, None) => a,
+        (Some(a), Some(b)) => Some(Paths {
+            definitely: a.definitely.intersection(&b.definitely).cloned().collect(),
+            maybe: a.maybe.union(&b.maybe).cloned().collect(),
+        }),
+    }
+}
+
+/// A variable declared with `let`
+struct Tracked {
+    name: String,
+    type_ref: TypeRef,
+}
+
+/// Dataflow pass tracking which variables (and their fields) are definitely initialised. Variables
+/// declared with a value are initialised once it has been evaluated into them
+struct InitialisationChecker<'a> {
+    global_table: &'a mut GlobalTable,
+    tracked: Vec<Tracked>,
+    /// Variables in scope
+    scopes: Vec<HashMap<String, usize>>,
+    /// The states at each break out of the innermost loop
+    breaks: Option<Vec<Paths>>,
+    /// Locations of assignments that are the first to a variable or field
+    initialising: Vec<Location>,
+}
+
+/// Errors if any variable (or any of its fields) in the lines of a function may be read before
+/// being assigned to, including by its own initial value
+///
+/// Returns the locations of the assignments (of the assigned references) that are definitely the
+/// first to a variable or field declared without a value. These initialise it so are allowed even
+/// if it isn't mutable
+pub fn check_initialisation(
+    lines: &[LineTokens],
+    global_table: &mut GlobalTable,
+) -> Result<Vec<Location>, WErr> {
+    let mut checker = InitialisationChecker {
+        global_table,
+        tracked: Vec::new(),
+        scopes: Vec::new(),
+        breaks: None,
+        initialising: Vec::new(),
+    };
+    checker.check_lines(lines, Paths::default())?;
+    Ok(checker.initialising)
+}
+
+impl InitialisationChecker<'_> {
+    fn check_lines(&mut self, lines: &[LineTokens], state: Paths) -> Result<State, WErr> {
+        self.scopes.push(HashMap::new());
+        let state = self.check_lines_in_scope(lines, state);
+        self.scopes.pop();
+        state
+    }
+
+    fn check_lines_in_scope(
+        &mut self,
+        lines: &[LineTokens],
+        state: Paths,
+    ) -> Result<State, WErr> {
+        let mut state = state;
+
+        for line in lines {
+            let next = match line {
+                LineTokens::Initialisation(it) => {
+                    // The variable is in scope while its value is evaluated into it, so its own
+                    // value (including the attributes of a struct or `new` initialiser) can't
+                    // read it
+                    let type_ref = self
+                        .global_table
+                        .resolve_to_type_ref(it.type_name(), None)?;
+                    self.tracked.push(Tracked {
+                        name: it.name().name().clone(),
+                        type_ref,
+                    });
+                    let var = self.tracked.len() - 1;
+                    self.scopes
+                        .last_mut()
+                        .unwrap()
+                        .insert(it.name().name().clone(), var);
+                    if let Some(value) = it.value() {
+                        self.check_evaluable(value, &mut state)?;
+                        state.insert((var, Vec::new()));
+                    }
+                    Some(state)
+                }
+                LineTokens::If(if_token) => {
+                    self.check_evaluable(if_token.if_condition(), &mut state)?;
+                    let mut out = self.check_lines(if_token.if_contents(), state.clone())?;
+
+                    // Each condition is only evaluated if the previous one failed
+                    for (elif_condition, elif_contents) in if_token.elif_condition_contents() {
+                        self.check_evaluable(elif_condition, &mut state)?;
+                        out = meet(out, self.check_lines(elif_contents, state.clone())?);
+                    }
+
+                    if let Some(else_contents) = if_token.else_contents() {
+                        meet(out, self.check_lines(else_contents, state)?)
+                    } else {
+                        meet(out, Some(state))
+                    }
+                }
+                LineTokens::While(while_token) => {
+                    self.check_evaluable(while_token.condition(), &mut state)?;
+
+                    // Assignments in the body may not have happened when the condition is
+                    // re-evaluated or the body is re-run so the body only sees the state before it.
+                    // The body is then checked again knowing it may have run before, so that its
+                    // assignments aren't taken to be the first
+                    let outer_breaks = self.breaks.replace(Vec::new());
+                    let end = self.check_lines(while_token.contents(), state.clone())?;
+                    let state = meet(Some(state), end).unwrap();
+                    self.breaks = Some(Vec::new());
+                    self.check_lines(while_token.contents(), state.clone())?;
+                    let breaks = std::mem::replace(&mut self.breaks, outer_breaks).unwrap();
+
+                    let mut out = if is_literal_true(while_token.condition()) {
+                        None
+                    } else {
+                        Some(state)
+                    };
+                    for b in breaks {
+                        out = meet(out, Some(b));
+                    }
+                    out
+                }
+                LineTokens::Return(rt) => {
+                    if let Some(value) = rt.return_value() {
+                        self.check_evaluable(value, &mut state)?;
+                    }
+                    None
+                }
+                LineTokens::Break(_) => {
+                    if let Some(breaks) = &mut self.breaks {
+                        breaks.push(state);
+                    }
+                    None
+                }
+                LineTokens::NoOp(et) => {
+                    self.check_evaluable(et, &mut state)?;
+                    Some(state)
+                }
+                #[cfg(debug_assertions)]
+                LineTokens::Marker(_) => Some(state),
+            };
+
+            // Following lines are unreachable
+            let Some(next) = next else {
+                return Ok(None);
+            };
+            state = next;
+        }
+
+        Ok(Some(state))
+    }
+
+    fn check_block(&mut self, block: &BlockToken, state: &mut Paths) -> Result<(), WErr> {
+        self.scopes.push(HashMap::new());
+        // Blocks used as values can't return or break so the end is always reached
+        if let Some(out) = self.check_lines_in_scope(block.lines(), state.clone())? {
+            *state = out;
+        }
+        let result = self.check_evaluable(block.value(), state);
+        self.scopes.pop();
+        result
+    }
+
+    fn check_evaluable(
+        &mut self,
+        et: &EvaluableToken,
+        state: &mut Paths,
+    ) -> Result<(), WErr> {
+        match et.token() {
+            EvaluableTokens::Name(_, _) | EvaluableTokens::DynamicAccess { .. } => {
+                if let Some((path, type_ref)) = self.path_of(et) {
+                    if !self.is_initialised(state, &path, &type_ref) {
+                        return WErr::ne(
+                            CompErrs::UseOfUninitialised(self.path_name(&path)),
+                            et.location().clone(),
+                        );
+                    }
+                } else if let EvaluableTokens::DynamicAccess { parent, section: _ } = et.token() {
+                    self.check_evaluable(parent, state)?;
+                }
+            }
+            EvaluableTokens::StaticAccess { .. } => {}
+            EvaluableTokens::FunctionCall { function, args } => {
+                // Method calls read `self`
+                if let EvaluableTokens::DynamicAccess { parent, section: _ } = function.token() {
+                    self.check_evaluable(parent, state)?;
+                }
+                for arg in args {
+                    self.check_evaluable(arg, state)?;
+                }
+            }
+            EvaluableTokens::Literal(_) | EvaluableTokens::None => {}
+            EvaluableTokens::StructInitialiser(struct_init) => {
+                for (_, value) in struct_init.contents() {
+                    self.check_evaluable(value, state)?;
+                }
+            }
+            EvaluableTokens::InfixOperator(lhs, op, rhs) => {
+                if op.operator() == &OperatorTokens::Assign {
+                    self.check_evaluable(rhs, state)?;
+                    // Assigning through a reference to a variable or field initialises it
+                    if let Some((path, _)) = self.assignment_target(lhs) {
+                        // Loop bodies are checked more than once, the last time knowing they may
+                        // have run before
+                        self.initialising.retain(|l| l != lhs.location());
+                        if !state.may_be_initialised(&path) {
+                            self.initialising.push(lhs.location().clone());
+                        }
+                        state.insert(path);
+                    } else {
+                        self.check_evaluable(lhs, state)?;
+                    }
+                } else {
+                    self.check_evaluable(lhs, state)?;
+                    self.check_evaluable(rhs, state)?;
+                }
+            }
+            EvaluableTokens::PrefixOperator(_, operand) => {
+                self.check_evaluable(operand, state)?;
+            }
+            EvaluableTokens::Block(block) => {
+                self.check_block(block, state)?;
+            }
+            EvaluableTokens::If(if_expression) => {
+                self.check_evaluable(if_expression.if_condition(), state)?;
+                let mut if_state = state.clone();
+                self.check_block(if_expression.if_block(), &mut if_state)?;
+                let mut out = Some(if_state);
+
+                // Each condition is only evaluated if the previous one failed
+                for (elif_condition, elif_block) in if_expression.elif_condition_blocks() {
+                    self.check_evaluable(elif_condition, state)?;
+                    let mut elif_state = state.clone();
+                    self.check_block(elif_block, &mut elif_state)?;
+                    out = meet(out, Some(elif_state));
+                }
+
+                self.check_block(if_expression.else_block(), state)?;
+                if let Some(out) = meet(out, Some(state.clone())) {
+                    *state = out;
+                }
+            }
+            EvaluableTokens::HeapArray(heap_array) => {
+                self.check_evaluable(heap_array.length(), state)?;
+                if let Some(from) = heap_array.from() {
+                    self.check_evaluable(from, state)?;
+                }
+            }
+            EvaluableTokens::Index { parent, index } => {
+                self.check_evaluable(parent, state)?;
+                self.check_evaluable(index, state)?;
+            }
+            EvaluableTokens::Delete(pointer) => {
+                self.check_evaluable(pointer, state)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds the variable a name refers to. `None` if it isn't a local variable e.g. a parameter
+    fn lookup(&self, name: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .copied()
+    }
+
+    /// Returns the path (and its type) of a tracked variable or field stored within it. `None` if
+    /// the evaluable isn't a tracked variable or a field stored directly in one
+    fn path_of(&self, et: &EvaluableToken) -> Option<(Path, TypeRef)> {
+        match et.token() {
+            EvaluableTokens::Name(name, _) => {
+                let var = self.lookup(name.name())?;
+                Some(((var, Vec::new()), self.tracked[var].type_ref.clone()))
+            }
+            EvaluableTokens::DynamicAccess { parent, section } => {
+                let ((var, mut fields), parent_type) = self.path_of(parent)?;
+                // Fields accessed through a reference aren't stored in the variable
+                if parent_type.indirection().has_indirection() {
+                    return None;
+                }
+                let attribute_type = self
+                    .attributes(&parent_type, section.location())?
+                    .into_iter()
+                    .find(|(name, _)| name == section.name())?
+                    .1;
+                fields.push(section.name().clone());
+                Some(((var, fields), attribute_type))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the path written to by assigning to `lhs` (which evaluates to a reference) e.g. `a.b`
+    /// or `&a`. `None` if it isn't a tracked variable or a field stored directly in one
+    fn assignment_target(&self, lhs: &EvaluableToken) -> Option<(Path, TypeRef)> {
+        match lhs.token() {
+            EvaluableTokens::DynamicAccess { .. } => self.path_of(lhs),
+            EvaluableTokens::PrefixOperator(op, operand)
+                if op.operator() == &OperatorTokens::Reference =>
+            {
+                self.path_of(operand)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the attributes of a type, if it has any
+    fn attributes(
+        &self,
+        type_ref: &TypeRef,
+        location: &Location,
+    ) -> Option<Vec<(String, TypeRef)>> {
+        self.global_table
+            .get_type(*type_ref.type_id())
+            .get_attributes(location)
+            .ok()
+            .map(|attributes| {
+                attributes
+                    .iter()
+                    .map(|(_, name, t)| (name.name().clone(), t.clone()))
+                    .collect()
+            })
+    }
+
+    /// Whether a path is definitely initialised - either it (or a path containing it) has been
+    /// assigned to or all of its fields have
+    fn is_initialised(&self, state: &Paths, path: &Path, type_ref: &TypeRef) -> bool {
+        let (var, fields) = path;
+        if (0..=fields.len()).any(|i| {
+            state
+                .definitely
+                .contains(&(*var, fields[..i].to_vec()))
+        }) {
+            return true;
+        }
+
+        if type_ref.indirection().has_indirection() {
+            return false;
+        }
+        match self.attributes(type_ref, &Location::builtin()) {
+            Some(attributes) if !attributes.is_empty() => {
+                attributes.into_iter().all(|(name, t)| {
+                    let mut fields = fields.clone();
+                    fields.push(name);
+                    self.is_initialised(state, &(*var, fields), &t)
+                })
+            }
+            _ => false,
+        }
+    }
+
+    /// Formats a path e.g. `a.b.c`
+    fn path_name(&self, path: &Path) -> String {
+        let (var, fields) = path;
+        let mut name = self.tracked[*var].name.clone();
+        for field in fields {
+            name += ".";
+            name += field;
+        }
+        name
+    }
+}
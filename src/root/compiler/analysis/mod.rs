@@ -0,0 +1,4 @@
+pub mod control_flow;
+pub mod escape;
+pub mod initialisation;
+pub mod nullability;
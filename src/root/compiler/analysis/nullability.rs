@@ -0,0 +1,776 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::root::compiler::analysis::control_flow::is_literal_true;
+use crate::root::compiler::evaluation::reference_comparison::is_null_literal;
+use crate::root::errors::compiler_errors::CompErrs;
+use crate::root::errors::WErr;
+use crate::root::name_resolver::name_resolvers::GlobalTable;
+use crate::root::parser::location::Location;
+use crate::root::parser::parse_function::parse_block::BlockToken;
+use crate::root::parser::parse_function::parse_evaluable::{
+    EvaluableToken, EvaluableTokens, UnresolvedTypeRefToken,
+};
+use crate::root::parser::parse_function::parse_line::LineTokens;
+use crate::root::parser::parse_function::parse_operator::OperatorTokens;
+use crate::root::parser::parse_parameters::{Parameters, SelfType};
+use crate::root::shared::common::{FunctionID, Indirection, TypeRef};
+
+/// A variable and a path to one of its fields e.g. `a.b.c` is `(a, ["b", "c"])`. Fields accessed
+/// through references are included e.g. `self.next` where `self` is `&Node`. Paths accessed through
+/// references, or within variables a reference has been taken to, are aliased - they may be
+/// changed without being named
+type Path = (usize, Vec<String>);
+
+/// The paths holding nullable references that are known not to be null at a point in a function.
+/// `None` if the point can't be reached
+type State = Option<HashSet<Path>>;
+
+/// Combines the states of two paths of execution meeting
+fn meet(a: State, b: State) -> State {
+    match (a, b) {
+        (None, b) => b,
+        (a, None) => a,
+        (Some(a), Some(b)) => Some(a.intersection(&b).cloned().collect()),
+    }
+}
+
+/// Removes `path` and every path within it from `state`. If `inclusive` is false, `path` itself is
+/// kept
+fn forget(state: &mut HashSet<Path>, path: &Path, inclusive: bool) {
+    state.retain(|(var, fields)| {
+        *var != path.0
+            || !fields.starts_with(&path.1)
+            || (!inclusive && fields.len() == path.1.len())
+    });
+}
+
+/// A local variable or parameter
+struct Variable {
+    type_ref: TypeRef,
+}
+
+/// Paths known not to be null if a condition is true and if it is false
+#[derive(Default)]
+struct Facts {
+    when_true: Vec<Path>,
+    when_false: Vec<Path>,
+}
+
+/// Dataflow pass checking that nullable (`?&`) references are compared against `null` before they
+/// are dereferenced or have their attributes or methods accessed
+struct NullabilityChecker<'a> {
+    global_table: &'a mut GlobalTable,
+    variables: Vec<Variable>,
+    scopes: Vec<HashMap<String, usize>>,
+    /// The states at each break out of the innermost loop
+    breaks: Option<Vec<HashSet<Path>>>,
+    /// Locations of values known not to be null every time they are evaluated
+    non_null: Vec<Location>,
+    /// Locations of values that may be null at least once when they are evaluated
+    maybe_null: Vec<Location>,
+    /// Variables a reference may have been taken to e.g. `x` after `let p: &mut T = &x;`
+    escaped: HashSet<usize>,
+}
+
+/// Errors if a nullable reference in the lines of a function may be dereferenced (or used to
+/// access an attribute or method) without first being checked against `null`. Returns the
+/// locations of the variables (and dereferences) known not to be null where they are used
+pub fn check_nullability(
+    parameters: &Parameters,
+    lines: &[LineTokens],
+    global_table: &mut GlobalTable,
+) -> Result<Vec<Location>, WErr> {
+    let mut checker = NullabilityChecker {
+        global_table,
+        variables: Vec::new(),
+        scopes: vec![HashMap::new()],
+        breaks: None,
+        non_null: Vec::new(),
+        maybe_null: Vec::new(),
+        escaped: HashSet::new(),
+    };
+
+    for (name, type_name) in parameters {
+        checker.declare(name.name(), type_name)?;
+    }
+    checker.check_lines(lines, HashSet::new())?;
+    Ok(checker.non_null)
+}
+
+impl NullabilityChecker<'_> {
+    fn declare(&mut self, name: &str, type_name: &UnresolvedTypeRefToken) -> Result<usize, WErr> {
+        let type_ref = self.global_table.resolve_to_type_ref(type_name, None)?;
+        self.variables.push(Variable { type_ref });
+        let variable = self.variables.len() - 1;
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(name.to_string(), variable);
+        Ok(variable)
+    }
+
+    /// Finds the variable a name refers to
+    fn lookup(&self, name: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .copied()
+    }
+
+    fn check_lines(&mut self, lines: &[LineTokens], state: HashSet<Path>) -> Result<State, WErr> {
+        self.scopes.push(HashMap::new());
+        let state = self.check_lines_in_scope(lines, state);
+        self.scopes.pop();
+        state
+    }
+
+    fn check_lines_in_scope(
+        &mut self,
+        lines: &[LineTokens],
+        state: HashSet<Path>,
+    ) -> Result<State, WErr> {
+        let mut state = state;
+
+        for line in lines {
+            let next = match line {
+                LineTokens::Initialisation(it) => {
+                    let non_null = if let Some(value) = it.value() {
+                        self.check_evaluable(value, &mut state)?;
+                        self.escape(value);
+                        self.is_non_null(value, &state)
+                    } else {
+                        false
+                    };
+                    let variable = self.declare(it.name().name(), it.type_name())?;
+                    if non_null {
+                        state.insert((variable, Vec::new()));
+                    }
+                    Some(state)
+                }
+                LineTokens::If(if_token) => {
+                    let mut condition_state = state;
+                    self.check_evaluable(if_token.if_condition(), &mut condition_state)?;
+                    let facts = self.facts(if_token.if_condition());
+                    let mut out = self.check_lines(
+                        if_token.if_contents(),
+                        with(&condition_state, &facts.when_true),
+                    )?;
+                    let mut state = with(&condition_state, &facts.when_false);
+
+                    // Each condition is only evaluated if the previous one failed
+                    for (elif_condition, elif_contents) in if_token.elif_condition_contents() {
+                        self.check_evaluable(elif_condition, &mut state)?;
+                        let facts = self.facts(elif_condition);
+                        out = meet(
+                            out,
+                            self.check_lines(elif_contents, with(&state, &facts.when_true))?,
+                        );
+                        state = with(&state, &facts.when_false);
+                    }
+
+                    if let Some(else_contents) = if_token.else_contents() {
+                        meet(out, self.check_lines(else_contents, state)?)
+                    } else {
+                        meet(out, Some(state))
+                    }
+                }
+                LineTokens::While(while_token) => {
+                    // References may be set to null (or taken) later in the loop so the loop is
+                    // checked until the state at the start of each iteration stops changing
+                    let mut head = state;
+                    loop {
+                        let escaped = self.escaped.len();
+                        let mut condition_state = head.clone();
+                        self.check_evaluable(while_token.condition(), &mut condition_state)?;
+                        let facts = self.facts(while_token.condition());
+
+                        let outer_breaks = self.breaks.replace(Vec::new());
+                        let end = self.check_lines(
+                            while_token.contents(),
+                            with(&condition_state, &facts.when_true),
+                        )?;
+                        let breaks = std::mem::replace(&mut self.breaks, outer_breaks).unwrap();
+
+                        let next_head = meet(Some(head.clone()), end).unwrap();
+                        if next_head != head || self.escaped.len() != escaped {
+                            head = next_head;
+                            continue;
+                        }
+
+                        let mut out = if is_literal_true(while_token.condition()) {
+                            None
+                        } else {
+                            Some(with(&condition_state, &facts.when_false))
+                        };
+                        for b in breaks {
+                            out = meet(out, Some(b));
+                        }
+                        break out;
+                    }
+                }
+                LineTokens::Return(rt) => {
+                    if let Some(value) = rt.return_value() {
+                        self.check_evaluable(value, &mut state)?;
+                    }
+                    None
+                }
+                LineTokens::Break(_) => {
+                    if let Some(breaks) = &mut self.breaks {
+                        breaks.push(state);
+                    }
+                    None
+                }
+                LineTokens::NoOp(et) => {
+                    self.check_evaluable(et, &mut state)?;
+                    Some(state)
+                }
+                #[cfg(debug_assertions)]
+                LineTokens::Marker(_) => Some(state),
+            };
+
+            // Following lines are unreachable
+            let Some(next) = next else {
+                return Ok(None);
+            };
+            state = next;
+        }
+
+        Ok(Some(state))
+    }
+
+    fn check_block(&mut self, block: &BlockToken, state: &mut HashSet<Path>) -> Result<(), WErr> {
+        self.scopes.push(HashMap::new());
+        // Blocks used as values can't return or break so the end is always reached
+        if let Some(out) = self.check_lines_in_scope(block.lines(), state.clone())? {
+            *state = out;
+        }
+        let result = self.check_evaluable(block.value(), state);
+        self.scopes.pop();
+        result
+    }
+
+    fn check_evaluable(
+        &mut self,
+        et: &EvaluableToken,
+        state: &mut HashSet<Path>,
+    ) -> Result<(), WErr> {
+        match et.token() {
+            EvaluableTokens::Name(_, _) => self.record(et, state),
+            EvaluableTokens::StaticAccess { .. }
+            | EvaluableTokens::Literal(_)
+            | EvaluableTokens::None => {}
+            EvaluableTokens::DynamicAccess { parent, section: _ } => {
+                self.check_evaluable(parent, state)?;
+                self.check_accessed(parent, state)?;
+            }
+            EvaluableTokens::FunctionCall { function, args } => {
+                for arg in args {
+                    self.check_evaluable(arg, state)?;
+                }
+                // Anything aliased may be changed through a reference passed to the function
+                let mut passes_reference = false;
+                for arg in args {
+                    passes_reference |= self.passes_reference(arg);
+                    self.escape(arg);
+                }
+
+                if let EvaluableTokens::DynamicAccess { parent, section } = function.token() {
+                    self.check_evaluable(parent, state)?;
+                    self.check_accessed(parent, state)?;
+
+                    // Methods taking `&mut self` may change anything within `self`
+                    let self_type = self
+                        .method_of(parent, section.name())
+                        .map(|f| *self.global_table.get_function_signature(f).self_type());
+                    if !matches!(self_type, Some(SelfType::RefSelf | SelfType::CopySelf)) {
+                        if let Some(path) = self.container_path(parent) {
+                            forget(state, &path, false);
+                        }
+                    }
+                    // `self` is passed by reference unless the method is known to copy it
+                    let by_reference = !matches!(self_type, Some(SelfType::CopySelf));
+                    passes_reference |= by_reference || self.passes_reference(parent);
+                    if by_reference {
+                        if let Some(path) = self.container_path(parent) {
+                            self.escape_path(&path);
+                        }
+                    }
+                }
+
+                if passes_reference {
+                    self.forget_aliased(state);
+                }
+                // Anything referred to by an argument may be changed
+                for arg in args {
+                    if let Some(path) = self.referred_path(arg) {
+                        forget(state, &path, true);
+                    }
+                }
+            }
+            EvaluableTokens::StructInitialiser(struct_init) => {
+                for (_, value) in struct_init.contents() {
+                    self.check_evaluable(value, state)?;
+                    self.escape(value);
+                }
+            }
+            EvaluableTokens::InfixOperator(lhs, op, rhs) => match op.operator() {
+                OperatorTokens::Assign => {
+                    self.check_evaluable(rhs, state)?;
+                    self.escape(rhs);
+                    self.check_evaluable(lhs, state)?;
+                    let non_null = self.is_non_null(rhs, state);
+                    let path = self.referred_path(lhs);
+                    // Assigning to an aliased (or unknown) path may change any other aliased path
+                    if path.as_ref().is_none_or(|path| self.is_aliased(path)) {
+                        self.forget_aliased(state);
+                    }
+                    if let Some(path) = path {
+                        forget(state, &path, true);
+                        if non_null {
+                            state.insert(path);
+                        }
+                    }
+                }
+                // The right-hand side is only evaluated if the left-hand side doesn't decide the
+                // result
+                OperatorTokens::And | OperatorTokens::Or => {
+                    self.check_evaluable(lhs, state)?;
+                    let facts = self.facts(lhs);
+                    let known = if op.operator() == &OperatorTokens::And {
+                        facts.when_true
+                    } else {
+                        facts.when_false
+                    };
+                    self.check_evaluable(rhs, &mut with(state, &known))?;
+                }
+                _ => {
+                    self.check_evaluable(lhs, state)?;
+                    self.check_evaluable(rhs, state)?;
+                }
+            },
+            EvaluableTokens::PrefixOperator(op, operand) => {
+                self.check_evaluable(operand, state)?;
+                if op.operator() == &OperatorTokens::Multiply {
+                    self.record(et, state);
+                    if let Some(t) = self.type_of(operand) {
+                        if t.is_nullable_reference() {
+                            self.require_non_null(operand, &t, state)?;
+                        }
+                    }
+                }
+            }
+            EvaluableTokens::Block(block) => {
+                self.check_block(block, state)?;
+            }
+            EvaluableTokens::If(if_expression) => {
+                self.check_evaluable(if_expression.if_condition(), state)?;
+                let facts = self.facts(if_expression.if_condition());
+                let mut if_state = with(state, &facts.when_true);
+                self.check_block(if_expression.if_block(), &mut if_state)?;
+                let mut out = Some(if_state);
+                *state = with(state, &facts.when_false);
+
+                // Each condition is only evaluated if the previous one failed
+                for (elif_condition, elif_block) in if_expression.elif_condition_blocks() {
+                    self.check_evaluable(elif_condition, state)?;
+                    let facts = self.facts(elif_condition);
+                    let mut elif_state = with(state, &facts.when_true);
+                    self.check_block(elif_block, &mut elif_state)?;
+                    out = meet(out, Some(elif_state));
+                    *state = with(state, &facts.when_false);
+                }
+
+                self.check_block(if_expression.else_block(), state)?;
+                if let Some(out) = meet(out, Some(state.clone())) {
+                    *state = out;
+                }
+            }
+            // Reallocating or freeing `null` is allowed
+            EvaluableTokens::HeapArray(heap_array) => {
+                self.check_evaluable(heap_array.length(), state)?;
+                if let Some(from) = heap_array.from() {
+                    self.check_evaluable(from, state)?;
+                }
+            }
+            EvaluableTokens::Index { parent, index } => {
+                self.check_evaluable(parent, state)?;
+                self.check_evaluable(index, state)?;
+                if let Some(t) = self.type_of(parent) {
+                    if t.is_nullable_reference() {
+                        self.require_non_null(parent, &t, state)?;
+                    }
+                }
+            }
+            EvaluableTokens::Delete(pointer) => {
+                self.check_evaluable(pointer, state)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Records that a reference `et` evaluates to (e.g. `&x` or `x.y`) may be kept, so the variable
+    /// it refers to is aliased
+    fn escape(&mut self, et: &EvaluableToken) {
+        match et.token() {
+            EvaluableTokens::DynamicAccess { .. } | EvaluableTokens::PrefixOperator(..) => {
+                if let Some(path) = self.referred_path(et) {
+                    self.escape_path(&path);
+                }
+            }
+            EvaluableTokens::Block(block) => self.escape(block.value()),
+            EvaluableTokens::If(if_expression) => {
+                self.escape(if_expression.if_block().value());
+                for (_, elif_block) in if_expression.elif_condition_blocks() {
+                    self.escape(elif_block.value());
+                }
+                self.escape(if_expression.else_block().value());
+            }
+            _ => {}
+        }
+    }
+
+    /// Records that a reference to `path` may be kept. Paths accessed through references are
+    /// already aliased
+    fn escape_path(&mut self, path: &Path) {
+        if !self.through_reference(path) {
+            self.escaped.insert(path.0);
+        }
+    }
+
+    /// Whether `path` may be changed without being named
+    fn is_aliased(&self, path: &Path) -> bool {
+        self.escaped.contains(&path.0) || self.through_reference(path)
+    }
+
+    /// Whether any of the values `path` is accessed through is a reference e.g. `p.next` where `p`
+    /// is `&Node`, or `a.next.value` where `a.next` is `?&Node`
+    fn through_reference(&self, path: &Path) -> bool {
+        let mut t = self.variables[path.0].type_ref.clone();
+        for field in &path.1 {
+            if t.indirection().has_indirection() {
+                return true;
+            }
+            let Some(attribute) = self.attribute_type(&t, field) else {
+                return true;
+            };
+            t = attribute;
+        }
+        false
+    }
+
+    /// Removes every aliased path from `state`
+    fn forget_aliased(&self, state: &mut HashSet<Path>) {
+        state.retain(|path| !self.is_aliased(path));
+    }
+
+    /// Whether `et`, passed to a function, may contain a reference the function can change
+    /// aliased paths through
+    fn passes_reference(&mut self, et: &EvaluableToken) -> bool {
+        match self.type_of(et) {
+            Some(t) => self.contains_reference(&t),
+            None => !matches!(
+                et.token(),
+                EvaluableTokens::Literal(_) | EvaluableTokens::InfixOperator(..)
+            ),
+        }
+    }
+
+    /// Whether a value of type `t` is or contains a reference
+    fn contains_reference(&self, t: &TypeRef) -> bool {
+        if t.indirection().has_indirection() {
+            return true;
+        }
+        self.global_table
+            .get_type(*t.type_id())
+            .get_attributes(&Location::builtin())
+            .is_ok_and(|attributes| {
+                attributes
+                    .iter()
+                    .any(|(_, _, t)| self.contains_reference(t))
+            })
+    }
+
+    /// Returns the type of the attribute `name` of a value of type `t`
+    fn attribute_type(&self, t: &TypeRef, name: &str) -> Option<TypeRef> {
+        self.global_table
+            .get_type(*t.type_id())
+            .get_attributes(&Location::builtin())
+            .ok()?
+            .iter()
+            .find(|(_, attribute, _)| attribute.name() == name)
+            .map(|(_, _, t)| t.clone())
+    }
+
+    /// Checks that every nullable reference that must be followed to access an attribute or
+    /// method of `parent` is known not to be null
+    fn check_accessed(
+        &mut self,
+        parent: &EvaluableToken,
+        state: &HashSet<Path>,
+    ) -> Result<(), WErr> {
+        let Some(t) = self.type_of(parent) else {
+            return Ok(());
+        };
+
+        if t.is_nullable_reference() {
+            self.require_non_null(parent, &t, state)?;
+        }
+        // A reference to a reference is dereferenced twice
+        if t.indirection().0 == 2
+            && t.is_nullable_at(Indirection(1))
+            && !self
+                .referred_path(parent)
+                .is_some_and(|path| state.contains(&path))
+        {
+            return WErr::ne(
+                CompErrs::MaybeNull(self.global_table.get_type_name(&t.minus_one_indirect())),
+                parent.location().clone(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Records whether the value `et` evaluates to is known not to be null. Code in loops is
+    /// checked more than once so a value is only non-null if it is every time
+    fn record(&mut self, et: &EvaluableToken, state: &HashSet<Path>) {
+        let location = et.location();
+        if self.maybe_null.contains(location) {
+            return;
+        }
+        if self
+            .value_path(et)
+            .is_some_and(|path| state.contains(&path))
+        {
+            if !self.non_null.contains(location) {
+                self.non_null.push(location.clone());
+            }
+        } else {
+            self.non_null.retain(|l| l != location);
+            self.maybe_null.push(location.clone());
+        }
+    }
+
+    /// Errors if `et`, a nullable reference, isn't known not to be null
+    fn require_non_null(
+        &self,
+        et: &EvaluableToken,
+        t: &TypeRef,
+        state: &HashSet<Path>,
+    ) -> Result<(), WErr> {
+        if self
+            .value_path(et)
+            .is_some_and(|path| state.contains(&path))
+        {
+            return Ok(());
+        }
+        WErr::ne(
+            CompErrs::MaybeNull(self.global_table.get_type_name(t)),
+            et.location().clone(),
+        )
+    }
+
+    /// Whether `et` is known not to evaluate to a null reference
+    fn is_non_null(&mut self, et: &EvaluableToken, state: &HashSet<Path>) -> bool {
+        match et.token() {
+            EvaluableTokens::PrefixOperator(op, _)
+                if op.operator() == &OperatorTokens::Reference =>
+            {
+                true
+            }
+            EvaluableTokens::StructInitialiser(struct_init) => *struct_init.heap_alloc(),
+            EvaluableTokens::HeapArray(_) | EvaluableTokens::Index { .. } => true,
+            _ if is_null_literal(et) => false,
+            _ => {
+                self.value_path(et)
+                    .is_some_and(|path| state.contains(&path))
+                    || self.type_of(et).is_some_and(|t| {
+                        t.indirection().has_indirection() && !t.is_nullable_reference()
+                    })
+            }
+        }
+    }
+
+    /// Returns the paths known not to be null when a condition is true and when it is false
+    fn facts(&self, condition: &EvaluableToken) -> Facts {
+        match condition.token() {
+            EvaluableTokens::InfixOperator(lhs, op, rhs) => match op.operator() {
+                OperatorTokens::Equals | OperatorTokens::NotEqual => {
+                    let compared = if is_null_literal(lhs) {
+                        rhs
+                    } else if is_null_literal(rhs) {
+                        lhs
+                    } else {
+                        return Facts::default();
+                    };
+                    let Some(path) = self.value_path(compared) else {
+                        return Facts::default();
+                    };
+                    if op.operator() == &OperatorTokens::NotEqual {
+                        Facts {
+                            when_true: vec![path],
+                            when_false: Vec::new(),
+                        }
+                    } else {
+                        Facts {
+                            when_true: Vec::new(),
+                            when_false: vec![path],
+                        }
+                    }
+                }
+                OperatorTokens::And => {
+                    let mut when_true = self.facts(lhs).when_true;
+                    when_true.extend(self.facts(rhs).when_true);
+                    Facts {
+                        when_true,
+                        when_false: Vec::new(),
+                    }
+                }
+                OperatorTokens::Or => {
+                    let mut when_false = self.facts(lhs).when_false;
+                    when_false.extend(self.facts(rhs).when_false);
+                    Facts {
+                        when_true: Vec::new(),
+                        when_false,
+                    }
+                }
+                _ => Facts::default(),
+            },
+            EvaluableTokens::PrefixOperator(op, operand)
+                if op.operator() == &OperatorTokens::Not =>
+            {
+                let facts = self.facts(operand);
+                Facts {
+                    when_true: facts.when_false,
+                    when_false: facts.when_true,
+                }
+            }
+            // `T::is_null(x)`
+            EvaluableTokens::FunctionCall { function, args } => {
+                let EvaluableTokens::StaticAccess { parent: _, section } = function.token() else {
+                    return Facts::default();
+                };
+                match args.as_slice() {
+                    [arg] if section.name() == "is_null" => Facts {
+                        when_true: Vec::new(),
+                        when_false: self.value_path(arg).into_iter().collect(),
+                    },
+                    _ => Facts::default(),
+                }
+            }
+            _ => Facts::default(),
+        }
+    }
+
+    /// Returns the path holding the value `et` evaluates to e.g. `x` or `*a.b`
+    fn value_path(&self, et: &EvaluableToken) -> Option<Path> {
+        match et.token() {
+            EvaluableTokens::Name(name, _) => Some((self.lookup(name.name())?, Vec::new())),
+            EvaluableTokens::PrefixOperator(op, operand)
+                if op.operator() == &OperatorTokens::Multiply =>
+            {
+                self.referred_path(operand)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the path `et`, which evaluates to a reference, refers to e.g. `a.b` or `&x`
+    fn referred_path(&self, et: &EvaluableToken) -> Option<Path> {
+        match et.token() {
+            EvaluableTokens::DynamicAccess { parent, section } => {
+                let (var, mut fields) = self.container_path(parent)?;
+                fields.push(section.name().clone());
+                Some((var, fields))
+            }
+            EvaluableTokens::PrefixOperator(op, operand)
+                if op.operator() == &OperatorTokens::Reference =>
+            {
+                self.value_path(operand)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the path of the value whose attributes are accessed by `parent.attribute`
+    fn container_path(&self, parent: &EvaluableToken) -> Option<Path> {
+        match parent.token() {
+            EvaluableTokens::Name(name, _) => Some((self.lookup(name.name())?, Vec::new())),
+            EvaluableTokens::DynamicAccess { .. } => self.referred_path(parent),
+            _ => None,
+        }
+    }
+
+    /// Returns the method called by `parent.name(...)`, if it can be found
+    fn method_of(&mut self, parent: &EvaluableToken, name: &str) -> Option<FunctionID> {
+        let t = self.type_of(parent)?;
+        self.global_table
+            .get_impl_function_by_name(*t.type_id(), name)
+    }
+
+    /// Returns the type of an evaluable, if it can be found without compiling it
+    fn type_of(&mut self, et: &EvaluableToken) -> Option<TypeRef> {
+        match et.token() {
+            EvaluableTokens::Name(name, _) => self
+                .lookup(name.name())
+                .map(|variable| self.variables[variable].type_ref.clone()),
+            EvaluableTokens::DynamicAccess { parent, section } => {
+                let parent_type = self.type_of(parent)?;
+                self.attribute_type(&parent_type, section.name())
+                    .map(|t| t.plus_one_indirect())
+            }
+            EvaluableTokens::PrefixOperator(op, operand) => match op.operator() {
+                OperatorTokens::Reference => Some(self.type_of(operand)?.plus_one_indirect()),
+                OperatorTokens::Multiply => {
+                    let t = self.type_of(operand)?;
+                    t.indirection()
+                        .has_indirection()
+                        .then(|| t.minus_one_indirect())
+                }
+                _ => None,
+            },
+            EvaluableTokens::Index { parent, index: _ } => {
+                Some(self.type_of(parent)?.non_null().non_heap())
+            }
+            EvaluableTokens::FunctionCall { function, args: _ } => {
+                let function_id = match function.token() {
+                    EvaluableTokens::DynamicAccess { parent, section } => {
+                        self.method_of(parent, section.name())?
+                    }
+                    EvaluableTokens::StaticAccess { parent, section } => {
+                        let EvaluableTokens::Name(type_name, containing_class) = parent.token()
+                        else {
+                            return None;
+                        };
+                        let t = self
+                            .global_table
+                            .resolve_to_type_ref(
+                                &UnresolvedTypeRefToken::from_simple(
+                                    type_name.clone(),
+                                    containing_class.clone(),
+                                    type_name.location().clone(),
+                                ),
+                                None,
+                            )
+                            .ok()?;
+                        self.global_table
+                            .get_impl_function_by_name(*t.type_id(), section.name())?
+                    }
+                    _ => return None,
+                };
+                self.global_table
+                    .get_function_signature(function_id)
+                    .return_type()
+                    .clone()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns `state` with `paths` added
+fn with(state: &HashSet<Path>, paths: &[Path]) -> HashSet<Path> {
+    let mut state = state.clone();
+    state.extend(paths.iter().cloned());
+    state
+}
@@ -1,37 +1,227 @@
 use crate::root::assembler::assembly_builder::Assembly;
-use crate::root::builtin::{BuiltinInlineFunction, InlineFnGenerator};
+use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator, InterpretFn};
+use crate::root::compiler::assembly::heap_tracker::{
+    site_string, TRACKED_FREE, TRACKED_MALLOC, TRACKED_REALLOC,
+};
+use crate::root::compiler::assembly::panic::{panic_at, PanicRoutine};
+use crate::root::compiler::global_tracker::GlobalTracker;
 use crate::root::compiler::local_variable_table::LocalVariableTable;
 use crate::root::name_resolver::name_resolvers::GlobalTable;
 use crate::root::name_resolver::resolve_function_signatures::FunctionSignature;
+use crate::root::parser::location::Location;
 use crate::root::parser::parse_name::SimpleNameToken;
 use crate::root::parser::parse_parameters::SelfType;
-use crate::root::shared::common::{AddressedTypeRef, FunctionID, TypeID, TypeRef};
+use crate::root::shared::common::{
+    AddressedTypeRef, ByteSize, FunctionID, LocalAddress, TypeID, TypeRef,
+};
+use unique_type_id::UniqueTypeId;
+
+/// Size of the header at the start of every heap allocation, holding the number of values in it.
+/// References to heap allocations point past the header, to the first value
+pub const HEAP_HEADER_SIZE: usize = 8;
+
+/// Source of unique ids for the labels used when allocating
+#[derive(UniqueTypeId)]
+#[UniqueTypeIdType = "u16"]
+struct HeapAllocation;
+
+impl HeapAllocation {
+    const fn id() -> FunctionID {
+        f_id(HeapAllocation::unique_type_id().0)
+    }
+}
+
+/// Returns the assembly to store the number of values in `rcx` in the header of the allocation
+/// in `rax`, moving `rax` past the header. Null (a failed allocation) is left as it is
+fn write_header(global_tracker: &mut GlobalTracker) -> Assembly {
+    let null = global_tracker.get_unique_tag(HeapAllocation::id());
+    format!(
+        "    cmp rax, 0
+    je {null}
+    mov qword [rax], rcx
+    add rax, {HEAP_HEADER_SIZE}
+    {null}:\n"
+    )
+}
+
+/// Returns the assembly to move the reference in `rdi` back to the start of its allocation,
+/// before the header. Null is left as it is
+fn to_allocation_start() -> Assembly {
+    format!(
+        "    lea r8, [rdi-{HEAP_HEADER_SIZE}]
+    test rdi, rdi
+    cmovnz rdi, r8\n"
+    )
+}
+
+/// Returns the assembly to call `malloc` with the size in `rdi`, recording `location` if heap
+/// allocations are tracked
+fn call_malloc(location: &Location, global_tracker: &mut GlobalTracker) -> Assembly {
+    if *global_tracker.runtime_checks().heap() {
+        let site = site_string(location, global_tracker);
+        format!(
+            "    mov rsi, {site}
+    call {TRACKED_MALLOC}\n"
+        )
+    } else {
+        "    extern malloc
+    call malloc\n"
+            .to_string()
+    }
+}
 
 /// Allocates space for a type on the heap and return `(Assembly, [the address])`
 pub fn heap_alloc(
     t: TypeRef,
+    location: &Location,
     global_table: &mut GlobalTable,
     local_variable_table: &mut LocalVariableTable,
+    global_tracker: &mut GlobalTracker,
 ) -> (Assembly, AddressedTypeRef) {
     let size = global_table.get_size(&t).0;
     let sz = local_variable_table.stack_size().0;
-    let output =
-        global_table.add_local_variable_unnamed(t.plus_one_indirect(), local_variable_table);
+    // The allocation is owned by the caller so can be mutated
+    let output = global_table
+        .add_local_variable_unnamed(t.plus_one_indirect_heap(true), local_variable_table);
+    let call = call_malloc(location, global_tracker);
+    let header = write_header(global_tracker);
 
     (
         format!(
-            "    mov rdi, {size}
+            "    mov rdi, {}
     sub rsp, {sz}
-    extern malloc
-    call malloc
-    add rsp, {sz}
-    mov qword {}, rax\n",
+{call}    add rsp, {sz}
+    mov rcx, 1
+{header}    mov qword {}, rax\n",
+            size + HEAP_HEADER_SIZE,
             output.local_address()
         ),
         output,
     )
 }
 
+/// Allocates space for `length` (an integer) values of a type on the heap, reallocating the
+/// existing allocation `from` if given, and returns
+/// `(Assembly, [a heap array reference to the first value])`
+pub fn heap_alloc_array(
+    t: TypeRef,
+    length: LocalAddress,
+    from: Option<LocalAddress>,
+    location: &Location,
+    global_table: &mut GlobalTable,
+    local_variable_table: &mut LocalVariableTable,
+    global_tracker: &mut GlobalTracker,
+) -> (Assembly, AddressedTypeRef) {
+    let size = global_table.get_size(&t).0;
+    let sz = local_variable_table.stack_size().0;
+    // The allocation is owned by the caller so can be mutated
+    let output = global_table
+        .add_local_variable_unnamed(t.plus_one_indirect_heap(true), local_variable_table);
+
+    let arithmetic_checks = *global_tracker.runtime_checks().arithmetic();
+    let negative_check = if arithmetic_checks {
+        let non_negative = global_tracker.get_unique_tag(PanicRoutine::id());
+        let negative_panic = panic_at(
+            location,
+            "attempt to allocate a negative number of values",
+            global_tracker,
+        );
+        format!(
+            "    cmp rax, 0
+    jge {non_negative}
+{negative_panic}    {non_negative}:\n"
+        )
+    } else {
+        String::new()
+    };
+    let mut overflow_check = || {
+        if !arithmetic_checks {
+            return String::new();
+        }
+        let no_overflow = global_tracker.get_unique_tag(PanicRoutine::id());
+        let overflow_panic = panic_at(
+            location,
+            "attempt to allocate with overflow",
+            global_tracker,
+        );
+        format!(
+            "    jno {no_overflow}
+{overflow_panic}    {no_overflow}:\n"
+        )
+    };
+    let values_overflow_check = overflow_check();
+    let header_overflow_check = overflow_check();
+
+    let call = match from {
+        None => format!(
+            "    mov rdi, rax
+{}",
+            call_malloc(location, global_tracker)
+        ),
+        Some(from) if *global_tracker.runtime_checks().heap() => {
+            let site = site_string(location, global_tracker);
+            format!(
+                "    mov rdi, qword {from}
+{}    mov rsi, rax
+    mov rdx, {site}
+    call {TRACKED_REALLOC}\n",
+                to_allocation_start()
+            )
+        }
+        Some(from) => format!(
+            "    mov rdi, qword {from}
+{}    mov rsi, rax
+    extern realloc
+    call realloc\n",
+            to_allocation_start()
+        ),
+    };
+    let header = write_header(global_tracker);
+
+    (
+        format!(
+            "    mov rax, qword {length}
+{negative_check}    mov rcx, {size}
+    imul rax, rcx
+{values_overflow_check}    add rax, {HEAP_HEADER_SIZE}
+{header_overflow_check}    sub rsp, {sz}
+{call}    add rsp, {sz}
+    mov rcx, qword {length}
+{header}    mov qword {}, rax\n",
+            output.local_address()
+        ),
+        output,
+    )
+}
+
+/// Frees the heap allocation `pointer` (a reference to its first value) points to, recording `location` if heap
+/// allocations are tracked
+pub fn heap_free(
+    pointer: LocalAddress,
+    sz: ByteSize,
+    location: &Location,
+    global_tracker: &mut GlobalTracker,
+) -> Assembly {
+    let call = if *global_tracker.runtime_checks().heap() {
+        let site = site_string(location, global_tracker);
+        format!(
+            "    mov rsi, {site}
+    call {TRACKED_FREE}\n"
+        )
+    } else {
+        "    extern free
+    call free\n"
+            .to_string()
+    };
+
+    format!(
+        "    mov rdi, qword {pointer}
+{}    sub rsp, {sz}
+{call}    add rsp, {sz}\n",
+        to_allocation_start()
+    )
+}
+
 /// `free` function for deallocating heap memory
 pub struct FreeFunction {
     id: FunctionID,
@@ -59,15 +249,16 @@ impl BuiltinInlineFunction for FreeFunction {
     }
 
     fn inline(&self) -> InlineFnGenerator {
-        |args, _, _, sz| -> Assembly {
-            let to_free = &args[0];
-            format!(
-                "    mov rdi, qword {to_free}
-    sub rsp, {sz}
-    extern free
-    call free
-    add rsp, {sz}\n"
-            )
+        |args, _, gt, sz| -> Assembly {
+            let location = gt.call_location().clone();
+            heap_free(args[0], sz, &location, gt)
+        }
+    }
+
+    fn interpret(&self) -> InterpretFn {
+        |args, _, interpreter| {
+            let pointer = interpreter.pointer(args[0])?;
+            interpreter.free(pointer)
         }
     }
 
@@ -0,0 +1,253 @@
+use crate::root::assembler::assembly_builder::Assembly;
+use crate::root::builtin::f_id;
+use crate::root::compiler::assembly::heap::HEAP_HEADER_SIZE;
+use crate::root::compiler::assembly::panic::PANIC_EXIT_CODE;
+use crate::root::compiler::global_tracker::GlobalTracker;
+use crate::root::parser::location::Location;
+use crate::root::shared::common::FunctionID;
+use unique_type_id::UniqueTypeId;
+
+// Every tracked allocation has a record on the heap laid out as:
+//   [+0]  next record
+//   [+8]  address of the allocation
+//   [+16] location the allocation was made (string)
+//   [+24] location the allocation was freed (string), once freed
+//   [+32] size in bytes, including the header (see `HEAP_HEADER_SIZE`)
+// Records start in the live list and are moved to the freed list when freed so that double frees
+// can be told apart from frees of memory that was never heap allocated
+
+/// Tracked replacement for `malloc`. Takes the size in `rdi` and allocation location in `rsi`
+pub const TRACKED_MALLOC: &str = "__whython_malloc";
+/// Tracked replacement for `realloc`. Takes the allocation in `rdi`, the new size in `rsi` and the
+/// reallocation location in `rdx`
+pub const TRACKED_REALLOC: &str = "__whython_realloc";
+/// Tracked replacement for `free`. Takes the allocation in `rdi` and the free location in `rsi`
+pub const TRACKED_FREE: &str = "__whython_free";
+/// Prints the allocations that were never freed
+const LEAK_REPORT: &str = "__whython_leak_report";
+
+/// Source of unique ids for the location strings of allocation sites
+#[derive(UniqueTypeId)]
+#[UniqueTypeIdType = "u16"]
+pub struct HeapTracker;
+
+impl HeapTracker {
+    const fn id() -> FunctionID {
+        f_id(HeapTracker::unique_type_id().0)
+    }
+}
+
+/// Stores the location of an allocation or free as a string, returning its label
+pub fn site_string(location: &Location, gt: &mut GlobalTracker) -> String {
+    let id = format!("{}_hstr", gt.get_unique_tag(HeapTracker::id()));
+    let site = location
+        .short_position(gt.path_storage())
+        .replace('\\', "\\\\")
+        .replace('`', "\\`");
+    let data = format!("{id} db `{site}`,0");
+    gt.add_readonly_data(&id, &data);
+    id
+}
+
+/// Registers the leak report to run when `main` returns. Must be placed at the start of `main`
+pub fn register_leak_report() -> Assembly {
+    format!(
+        "    mov rdi, {LEAK_REPORT}
+    extern atexit
+    call atexit\n"
+    )
+}
+
+/// The tracked allocation routines and the data they use
+pub fn heap_tracker_routines() -> Assembly {
+    format!(
+        "__whython_heap_find:
+    mov rax, rsi
+.next:
+    mov rdx, [rax]
+    cmp rdx, 0
+    je .missing
+    cmp [rdx+8], rdi
+    je .found
+    mov rax, rdx
+    jmp .next
+.missing:
+    mov rax, 0
+.found:
+    ret
+
+{TRACKED_MALLOC}:
+    push rbp
+    mov rbp, rsp
+    and rsp, -16
+    sub rsp, 32
+    mov [rsp], rsi
+    mov [rsp+16], rdi
+    extern malloc
+    call malloc
+    mov [rsp+8], rax
+    mov rdi, 40
+    call malloc
+    mov rcx, [rsp+8]
+    mov [rax+8], rcx
+    mov rcx, [rsp]
+    mov [rax+16], rcx
+    mov rcx, [rsp+16]
+    mov [rax+32], rcx
+    mov rcx, [rel __whython_heap_live]
+    mov [rax], rcx
+    mov [rel __whython_heap_live], rax
+    mov rax, [rsp+8]
+    leave
+    ret
+
+{TRACKED_REALLOC}:
+    cmp rdi, 0
+    jne .existing
+    mov rdi, rsi
+    mov rsi, rdx
+    jmp {TRACKED_MALLOC}
+.existing:
+    push rbp
+    mov rbp, rsp
+    and rsp, -16
+    sub rsp, 32
+    mov [rsp], rdi
+    mov [rsp+8], rsi
+    mov [rsp+16], rdx
+    lea rsi, [rel __whython_heap_live]
+    call __whython_heap_find
+    cmp rax, 0
+    jne .found
+    mov rdi, [rsp]
+    mov rsi, [rsp+16]
+    jmp __whython_heap_invalid
+.found:
+    mov rax, [rax]
+    mov [rsp+24], rax
+    mov rdi, [rsp]
+    mov rsi, [rsp+8]
+    extern realloc
+    call realloc
+    mov rcx, [rsp+24]
+    mov [rcx+8], rax
+    mov rdx, [rsp+16]
+    mov [rcx+16], rdx
+    mov rdx, [rsp+8]
+    mov [rcx+32], rdx
+    leave
+    ret
+
+{TRACKED_FREE}:
+    push rbp
+    mov rbp, rsp
+    and rsp, -16
+    sub rsp, 16
+    cmp rdi, 0
+    je .done
+    mov [rsp], rdi
+    mov [rsp+8], rsi
+    lea rsi, [rel __whython_heap_live]
+    call __whython_heap_find
+    cmp rax, 0
+    jne .found
+    mov rdi, [rsp]
+    mov rsi, [rsp+8]
+    jmp __whython_heap_invalid
+.found:
+    mov rdx, [rax]
+    mov rcx, [rdx]
+    mov [rax], rcx
+    mov rcx, [rel __whython_heap_freed]
+    mov [rdx], rcx
+    mov [rel __whython_heap_freed], rdx
+    mov rcx, [rsp+8]
+    mov [rdx+24], rcx
+    mov rdi, [rsp]
+    extern free
+    call free
+.done:
+    leave
+    ret
+
+__whython_heap_invalid:
+    and rsp, -16
+    sub rsp, 16
+    mov [rsp], rdi
+    mov [rsp+8], rsi
+    mov rdi, 0
+    extern fflush
+    call fflush
+    mov rdi, [rsp]
+    lea rsi, [rel __whython_heap_freed]
+    call __whython_heap_find
+    cmp rax, 0
+    je .not_heap
+    mov rax, [rax]
+    mov rdi, 2
+    mov rsi, __whython_double_free_fstr
+    mov rdx, [rsp+8]
+    mov rcx, [rax+16]
+    mov r8, [rax+24]
+    mov al, 0
+    extern dprintf
+    call dprintf
+    jmp .exit
+.not_heap:
+    mov rdi, 2
+    mov rsi, __whython_invalid_free_fstr
+    mov rdx, [rsp+8]
+    mov al, 0
+    call dprintf
+.exit:
+    mov rax, 60
+    mov rdi, {PANIC_EXIT_CODE}
+    syscall
+
+{LEAK_REPORT}:
+    push rbp
+    mov rbp, rsp
+    and rsp, -16
+    sub rsp, 16
+    mov rax, [rel __whython_heap_live]
+    cmp rax, 0
+    je .done
+    mov [rsp], rax
+    mov rdi, 0
+    call fflush
+    mov rdi, 2
+    mov rsi, __whython_leak_header_fstr
+    mov al, 0
+    call dprintf
+.next:
+    mov rax, [rsp]
+    cmp rax, 0
+    je .done
+    mov rcx, [rax]
+    mov [rsp], rcx
+    mov rdi, 2
+    mov rsi, __whython_leak_fstr
+    mov rdx, [rax+32]
+    sub rdx, {HEAP_HEADER_SIZE}
+    mov rcx, [rax+16]
+    mov al, 0
+    call dprintf
+    jmp .next
+.done:
+    leave
+    ret
+
+section .bss
+    __whython_heap_live resq 1
+    __whython_heap_freed resq 1
+
+section .data_readonly
+    __whython_double_free_fstr db `Panicked at %s: double free of memory allocated at %s and already freed at %s\\n`,0
+    __whython_invalid_free_fstr db `Panicked at %s: free of memory that isn't heap allocated\\n`,0
+    __whython_leak_header_fstr db `Leak report: heap memory was never freed\\n`,0
+    __whython_leak_fstr db `    %ld bytes allocated at %s\\n`,0
+
+section .text
+"
+    )
+}
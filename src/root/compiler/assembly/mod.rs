@@ -1,3 +1,6 @@
 pub mod heap;
+pub mod heap_tracker;
 pub mod null;
+pub mod panic;
+pub mod test_harness;
 pub mod utils;
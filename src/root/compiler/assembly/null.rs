@@ -1,13 +1,13 @@
 use crate::root::assembler::assembly_builder::Assembly;
 use crate::root::builtin::types::bool::BoolType;
-use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator};
+use crate::root::builtin::{f_id, BuiltinInlineFunction, InlineFnGenerator, InterpretFn};
 use crate::root::name_resolver::resolve_function_signatures::FunctionSignature;
 use crate::root::parser::parse_name::SimpleNameToken;
 use crate::root::parser::parse_parameters::SelfType;
 use crate::root::shared::common::{FunctionID, LocalAddress, TypeID};
 use unique_type_id::UniqueTypeId;
 
-/// `null` function that returns a null pointer with a specified type
+/// `null` function that returns a null (mutable) pointer with a specified type
 pub struct NullFunction {
     id: FunctionID,
     parent_type: TypeID,
@@ -26,7 +26,7 @@ impl BuiltinInlineFunction for NullFunction {
         FunctionSignature::new(
             SelfType::None,
             vec![],
-            Some(self.parent_type.with_indirection_single(1)),
+            Some(self.parent_type.mutable_reference_single()),
         )
     }
 
@@ -37,6 +37,10 @@ impl BuiltinInlineFunction for NullFunction {
         }
     }
 
+    fn interpret(&self) -> InterpretFn {
+        |_, return_into, interpreter| interpreter.set_pointer(return_into.unwrap(), 0)
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         Some(self.parent_type)
     }
@@ -104,6 +108,14 @@ impl BuiltinInlineFunction for IsNullFunction {
             )
         }
     }
+
+    fn interpret(&self) -> InterpretFn {
+        |args, return_into, interpreter| {
+            let pointer = interpreter.pointer(args[0])?;
+            interpreter.set_bool(return_into.unwrap(), pointer == 0)
+        }
+    }
+
     fn parent_type(&self) -> Option<TypeID> {
         Some(self.parent_type)
     }
@@ -0,0 +1,157 @@
+use crate::root::assembler::assembly_builder::Assembly;
+use crate::root::builtin::f_id;
+use crate::root::compiler::assembly::heap::HEAP_HEADER_SIZE;
+use crate::root::compiler::global_tracker::GlobalTracker;
+use crate::root::parser::location::Location;
+use crate::root::shared::common::{FunctionID, LocalAddress};
+use unique_type_id::UniqueTypeId;
+
+/// Label of the routine shared by all runtime panics
+const PANIC_ROUTINE: &str = "__whython_panic";
+
+/// Exit code of a program that panics
+pub const PANIC_EXIT_CODE: u8 = 101;
+
+/// Shared routine jumped to when a runtime check fails. Expects the message address in `rsi` and
+/// its length in `rdx`
+#[derive(UniqueTypeId)]
+#[UniqueTypeIdType = "u16"]
+pub struct PanicRoutine;
+
+impl PanicRoutine {
+    pub const fn id() -> FunctionID {
+        f_id(PanicRoutine::unique_type_id().0)
+    }
+}
+
+/// Escapes text for use in a NASM backtick string
+fn escape_nasm(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('`', "\\`")
+}
+
+/// Jumps to the panic routine, printing `message` and the location to stderr before exiting
+pub fn panic_at(location: &Location, message: &str, gt: &mut GlobalTracker) -> Assembly {
+    gt.use_panic();
+
+    let id = format!("{}_pstr", gt.get_unique_tag(PanicRoutine::id()));
+    let text = format!(
+        "Panicked at {}: {message}",
+        location.short_position(gt.path_storage())
+    );
+    let data = format!(
+        "{id} db `{}\\n`\n    {id}_len equ $ - {id}",
+        escape_nasm(&text)
+    );
+    gt.add_readonly_data(&id, &data);
+
+    format!(
+        "    mov rsi, {id}
+    mov rdx, {id}_len
+    jmp {PANIC_ROUTINE}\n"
+    )
+}
+
+/// Panics if the qword `index` is not less than the number of values in the heap allocation the
+/// heap array reference (`&[T]`) `reference` points to. Negative indices are treated as
+/// out-of-bounds. Emits nothing if bounds checks are disabled
+pub fn bounds_check(
+    reference: LocalAddress,
+    index: LocalAddress,
+    location: &Location,
+    gt: &mut GlobalTracker,
+) -> Assembly {
+    if !gt.runtime_checks().bounds() {
+        return String::new();
+    }
+
+    let in_bounds = gt.get_unique_tag(PanicRoutine::id());
+    let panic = panic_at(location, "index out of bounds", gt);
+
+    // The number of values is in the header before the first. Unsigned comparison so negative
+    // indices are also out-of-bounds
+    format!(
+        "    mov rax, qword {reference}
+    mov rcx, qword {index}
+    cmp rcx, qword [rax-{HEAP_HEADER_SIZE}]
+    jb {in_bounds}
+{panic}    {in_bounds}:\n"
+    )
+}
+
+/// Panics if the last arithmetic instruction overflowed. Emits nothing if arithmetic checks are
+/// disabled
+pub fn overflow_check(operation: &str, gt: &mut GlobalTracker) -> Assembly {
+    if !gt.runtime_checks().arithmetic() {
+        return String::new();
+    }
+
+    let no_overflow = gt.get_unique_tag(PanicRoutine::id());
+    let location = gt.call_location().clone();
+    let panic = panic_at(
+        &location,
+        &format!("attempt to {operation} with overflow"),
+        gt,
+    );
+
+    format!(
+        "    jno {no_overflow}
+{panic}    {no_overflow}:\n"
+    )
+}
+
+/// Panics if dividing `rax` by the `divisor` register would divide by zero or overflow. Emits
+/// nothing if arithmetic checks are disabled
+pub fn division_check(divisor: &str, operation: &str, gt: &mut GlobalTracker) -> Assembly {
+    if !gt.runtime_checks().arithmetic() {
+        return String::new();
+    }
+
+    let non_zero = gt.get_unique_tag(PanicRoutine::id());
+    let no_overflow = gt.get_unique_tag(PanicRoutine::id());
+    let location = gt.call_location().clone();
+    let zero_panic = panic_at(
+        &location,
+        &format!("attempt to {operation} by zero"),
+        gt,
+    );
+    let overflow_panic = panic_at(
+        &location,
+        &format!("attempt to {operation} with overflow"),
+        gt,
+    );
+
+    // The smallest integer divided by -1 doesn't fit in an integer
+    format!(
+        "    cmp {divisor}, 0
+    jne {non_zero}
+{zero_panic}    {non_zero}:
+    cmp {divisor}, -1
+    jne {no_overflow}
+    mov r8, 0x8000000000000000
+    cmp rax, r8
+    jne {no_overflow}
+{overflow_panic}    {no_overflow}:\n"
+    )
+}
+
+/// The shared panic routine. Flushes stdout so earlier output isn't lost, writes the message to
+/// stderr then exits
+pub fn panic_routine() -> Assembly {
+    format!(
+        "{PANIC_ROUTINE}:
+    mov r12, rsi
+    mov r13, rdx
+    and rsp, -16
+    mov rdi, 0
+    extern fflush
+    call fflush
+    mov rax, 1
+    mov rdi, 2
+    mov rsi, r12
+    mov rdx, r13
+    syscall
+    mov rax, 60
+    mov rdi, {PANIC_EXIT_CODE}
+    syscall\n"
+    )
+}
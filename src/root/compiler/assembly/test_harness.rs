@@ -0,0 +1,177 @@
+use crate::root::assembler::assembly_builder::{Assembly, AssemblyBuilder};
+use crate::root::builtin::f_id;
+use crate::root::compiler::assembly::heap_tracker::register_leak_report;
+use crate::root::compiler::assembly::utils::align_16_bytes;
+use crate::root::compiler::global_tracker::GlobalTracker;
+use crate::root::parser::location::Location;
+use crate::root::shared::common::{ByteSize, FunctionID};
+use unique_type_id::UniqueTypeId;
+
+/// Source of unique ids for the labels and strings of the test harness
+#[derive(UniqueTypeId)]
+#[UniqueTypeIdType = "u16"]
+pub struct TestHarness;
+
+impl TestHarness {
+    const fn id() -> FunctionID {
+        f_id(TestHarness::unique_type_id().0)
+    }
+}
+
+/// A function marked `#[test]` to be run by the harness
+pub struct TestFunction {
+    pub id: FunctionID,
+    pub name: String,
+    pub location: Location,
+}
+
+/// Stores a 0-terminated string, returning its label
+fn c_string(text: &str, gt: &mut GlobalTracker) -> String {
+    let id = format!("{}_tstr", gt.get_unique_tag(TestHarness::id()));
+    let data = format!(
+        "{id} db `{}`,0",
+        text.replace('\\', "\\\\")
+            .replace('`', "\\`")
+            .replace('\n', "\\n")
+    );
+    gt.add_readonly_data(&id, &data);
+    id
+}
+
+/// Builds a `main` that runs every test in its own forked process so that a panicking test doesn't
+/// stop the others. A test fails if its process exits with a non-zero code. The names and
+/// locations of failing tests are reported at the end and `main` returns 1 if any test failed
+pub fn test_harness(tests: &[TestFunction], gt: &mut GlobalTracker) -> Assembly {
+    // [rbp-8] - wait status, [rbp-16] - failure count, [rbp-24-8i] - whether test i failed
+    let stack_size = align_16_bytes(ByteSize(24 + 8 * tests.len())).0;
+    let result = |i: usize| 24 + 8 * i;
+
+    let running = c_string(
+        &format!(
+            "\nrunning {} test{}\n",
+            tests.len(),
+            if tests.len() == 1 { "" } else { "s" }
+        ),
+        gt,
+    );
+    let ok = c_string("test %s ... ok\n", gt);
+    let failed = c_string("test %s ... FAILED (exit code %ld)\n", gt);
+    let failures = c_string("\nfailures:\n", gt);
+    let failure = c_string("    %s at %s\n", gt);
+    let summary = c_string("\ntest result: %s. %ld passed; %ld failed\n", gt);
+    let summary_ok = c_string("ok", gt);
+    let summary_failed = c_string("FAILED", gt);
+
+    let mut ab = AssemblyBuilder::new();
+    ab.toplevel("main:");
+    ab.line("push rbp");
+    ab.line("mov rbp, rsp");
+    if *gt.runtime_checks().heap() {
+        // Inherited by each test's process
+        ab.other(&register_leak_report());
+    }
+    ab.line(&format!("sub rsp, {stack_size}"));
+    ab.line("mov qword [rbp-16], 0");
+    ab.line(&format!("mov rdi, {running}"));
+    ab.line("mov al, 0");
+    ab.line("extern printf");
+    ab.line("call printf");
+
+    let mut names = Vec::with_capacity(tests.len());
+    for (i, test) in tests.iter().enumerate() {
+        let name = c_string(&test.name, gt);
+        let position = c_string(&test.location.short_position(gt.path_storage()), gt);
+        let parent = gt.get_unique_tag(TestHarness::id());
+        let test_failed = gt.get_unique_tag(TestHarness::id());
+        let next = gt.get_unique_tag(TestHarness::id());
+
+        // Flush so buffered output isn't written by both processes
+        ab.line("mov rdi, 0");
+        ab.line("extern fflush");
+        ab.line("call fflush");
+        ab.line("extern fork");
+        ab.line("call fork");
+        ab.line("cmp rax, 0");
+        ab.line(&format!("jne {parent}"));
+
+        // Child - run the test then exit successfully
+        ab.line(&format!("call {}", test.id.string_id()));
+        ab.line("mov rdi, 0");
+        ab.line("extern exit");
+        ab.line("call exit");
+
+        // Parent - wait for the test and report it
+        ab.line(&format!("{parent}:"));
+        ab.line("mov rdi, rax");
+        ab.line("lea rsi, [rbp-8]");
+        ab.line("mov rdx, 0");
+        ab.line("extern waitpid");
+        ab.line("call waitpid");
+        ab.line(&format!("mov qword [rbp-{}], 0", result(i)));
+        ab.line("mov eax, dword [rbp-8]");
+        ab.line("cmp eax, 0");
+        ab.line(&format!("jne {test_failed}"));
+        ab.line(&format!("mov rdi, {ok}"));
+        ab.line(&format!("mov rsi, {name}"));
+        ab.line("mov al, 0");
+        ab.line("call printf");
+        ab.line(&format!("jmp {next}"));
+        ab.line(&format!("{test_failed}:"));
+        ab.line("add qword [rbp-16], 1");
+        ab.line(&format!("mov qword [rbp-{}], 1", result(i)));
+        // The exit code is the second byte of the wait status
+        ab.line("mov edx, dword [rbp-8]");
+        ab.line("shr edx, 8");
+        ab.line("and edx, 0xff");
+        ab.line(&format!("mov rdi, {failed}"));
+        ab.line(&format!("mov rsi, {name}"));
+        ab.line("mov al, 0");
+        ab.line("call printf");
+        ab.line(&format!("{next}:"));
+
+        names.push((name, position));
+    }
+
+    // List failures
+    let all_passed = gt.get_unique_tag(TestHarness::id());
+    ab.line("cmp qword [rbp-16], 0");
+    ab.line(&format!("je {all_passed}"));
+    ab.line(&format!("mov rdi, {failures}"));
+    ab.line("mov al, 0");
+    ab.line("call printf");
+    for (i, (name, position)) in names.iter().enumerate() {
+        let passed = gt.get_unique_tag(TestHarness::id());
+        ab.line(&format!("cmp qword [rbp-{}], 0", result(i)));
+        ab.line(&format!("je {passed}"));
+        ab.line(&format!("mov rdi, {failure}"));
+        ab.line(&format!("mov rsi, {name}"));
+        ab.line(&format!("mov rdx, {position}"));
+        ab.line("mov al, 0");
+        ab.line("call printf");
+        ab.line(&format!("{passed}:"));
+    }
+    ab.line(&format!("{all_passed}:"));
+
+    // Summary
+    let print_summary = gt.get_unique_tag(TestHarness::id());
+    ab.line(&format!("mov rsi, {summary_ok}"));
+    ab.line("cmp qword [rbp-16], 0");
+    ab.line(&format!("je {print_summary}"));
+    ab.line(&format!("mov rsi, {summary_failed}"));
+    ab.line(&format!("{print_summary}:"));
+    ab.line(&format!("mov rdi, {summary}"));
+    ab.line(&format!("mov rdx, {}", tests.len()));
+    ab.line("sub rdx, qword [rbp-16]");
+    ab.line("mov rcx, qword [rbp-16]");
+    ab.line("mov al, 0");
+    ab.line("call printf");
+
+    // Exit code
+    ab.line("mov rax, 0");
+    ab.line("cmp qword [rbp-16], 0");
+    ab.line("setne al");
+    ab.line("leave");
+    ab.line("ret");
+
+    ab.finish()
+}
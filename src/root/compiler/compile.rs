@@ -2,22 +2,29 @@ use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
 use crate::root::assembler::assembly_builder::Assembly;
+use crate::root::compiler::assembly::heap_tracker::heap_tracker_routines;
+use crate::root::compiler::assembly::panic::panic_routine;
+use crate::root::compiler::assembly::test_harness::{test_harness, TestFunction};
 use crate::root::compiler::compile_function::compile_function;
-use crate::root::compiler::global_tracker::GlobalTracker;
+use crate::root::compiler::global_tracker::{GlobalTracker, RuntimeChecks};
 use crate::root::errors::WErr;
 use crate::root::name_resolver::name_resolvers::GlobalTable;
 use crate::root::parser::parse_function::FunctionToken;
 use crate::root::parser::path_storage::PathStorage;
 use crate::root::shared::common::FunctionID;
 use crate::root::unrandom::{new_hashmap, new_hashset};
-#[cfg(debug_assertions)]
 use itertools::Itertools;
 
 /// Compiles the entire program. Returns assembly.
+///
+/// If `test` is set, the functions marked `#[test]` are compiled instead of `main` along with a
+/// harness `main` that runs them
 pub fn compile(
-    mut global_table: GlobalTable,
+    global_table: &mut GlobalTable,
     unprocessed_functions: HashMap<FunctionID, FunctionToken>,
     path_storage: &PathStorage,
+    runtime_checks: RuntimeChecks,
+    test: bool,
 ) -> Result<Assembly, WErr> {
     let mut unprocessed_functions = unprocessed_functions;
     // TODO: Write assembly to disk asynchronously while compiling
@@ -31,8 +38,27 @@ pub fn compile(
     // Last time progress was shown
     let mut last_shown = Instant::now();
 
-    open_set.insert(FunctionID::MAIN_FUNCTION); // Start with main
-    let mut global_tracker = GlobalTracker::new(path_storage);
+    // Start with the tests or main
+    let tests = if test {
+        unprocessed_functions
+            .iter()
+            .filter(|(_, ft)| *ft.is_test())
+            .map(|(id, ft)| TestFunction {
+                id: *id,
+                name: ft.name().name().clone(),
+                location: ft.location().clone(),
+            })
+            .sorted_by_key(|t| t.id.0)
+            .collect_vec()
+    } else {
+        Vec::new()
+    };
+    if test {
+        open_set.extend(tests.iter().map(|t| t.id));
+    } else {
+        open_set.insert(FunctionID::MAIN_FUNCTION);
+    }
+    let mut global_tracker = GlobalTracker::new(path_storage, runtime_checks);
 
     while !open_set.is_empty() {
         // Reset state tracked during function compilation
@@ -61,7 +87,7 @@ pub fn compile(
         let compiled = compile_function(
             current_function,
             current_function_token,
-            &mut global_table,
+            global_table,
             &mut global_tracker,
         )?;
 
@@ -116,6 +142,23 @@ section .text
         asm += "\n\n";
     }
 
+    if test {
+        asm += &test_harness(&tests, &mut global_tracker);
+        asm += "\n\n";
+    }
+
+    // Add the panic routine if any runtime check can fail
+    if *global_tracker.panic_used() {
+        asm += &panic_routine();
+        asm += "\n\n";
+    }
+
+    // Add tracked allocation routines
+    if *runtime_checks.heap() {
+        asm += &heap_tracker_routines();
+        asm += "\n\n";
+    }
+
     // Add static data
     if !global_tracker.readonly_data_section().is_empty() {
         asm += "section .data_readonly";
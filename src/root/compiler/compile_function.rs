@@ -4,18 +4,23 @@ use color_print::cprintln;
 use crate::root::assembler::assembly_builder::{Assembly, AssemblyBuilder};
 use crate::root::builtin::types::bool::BoolType;
 use crate::root::builtin::types::int::IntType;
+use crate::root::compiler::assembly::heap_tracker::register_leak_report;
+use crate::root::compiler::analysis::control_flow::ControlFlowGraph;
+use crate::root::compiler::analysis::escape::check_escapes;
+use crate::root::compiler::analysis::initialisation::check_initialisation;
+use crate::root::compiler::analysis::nullability::check_nullability;
 use crate::root::compiler::evaluation::into::compile_evaluable_into;
 use crate::root::compiler::evaluation::reference::compile_evaluable_reference;
 use crate::root::compiler::global_tracker::GlobalTracker;
-use crate::root::compiler::local_variable_table::LocalVariableTable;
+use crate::root::compiler::local_variable_table::{Binding, LocalVariableTable};
 use crate::root::errors::compiler_errors::CompErrs;
 use crate::root::errors::WErr;
-use crate::root::name_resolver::name_resolvers::GlobalTable;
+use crate::root::name_resolver::name_resolvers::{GlobalTable, NameResult};
+use crate::root::parser::parse_function::parse_evaluable::EvaluableTokens;
 use crate::root::parser::parse_function::parse_line::LineTokens;
 use crate::root::parser::parse_function::FunctionToken;
 use crate::root::shared::common::AddressedTypeRef;
 use crate::root::shared::common::{FunctionID, Indirection, LocalAddress, TypeRef};
-use crate::root::utils::warn;
 
 /// Compiles a given function into assembly
 pub fn compile_function(
@@ -26,7 +31,8 @@ pub fn compile_function(
 ) -> Result<Assembly, WErr> {
     let mut local_variables = LocalVariableTable::new();
 
-    let (_location, end_location, _name, return_type, _, parameters, lines) = function.dissolve();
+    let (_location, end_location, _name, return_type, _, parameters, lines, _, _) =
+        function.dissolve();
 
     let return_type = if fid.is_main() { None } else { return_type };
 
@@ -40,13 +46,17 @@ pub fn compile_function(
     let mut param_address = LocalAddress(16);
 
     // Put all parameters in local_variables
-    for (param_name, param_type) in parameters {
-        let t = global_table.resolve_to_type_ref(&param_type, None)?;
+    for (param_name, param_type) in &parameters {
+        let t = global_table.resolve_to_type_ref(param_type, None)?;
 
-        local_variables.add_existing(
+        // Parameters can't be reassigned (though mutable references can be mutated through)
+        let binding = Binding::new(
             param_name.name().clone(),
-            AddressedTypeRef::new(param_address, t.clone()),
+            false,
+            param_name.location().clone(),
         );
+        global_table.add_variable_definition(&binding, &t);
+        local_variables.add_existing(binding, AddressedTypeRef::new(param_address, t.clone()));
 
         param_address += LocalAddress(global_table.get_size(&t).0 as isize);
     }
@@ -59,8 +69,40 @@ pub fn compile_function(
         )
     });
 
+    // Analyse control flow
+    let control_flow = ControlFlowGraph::new(&lines, |et| {
+        let EvaluableTokens::FunctionCall { function, args: _ } = et.token() else {
+            return false;
+        };
+        let EvaluableTokens::Name(name, containing_class) = function.token() else {
+            return false;
+        };
+        matches!(
+            global_table.resolve_name(
+                name,
+                None,
+                containing_class.as_ref(),
+                &local_variables,
+                global_tracker,
+            ),
+            Ok(NameResult::Function(f)) if global_table.is_diverging(f)
+        )
+    });
+    control_flow.warn_unreachable(global_tracker);
+    let all_paths_return = control_flow.all_paths_return();
+    let initialising_assignments = check_initialisation(&lines, global_table)?;
+    global_tracker.set_initialising_assignments(initialising_assignments);
+    check_escapes(
+        &parameters,
+        return_variable.as_ref().map(|r| r.type_ref()),
+        &lines,
+        global_table,
+    )?;
+    let non_null_values = check_nullability(&parameters, &lines, global_table)?;
+    global_tracker.set_non_null_values(non_null_values);
+
     // Compile
-    let (mut full_contents, last_return) = recursively_compile_lines(
+    let mut full_contents = recursively_compile_lines(
         fid,
         &lines,
         &return_variable,
@@ -73,7 +115,7 @@ pub fn compile_function(
     // let stack_size = local_variables.stack_size();
 
     // Check for incorrect return
-    if (return_variable.is_some() || fid.is_main()) && !last_return {
+    if (return_variable.is_some() || fid.is_main()) && !all_paths_return {
         let type_ref = return_variable
             .map(|x| x.type_ref().clone())
             .unwrap_or_else(|| IntType::id().immediate_single());
@@ -83,18 +125,26 @@ pub fn compile_function(
         );
     }
 
-    // Add implicit return code if last line of function isn't 'return'
-    if !last_return {
+    // Add implicit return code if the end of the function can be reached
+    if !all_paths_return {
         full_contents += "\nleave\nret";
     }
 
+    // Report leaks when `main` returns
+    let leak_report = if fid.is_main() && *global_tracker.runtime_checks().heap() {
+        register_leak_report()
+    } else {
+        String::new()
+    };
+
     // Construct assembly
     let final_contents = format!(
         "{}:
     push rbp
     mov rbp, rsp
-{}",
+{}{}",
         fid.string_id(),
+        leak_report,
         full_contents
     );
 
@@ -106,7 +156,7 @@ pub fn compile_function(
 }
 
 /// Recursively compiles lines provided to it e.g. function body, while body, etc. Returns assembly
-fn recursively_compile_lines(
+pub fn recursively_compile_lines(
     fid: FunctionID,
     lines: &[LineTokens],
     return_variable: &Option<AddressedTypeRef>,
@@ -114,33 +164,58 @@ fn recursively_compile_lines(
     local_variables: &mut LocalVariableTable,
     global_table: &mut GlobalTable,
     global_tracker: &mut GlobalTracker,
-) -> Result<(Assembly, bool), WErr> {
+) -> Result<Assembly, WErr> {
     // Enter a new scope for variables
     local_variables.enter_scope();
 
-    let mut contents = AssemblyBuilder::new();
+    let contents = compile_lines_in_scope(
+        fid,
+        lines,
+        return_variable,
+        break_tag,
+        local_variables,
+        global_table,
+        global_tracker,
+    )?;
 
-    // Tracks whether the last line is a return statement
-    let mut last_is_return = false;
+    // Put variables out of scope
+    local_variables.leave_scope();
 
-    for (line_i, line) in lines.iter().enumerate() {
-        last_is_return = false;
+    Ok(contents)
+}
+
+/// Compiles lines in the current variable scope, leaving any variables they define in scope
+pub fn compile_lines_in_scope(
+    fid: FunctionID,
+    lines: &[LineTokens],
+    return_variable: &Option<AddressedTypeRef>,
+    break_tag: &Option<&str>,
+    local_variables: &mut LocalVariableTable,
+    global_table: &mut GlobalTable,
+    global_tracker: &mut GlobalTracker,
+) -> Result<Assembly, WErr> {
+    let mut contents = AssemblyBuilder::new();
+
+    for line in lines {
         match line {
             LineTokens::Initialisation(it) => {
                 let (name, type_name, value) = (it.name(), it.type_name(), it.value());
                 let address = global_table.add_local_variable_named(
-                    name.name().clone(),
+                    Binding::new(name.name().clone(), *it.mutable(), name.location().clone()),
                     type_name,
                     local_variables,
                 )?;
-                contents.other(&compile_evaluable_into(
-                    fid,
-                    value,
-                    address,
-                    local_variables,
-                    global_table,
-                    global_tracker,
-                )?);
+                // Variables declared without a value are checked to be assigned before use
+                if let Some(value) = value {
+                    contents.other(&compile_evaluable_into(
+                        fid,
+                        value,
+                        address,
+                        local_variables,
+                        global_table,
+                        global_tracker,
+                    )?);
+                }
             }
             LineTokens::If(if_token) => {
                 let condition_addr = global_table
@@ -168,7 +243,7 @@ fn recursively_compile_lines(
                     contents.line(&format!("jz {end_tag}"));
                 }
 
-                let (code, ret) = recursively_compile_lines(
+                contents.other(&recursively_compile_lines(
                     fid,
                     if_token.if_contents(),
                     return_variable,
@@ -176,9 +251,7 @@ fn recursively_compile_lines(
                     local_variables,
                     global_table,
                     global_tracker,
-                )?;
-                last_is_return = ret;
-                contents.other(&code);
+                )?);
 
                 for (elif_condition, elif_content) in if_token.elif_condition_contents() {
                     // Jump to end tag to prevent fall-through from previous condition
@@ -202,7 +275,7 @@ fn recursively_compile_lines(
                     // Skip if condition failed
                     contents.line(&format!("cmp byte {}, 0", condition_addr.local_address()));
                     contents.line(&format!("jz {next_tag}"));
-                    let (asm, ret) = recursively_compile_lines(
+                    contents.other(&recursively_compile_lines(
                         fid,
                         elif_content,
                         return_variable,
@@ -210,9 +283,7 @@ fn recursively_compile_lines(
                         local_variables,
                         global_table,
                         global_tracker,
-                    )?;
-                    last_is_return &= ret;
-                    contents.other(&asm);
+                    )?);
                 }
 
                 if let Some(else_contents) = if_token.else_contents() {
@@ -221,7 +292,7 @@ fn recursively_compile_lines(
 
                     contents.line(&format!("{next_tag}:"));
                     next_tag = global_tracker.get_unique_tag(fid);
-                    let (code, ret) = recursively_compile_lines(
+                    contents.other(&recursively_compile_lines(
                         fid,
                         else_contents,
                         return_variable,
@@ -229,9 +300,7 @@ fn recursively_compile_lines(
                         local_variables,
                         global_table,
                         global_tracker,
-                    )?;
-                    last_is_return &= ret;
-                    contents.other(&code);
+                    )?);
                 }
 
                 contents.line(&format!("{next_tag}:"));
@@ -257,7 +326,7 @@ fn recursively_compile_lines(
                 contents.line(&format!("cmp byte {}, 0", condition_addr.local_address()));
                 contents.line(&format!("jz {end_tag}"));
 
-                let (code, ret) = recursively_compile_lines(
+                contents.other(&recursively_compile_lines(
                     fid,
                     while_token.contents(),
                     return_variable,
@@ -265,16 +334,13 @@ fn recursively_compile_lines(
                     local_variables,
                     global_table,
                     global_tracker,
-                )?;
-                last_is_return = ret;
-                contents.other(&code);
+                )?);
 
                 // Jump to start (re-evaluates condition)
                 contents.line(&format!("jmp {start_tag}"));
                 contents.line(&format!("{end_tag}:"))
             }
             LineTokens::Return(rt) => {
-                last_is_return = true;
                 // Check return type
                 if fid.is_main() {
                     if rt.return_value().is_none() {
@@ -327,15 +393,6 @@ fn recursively_compile_lines(
 
                 contents.line("leave");
                 contents.line("ret");
-
-                if line_i != lines.len() - 1 {
-                    warn(
-                        &format!("Return isn't the last instruction in the block. Following lines in block will not be compiled/run.\n{}", 
-                                 rt.location().clone().into_warning().with_context(global_tracker.path_storage())
-                        )
-                    )
-                }
-                break;
             }
             LineTokens::Break(bt) => {
                 if let Some(break_tag) = break_tag {
@@ -366,8 +423,5 @@ fn recursively_compile_lines(
         }
     }
 
-    // Put variables out of scope
-    local_variables.leave_scope();
-
-    Ok((contents.finish(), last_is_return))
+    Ok(contents.finish())
 }
@@ -2,6 +2,7 @@ use crate::root::assembler::assembly_builder::{Assembly, AssemblyBuilder};
 use crate::root::compiler::assembly::utils::{align_16_bytes, align_16_bytes_plus_8, copy};
 use crate::root::compiler::evaluation::coerce_self::coerce_self;
 use crate::root::compiler::evaluation::into::compile_evaluable_into;
+use crate::root::compiler::evaluation::mutability::mutability_error;
 use crate::root::compiler::evaluation::reference::compile_evaluable_reference;
 use crate::root::compiler::global_tracker::GlobalTracker;
 use crate::root::compiler::local_variable_table::LocalVariableTable;
@@ -48,7 +49,10 @@ pub fn call_function(
             .clone()
         {
             if let Some(return_address) = return_address {
-                if return_address.type_ref() != &expected_return {
+                if return_address.type_ref() != &expected_return
+                    || !expected_return.can_coerce_mutability_to(return_address.type_ref())
+                    || !expected_return.can_coerce_nullability_to(return_address.type_ref())
+                {
                     return WErr::ne(
                         EvalErrs::BadFunctionReturn(
                             global_table.get_type_name(return_address.type_ref()),
@@ -99,7 +103,7 @@ pub fn call_function(
             if i == 0 && uses_self {
                 let slf = match a {
                     Either::Left(eval) => {
-                        if matches!(self_type, SelfType::RefSelf) {
+                        if self_type.is_reference() {
                             let (c, into) = compile_evaluable_reference(
                                 fid,
                                 eval,
@@ -147,8 +151,21 @@ pub fn call_function(
                     }
                     Either::Right(addr) => addr.clone(),
                 };
+                let uncoerced = slf.clone();
                 let (c, slf) = coerce_self(slf, self_type, global_table, local_variables)?;
                 code.other(&c);
+                if matches!(self_type, SelfType::MutRefSelf)
+                    && !slf.type_ref().is_mutable_reference()
+                {
+                    return Err(mutability_error(
+                        EvalErrs::ImmutableSelf(name.to_string()),
+                        location.clone(),
+                        a.as_ref().left().copied(),
+                        Some(&uncoerced),
+                        global_table,
+                        local_variables,
+                    ));
+                }
                 args.push(*slf.local_address());
                 continue;
             }
@@ -176,6 +193,7 @@ pub fn call_function(
             }
         }
 
+        global_tracker.set_call_location(location.clone());
         code.other(&inline_o(
             &args,
             return_into.as_ref().map(|x| *x.local_address()),
@@ -210,7 +228,7 @@ pub fn call_function(
             if i == 0 && uses_self {
                 let slf = match a {
                     Either::Left(eval) => {
-                        if matches!(self_type, SelfType::RefSelf) {
+                        if self_type.is_reference() {
                             let (c, into) = compile_evaluable_reference(
                                 fid,
                                 eval,
@@ -258,8 +276,21 @@ pub fn call_function(
                     }
                     Either::Right(addr) => addr.clone(),
                 };
+                let uncoerced = slf.clone();
                 let (c, slf) = coerce_self(slf, self_type, global_table, local_variables)?;
                 code.other(&c);
+                if matches!(self_type, SelfType::MutRefSelf)
+                    && !slf.type_ref().is_mutable_reference()
+                {
+                    return Err(mutability_error(
+                        EvalErrs::ImmutableSelf(name.to_string()),
+                        location.clone(),
+                        a.as_ref().left().copied(),
+                        Some(&uncoerced),
+                        global_table,
+                        local_variables,
+                    ));
+                }
                 size += global_table.get_size(slf.type_ref());
                 args.push(slf);
                 continue;
@@ -351,6 +382,19 @@ pub fn call_function(
                 );
             }
 
+            let return_type = return_addr.as_ref().unwrap().type_ref();
+            if !return_type.can_coerce_mutability_to(return_address.type_ref())
+                || !return_type.can_coerce_nullability_to(return_address.type_ref())
+            {
+                return WErr::ne(
+                    EvalErrs::BadFunctionReturn(
+                        global_table.get_type_name(return_address.type_ref()),
+                        global_table.get_type_name(return_type),
+                    ),
+                    location.clone(),
+                );
+            }
+
             code.other(&copy(
                 *return_addr.as_ref().unwrap().local_address(),
                 *return_address.local_address(),
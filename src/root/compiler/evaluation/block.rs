@@ -0,0 +1,252 @@
+use crate::root::assembler::assembly_builder::{Assembly, AssemblyBuilder};
+use crate::root::builtin::types::bool::BoolType;
+use crate::root::compiler::compile_function::compile_lines_in_scope;
+use crate::root::compiler::evaluation::into::compile_evaluable_into;
+use crate::root::compiler::evaluation::type_only::compile_evaluable_type_only;
+use crate::root::compiler::global_tracker::GlobalTracker;
+use crate::root::compiler::local_variable_table::{Binding, LocalVariableTable};
+use crate::root::errors::compiler_errors::CompErrs;
+use crate::root::errors::evaluable_errors::EvalErrs;
+use crate::root::errors::WErr;
+use crate::root::name_resolver::name_resolvers::GlobalTable;
+use crate::root::parser::parse_function::parse_block::BlockToken;
+use crate::root::parser::parse_function::parse_if::IfExpressionToken;
+use crate::root::parser::parse_function::parse_line::LineTokens;
+use crate::root::shared::common::{AddressedTypeRef, FunctionID, TypeRef};
+
+/// Errors if lines in a block used as a value would return or break out of the block
+fn check_no_control_flow(lines: &[LineTokens], in_loop: bool) -> Result<(), WErr> {
+    for line in lines {
+        match line {
+            LineTokens::Return(rt) => {
+                return WErr::ne(
+                    CompErrs::ControlFlowInBlockExpression("return".to_string()),
+                    rt.location().clone(),
+                );
+            }
+            // Breaking out of a loop within the block is fine
+            LineTokens::Break(bt) if !in_loop => {
+                return WErr::ne(
+                    CompErrs::ControlFlowInBlockExpression("break".to_string()),
+                    bt.location().clone(),
+                );
+            }
+            LineTokens::If(if_token) => {
+                check_no_control_flow(if_token.if_contents(), in_loop)?;
+                for (_, contents) in if_token.elif_condition_contents() {
+                    check_no_control_flow(contents, in_loop)?;
+                }
+                if let Some(contents) = if_token.else_contents() {
+                    check_no_control_flow(contents, in_loop)?;
+                }
+            }
+            LineTokens::While(while_token) => {
+                check_no_control_flow(while_token.contents(), true)?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Evaluates the type a block evaluates to. Does not generate any assembly.
+pub fn compile_block_type_only(
+    fid: FunctionID,
+    block: &BlockToken,
+    local_variables: &mut LocalVariableTable,
+    global_table: &mut GlobalTable,
+    global_tracker: &mut GlobalTracker,
+) -> Result<TypeRef, WErr> {
+    local_variables.enter_scope();
+
+    // Variables defined in the block may be used by the trailing value
+    for line in block.lines() {
+        if let LineTokens::Initialisation(it) = line {
+            global_table.add_local_variable_named(
+                Binding::new(
+                    it.name().name().clone(),
+                    *it.mutable(),
+                    it.name().location().clone(),
+                ),
+                it.type_name(),
+                local_variables,
+            )?;
+        }
+    }
+
+    let t = compile_evaluable_type_only(
+        fid,
+        block.value(),
+        local_variables,
+        global_table,
+        global_tracker,
+    )?;
+
+    local_variables.leave_scope();
+
+    Ok(t)
+}
+
+/// Evaluates a block into `target`
+pub fn compile_block_into(
+    fid: FunctionID,
+    block: &BlockToken,
+    target: AddressedTypeRef,
+    local_variables: &mut LocalVariableTable,
+    global_table: &mut GlobalTable,
+    global_tracker: &mut GlobalTracker,
+) -> Result<Assembly, WErr> {
+    check_no_control_flow(block.lines(), false)?;
+
+    // Lines and the trailing value share a scope
+    local_variables.enter_scope();
+
+    let mut ab = AssemblyBuilder::new();
+    let asm = compile_lines_in_scope(
+        fid,
+        block.lines(),
+        &None,
+        &None,
+        local_variables,
+        global_table,
+        global_tracker,
+    )?;
+    ab.other(&asm);
+    ab.other(&compile_evaluable_into(
+        fid,
+        block.value(),
+        target,
+        local_variables,
+        global_table,
+        global_tracker,
+    )?);
+
+    local_variables.leave_scope();
+
+    Ok(ab.finish())
+}
+
+/// Evaluates the type an if expression evaluates to, checking that all branches have the same type.
+/// Does not generate any assembly.
+pub fn compile_if_expression_type_only(
+    fid: FunctionID,
+    if_expression: &IfExpressionToken,
+    local_variables: &mut LocalVariableTable,
+    global_table: &mut GlobalTable,
+    global_tracker: &mut GlobalTracker,
+) -> Result<TypeRef, WErr> {
+    let expected = compile_block_type_only(
+        fid,
+        if_expression.if_block(),
+        local_variables,
+        global_table,
+        global_tracker,
+    )?;
+
+    for block in if_expression
+        .elif_condition_blocks()
+        .iter()
+        .map(|(_, b)| b)
+        .chain([if_expression.else_block()])
+    {
+        let found =
+            compile_block_type_only(fid, block, local_variables, global_table, global_tracker)?;
+        if found != expected {
+            return WErr::ne(
+                EvalErrs::IfBranchTypeMismatch(
+                    global_table.get_type_name(&expected),
+                    global_table.get_type_name(&found),
+                ),
+                block.value().location().clone(),
+            );
+        }
+    }
+
+    Ok(expected)
+}
+
+/// Evaluates an if expression into `target`
+pub fn compile_if_expression_into(
+    fid: FunctionID,
+    if_expression: &IfExpressionToken,
+    target: AddressedTypeRef,
+    local_variables: &mut LocalVariableTable,
+    global_table: &mut GlobalTable,
+    global_tracker: &mut GlobalTracker,
+) -> Result<Assembly, WErr> {
+    // Check type
+    let t = compile_if_expression_type_only(
+        fid,
+        if_expression,
+        local_variables,
+        global_table,
+        global_tracker,
+    )?;
+    if &t != target.type_ref() {
+        return WErr::ne(
+            EvalErrs::ExpectedDifferentType(
+                global_table.get_type_name(target.type_ref()),
+                global_table.get_type_name(&t),
+            ),
+            if_expression.location().clone(),
+        );
+    }
+
+    let mut ab = AssemblyBuilder::new();
+
+    let end_tag = global_tracker.get_unique_tag(fid);
+
+    let conditions = [(
+        if_expression.if_condition().as_ref(),
+        if_expression.if_block(),
+    )]
+    .into_iter()
+    .chain(
+        if_expression
+            .elif_condition_blocks()
+            .iter()
+            .map(|(c, b)| (c, b)),
+    );
+
+    for (condition, block) in conditions {
+        let next_tag = global_tracker.get_unique_tag(fid);
+
+        let condition_addr = global_table
+            .add_local_variable_unnamed(BoolType::id().immediate_single(), local_variables);
+        ab.other(&compile_evaluable_into(
+            fid,
+            condition,
+            condition_addr.clone(),
+            local_variables,
+            global_table,
+            global_tracker,
+        )?);
+        // Skip to next condition if this one failed
+        ab.line(&format!("cmp byte {}, 0", condition_addr.local_address()));
+        ab.line(&format!("jz {next_tag}"));
+
+        ab.other(&compile_block_into(
+            fid,
+            block,
+            target.clone(),
+            local_variables,
+            global_table,
+            global_tracker,
+        )?);
+        // Prevent fall-through
+        ab.line(&format!("jmp {end_tag}"));
+        ab.line(&format!("{next_tag}:"));
+    }
+
+    ab.other(&compile_block_into(
+        fid,
+        if_expression.else_block(),
+        target,
+        local_variables,
+        global_table,
+        global_tracker,
+    )?);
+    ab.line(&format!("{end_tag}:"));
+
+    Ok(ab.finish())
+}
@@ -38,10 +38,12 @@ pub fn coerce_self(
                 (String::new(), current_self)
             }
         }
-        SelfType::RefSelf => {
+        SelfType::RefSelf | SelfType::MutRefSelf => {
+            // Whether self can be mutated through the reference is checked by the caller
             if !current_self.type_ref().indirection().has_indirection() {
+                let mutable = local_variables.is_mutable_place(*current_self.local_address());
                 let new_self = global_table.add_local_variable_unnamed(
-                    current_self.type_ref().plus_one_indirect(),
+                    current_self.type_ref().plus_one_indirect_mutable(mutable),
                     local_variables,
                 );
                 (
@@ -4,7 +4,7 @@ use crate::root::compiler::local_variable_table::LocalVariableTable;
 use crate::root::errors::evaluable_errors::EvalErrs;
 use crate::root::errors::evaluable_errors::EvalErrs::ExpectedFunctionName;
 use crate::root::errors::WErr;
-use crate::root::name_resolver::name_resolvers::{GlobalTable, NameResult};
+use crate::root::name_resolver::name_resolvers::{GlobalTable, NameResult, NameTarget};
 use crate::root::parser::parse_function::parse_evaluable::{EvaluableToken, EvaluableTokens};
 use crate::root::shared::common::FunctionID;
 
@@ -107,6 +107,7 @@ pub fn compile_evaluable_function_only<'a>(
                     access.location().clone(),
                 );
             };
+            global_table.add_name_use(access, NameTarget::Function(function));
 
             (None, function, access.name().clone())
         }
@@ -134,6 +135,7 @@ pub fn compile_evaluable_function_only<'a>(
                     access.location().clone(),
                 );
             };
+            global_table.add_name_use(access, NameTarget::Function(function));
 
             (Some(inner), function, access.name().clone())
         }
@@ -0,0 +1,204 @@
+use crate::root::assembler::assembly_builder::{Assembly, AssemblyBuilder};
+use crate::root::builtin::types::int::IntType;
+use crate::root::compiler::assembly::heap::{heap_alloc_array, heap_free};
+use crate::root::compiler::assembly::panic::bounds_check;
+use crate::root::compiler::evaluation::into::compile_evaluable_into;
+use crate::root::compiler::evaluation::reference::compile_evaluable_reference;
+use crate::root::compiler::evaluation::type_only::compile_evaluable_type_only;
+use crate::root::compiler::global_tracker::GlobalTracker;
+use crate::root::compiler::local_variable_table::LocalVariableTable;
+use crate::root::errors::evaluable_errors::EvalErrs;
+use crate::root::errors::WErr;
+use crate::root::name_resolver::name_resolvers::GlobalTable;
+use crate::root::parser::location::Location;
+use crate::root::parser::parse_function::parse_evaluable::EvaluableToken;
+use crate::root::parser::parse_function::parse_heap::HeapArrayToken;
+use crate::root::shared::common::{AddressedTypeRef, FunctionID, TypeRef};
+
+/// Returns the type of a heap array - a mutable heap array reference (`&mut [T]`) to its first
+/// value
+pub fn heap_array_type(
+    heap_array: &HeapArrayToken,
+    global_table: &mut GlobalTable,
+) -> Result<TypeRef, WErr> {
+    Ok(global_table
+        .resolve_to_type_ref(heap_array.element(), None)?
+        .plus_one_indirect_heap(true))
+}
+
+/// Allocates a heap array, or reallocates the one it is `from`, returning a reference to its first
+/// value
+pub fn compile_heap_array_new(
+    fid: FunctionID,
+    heap_array: &HeapArrayToken,
+    local_variables: &mut LocalVariableTable,
+    global_table: &mut GlobalTable,
+    global_tracker: &mut GlobalTracker,
+) -> Result<(Assembly, AddressedTypeRef), WErr> {
+    let element = global_table.resolve_to_type_ref(heap_array.element(), None)?;
+    let mut ab = AssemblyBuilder::new();
+
+    let length = global_table
+        .add_local_variable_unnamed(IntType::id().immediate_single(), local_variables);
+    ab.other(&compile_evaluable_into(
+        fid,
+        heap_array.length(),
+        length.clone(),
+        local_variables,
+        global_table,
+        global_tracker,
+    )?);
+
+    let from = if let Some(from) = heap_array.from() {
+        // The existing allocation must be of the same type and owned mutably, as reallocating
+        // may move or change it
+        let from_type =
+            compile_evaluable_type_only(fid, from, local_variables, global_table, global_tracker)?;
+        let expected = element.plus_one_indirect_heap(true);
+        if from_type != expected {
+            return WErr::ne(
+                EvalErrs::ExpectedDifferentType(
+                    global_table.get_type_name(&expected),
+                    global_table.get_type_name(&from_type),
+                ),
+                from.location().clone(),
+            );
+        }
+        if !from_type.can_coerce_mutability_to(&expected) {
+            return WErr::ne(
+                EvalErrs::ExpectedMutable(
+                    global_table.get_type_name(&expected),
+                    global_table.get_type_name(&from_type),
+                ),
+                from.location().clone(),
+            );
+        }
+
+        let (c, from) =
+            compile_evaluable_reference(fid, from, local_variables, global_table, global_tracker)?;
+        ab.other(&c);
+        Some(*from.unwrap().local_address())
+    } else {
+        None
+    };
+
+    let (c, output) = heap_alloc_array(
+        element,
+        *length.local_address(),
+        from,
+        heap_array.location(),
+        global_table,
+        local_variables,
+        global_tracker,
+    );
+    ab.other(&c);
+
+    Ok((ab.finish(), output))
+}
+
+/// Frees the heap allocation `pointer` refers to
+pub fn compile_delete(
+    fid: FunctionID,
+    pointer: &EvaluableToken,
+    local_variables: &mut LocalVariableTable,
+    global_table: &mut GlobalTable,
+    global_tracker: &mut GlobalTracker,
+) -> Result<Assembly, WErr> {
+    let (c, address) =
+        compile_evaluable_reference(fid, pointer, local_variables, global_table, global_tracker)?;
+    let Some(address) = address else {
+        return WErr::ne(EvalErrs::ExpectedNotNone, pointer.location().clone());
+    };
+
+    // Only heap array references are known to point to the start of a heap allocation
+    if !address.type_ref().is_heap_reference() {
+        return WErr::ne(
+            EvalErrs::DeleteNotHeap(global_table.get_type_name(address.type_ref())),
+            pointer.location().clone(),
+        );
+    }
+
+    let mut ab = AssemblyBuilder::new();
+    ab.other(&c);
+    ab.other(&heap_free(
+        *address.local_address(),
+        local_variables.stack_size(),
+        pointer.location(),
+        global_tracker,
+    ));
+    Ok(ab.finish())
+}
+
+/// Returns the type of `parent[index]` - a reference to a value in the heap array `parent` refers
+/// to, as mutable as `parent`
+pub fn index_type(
+    fid: FunctionID,
+    parent: &EvaluableToken,
+    local_variables: &mut LocalVariableTable,
+    global_table: &mut GlobalTable,
+    global_tracker: &mut GlobalTracker,
+) -> Result<TypeRef, WErr> {
+    let parent_type =
+        compile_evaluable_type_only(fid, parent, local_variables, global_table, global_tracker)?;
+    // Only heap array references are known to point to the start of a heap allocation, after its
+    // header
+    if !parent_type.is_heap_reference() {
+        return WErr::ne(
+            EvalErrs::IndexNotHeap(global_table.get_type_name(&parent_type)),
+            parent.location().clone(),
+        );
+    }
+    // Nullable references are checked against `null` before being indexed by the nullability
+    // analysis
+    Ok(parent_type.non_null().non_heap())
+}
+
+/// Evaluates `parent[index]`, returning a reference to the value at `index` in the heap allocation
+/// `parent` refers to. Panics at `location` if the index is out of bounds
+pub fn compile_index_new(
+    fid: FunctionID,
+    parent: &EvaluableToken,
+    index: &EvaluableToken,
+    location: &Location,
+    local_variables: &mut LocalVariableTable,
+    global_table: &mut GlobalTable,
+    global_tracker: &mut GlobalTracker,
+) -> Result<(Assembly, AddressedTypeRef), WErr> {
+    let t = index_type(fid, parent, local_variables, global_table, global_tracker)?;
+    let size = global_table.get_size(&t.minus_one_indirect());
+    let mut ab = AssemblyBuilder::new();
+
+    let (c, reference) =
+        compile_evaluable_reference(fid, parent, local_variables, global_table, global_tracker)?;
+    let Some(reference) = reference else {
+        return WErr::ne(EvalErrs::ExpectedNotNone, parent.location().clone());
+    };
+    ab.other(&c);
+
+    let position =
+        global_table.add_local_variable_unnamed(IntType::id().immediate_single(), local_variables);
+    ab.other(&compile_evaluable_into(
+        fid,
+        index,
+        position.clone(),
+        local_variables,
+        global_table,
+        global_tracker,
+    )?);
+
+    ab.other(&bounds_check(
+        *reference.local_address(),
+        *position.local_address(),
+        location,
+        global_tracker,
+    ));
+
+    let output = global_table.add_local_variable_unnamed(t, local_variables);
+    ab.line(&format!("mov rax, qword {}", reference.local_address()));
+    ab.line(&format!("mov rcx, qword {}", position.local_address()));
+    ab.line(&format!("imul rcx, rcx, {}", size.0));
+    ab.line("add rax, rcx");
+    ab.line(&format!("mov qword {}, rax", output.local_address()));
+
+    Ok((ab.finish(), output))
+}
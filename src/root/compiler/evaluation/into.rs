@@ -1,24 +1,35 @@
 use either::{Left, Right};
-use itertools::Itertools;
 
 use crate::root::assembler::assembly_builder::{Assembly, AssemblyBuilder};
 use crate::root::builtin::core::referencing::{set_deref, set_reference};
 use crate::root::compiler::assembly::heap::heap_alloc;
 use crate::root::compiler::assembly::utils::{copy, copy_to_indirect};
 use crate::root::compiler::compile_function_call::call_function;
+use crate::root::compiler::evaluation::block::{compile_block_into, compile_if_expression_into};
 use crate::root::compiler::evaluation::coerce_self::coerce_self;
+use crate::root::compiler::evaluation::heap::{
+    compile_heap_array_new, compile_index_new, heap_array_type, index_type,
+};
+use crate::root::compiler::evaluation::mutability::{
+    check_deref_mutability, mutability_error, reference_is_mutable,
+};
 use crate::root::compiler::evaluation::new::compile_evaluable_new;
+use crate::root::compiler::evaluation::nullability::check_nullability_coercion;
 use crate::root::compiler::evaluation::reference::compile_evaluable_reference;
+use crate::root::compiler::evaluation::reference_comparison::{
+    compile_reference_comparison_into, reference_comparison_type,
+};
+use crate::root::compiler::evaluation::struct_init::compile_struct_init_attributes;
 use crate::root::compiler::evaluation::{function_only, reference, type_only};
 use crate::root::compiler::global_tracker::GlobalTracker;
 use crate::root::compiler::local_variable_table::LocalVariableTable;
 use crate::root::errors::evaluable_errors::EvalErrs;
-use crate::root::errors::evaluable_errors::EvalErrs::WrongAttributeNameInInit;
 use crate::root::errors::name_resolver_errors::NRErrs;
 use crate::root::errors::WErr;
 use crate::root::name_resolver::name_resolvers::{GlobalTable, NameResult};
 use crate::root::parser::location::Location;
 use crate::root::parser::parse_function::parse_evaluable::{EvaluableToken, EvaluableTokens};
+use crate::root::parser::parse_function::parse_literal::LiteralTokens;
 use crate::root::parser::parse_function::parse_operator::{OperatorTokens, PrefixOrInfixEx};
 use crate::root::parser::parse_parameters::SelfType;
 use crate::root::shared::common::{AddressedTypeRef, FunctionID, LocalAddress};
@@ -75,6 +86,29 @@ pub fn compile_evaluable_into(
                             name.location().clone(),
                         );
                     }
+                    if !address
+                        .type_ref()
+                        .can_coerce_mutability_to(target.type_ref())
+                    {
+                        return Err(mutability_error(
+                            EvalErrs::ExpectedMutable(
+                                global_table.get_type_name(target.type_ref()),
+                                global_table.get_type_name(address.type_ref()),
+                            ),
+                            name.location().clone(),
+                            Some(evaluable),
+                            None,
+                            global_table,
+                            local_variables,
+                        ));
+                    }
+                    check_nullability_coercion(
+                        address.type_ref(),
+                        target.type_ref(),
+                        evaluable.location(),
+                        global_table,
+                        global_tracker,
+                    )?;
 
                     // Copy into output
                     copy(
@@ -86,6 +120,17 @@ pub fn compile_evaluable_into(
             }
         }
         EvaluableTokens::Literal(literal) => {
+            // `null` coerces to any nullable reference
+            if matches!(literal.literal(), LiteralTokens::Null) {
+                if !target.type_ref().is_nullable_reference() {
+                    return WErr::ne(
+                        EvalErrs::NullNotReference(global_table.get_type_name(target.type_ref())),
+                        literal.location().clone(),
+                    );
+                }
+                return Ok(format!("    mov qword {}, 0\n", target.local_address()));
+            }
+
             // Check indirection
             if target.type_ref().indirection().has_indirection() {
                 return WErr::ne(
@@ -106,6 +151,29 @@ pub fn compile_evaluable_into(
                 );
             };
 
+            // References are compared by address
+            if let Some(operand_type) = reference_comparison_type(
+                fid,
+                lhs,
+                op,
+                rhs,
+                local_variables,
+                global_table,
+                global_tracker,
+            )? {
+                return compile_reference_comparison_into(
+                    fid,
+                    lhs,
+                    op,
+                    rhs,
+                    operand_type,
+                    target,
+                    local_variables,
+                    global_table,
+                    global_tracker,
+                );
+            }
+
             let lhs_type = type_only::compile_evaluable_type_only(
                 fid,
                 lhs,
@@ -165,7 +233,7 @@ pub fn compile_evaluable_into(
                 fid,
                 operator_fn,
                 operator_fn_signature.self_type().uses_self(),
-                evaluable.location(),
+                op.location(),
                 &op.operator()
                     .get_method_name(PrefixOrInfixEx::Infix)
                     .unwrap(),
@@ -204,6 +272,33 @@ pub fn compile_evaluable_into(
                     if *val.type_ref() != lhs_type {
                         panic!()
                     }
+                    let mutable = local_variables.is_mutable_place(*val.local_address());
+                    if !val
+                        .type_ref()
+                        .plus_one_indirect_mutable(mutable)
+                        .can_coerce_mutability_to(target.type_ref())
+                    {
+                        return Err(mutability_error(
+                            EvalErrs::ExpectedMutable(
+                                global_table.get_type_name(target.type_ref()),
+                                global_table.get_type_name(
+                                    &val.type_ref().plus_one_indirect_mutable(mutable),
+                                ),
+                            ),
+                            evaluable.location().clone(),
+                            Some(lhs),
+                            None,
+                            global_table,
+                            local_variables,
+                        ));
+                    }
+                    check_nullability_coercion(
+                        &val.type_ref().plus_one_indirect(),
+                        target.type_ref(),
+                        evaluable.location(),
+                        global_table,
+                        global_tracker,
+                    )?;
                     asm += &set_reference(op.location(), val, target, global_table)?;
                     return Ok(asm);
                 }
@@ -218,6 +313,23 @@ pub fn compile_evaluable_into(
                     let Some(val) = val else {
                         return WErr::ne(EvalErrs::ExpectedNotNone, lhs.location().clone());
                     };
+                    check_deref_mutability(
+                        &val,
+                        &target,
+                        evaluable,
+                        lhs,
+                        global_table,
+                        local_variables,
+                    )?;
+                    if val.type_ref().indirection().has_indirection() {
+                        check_nullability_coercion(
+                            &val.type_ref().minus_one_indirect(),
+                            target.type_ref(),
+                            evaluable.location(),
+                            global_table,
+                            global_tracker,
+                        )?;
+                    }
 
                     c += &set_deref(lhs.location(), val, target, global_table)?;
                     return Ok(c);
@@ -276,7 +388,7 @@ pub fn compile_evaluable_into(
                 fid,
                 operator_fn,
                 operator_fn_signature.self_type().uses_self(),
-                evaluable.location(),
+                op.location(),
                 &op.operator()
                     .get_method_name(PrefixOrInfixEx::Prefix)
                     .unwrap(),
@@ -321,6 +433,9 @@ pub fn compile_evaluable_into(
             let inner_attributes = inner_type.get_attributes(access.location())?;
             let mut found_offset = None;
 
+            // Attributes are as mutable as the value (or reference) they're accessed through
+            let mutable = reference_is_mutable(&inner, local_variables);
+
             // Find the byte offset of the attribute
             for (offset, name, t) in inner_attributes {
                 if name.name() == access.name() {
@@ -333,6 +448,27 @@ pub fn compile_evaluable_into(
                             access.location().clone(),
                         );
                     }
+                    let found = t.plus_one_indirect_mutable(mutable);
+                    if !found.can_coerce_mutability_to(target.type_ref()) {
+                        return Err(mutability_error(
+                            EvalErrs::ExpectedMutable(
+                                global_table.get_type_name(target.type_ref()),
+                                global_table.get_type_name(&found),
+                            ),
+                            access.location().clone(),
+                            Some(inner_eval),
+                            Some(&inner),
+                            global_table,
+                            local_variables,
+                        ));
+                    }
+                    check_nullability_coercion(
+                        &found,
+                        target.type_ref(),
+                        access.location(),
+                        global_table,
+                        global_tracker,
+                    )?;
                     found_offset = Some(*offset);
                 }
             }
@@ -502,58 +638,27 @@ pub fn compile_evaluable_into(
                 );
             }
 
-            let struct_type = global_table.get_type(*struct_type_ref.type_id());
-            let attributes = struct_type
-                .get_attributes(struct_init.location())
-                .map_err(|_| {
-                    WErr::n(
-                        EvalErrs::TypeCannotBeInitialised(struct_type.name().to_string()),
-                        struct_init.location().clone(),
-                    )
-                })?
-                .iter()
-                .cloned()
-                .collect_vec();
-            let given_attributes = struct_init.contents();
-
-            if attributes.len() != given_attributes.len() {
-                return WErr::ne(
-                    EvalErrs::WrongAttributeCount(attributes.len(), given_attributes.len()),
-                    struct_init.location().clone(),
-                );
-            }
-
             let mut asm = AssemblyBuilder::new();
 
-            // Create all attributes in correct place in struct
-            for ((offset, t_name, t_type), (name, val)) in
-                attributes.iter().zip(given_attributes.iter())
-            {
-                // Incorrect attribute
-                if t_name.name() != name.name() {
-                    return WErr::ne(
-                        WrongAttributeNameInInit(t_name.name().clone(), name.name().clone()),
-                        name.location().clone(),
-                    );
-                }
-
-                let new_addr = AddressedTypeRef::new(
-                    LocalAddress(target.local_address().0 + offset.0 as isize),
-                    t_type.clone(),
-                );
-                asm.other(&compile_evaluable_into(
-                    fid,
-                    val,
-                    new_addr,
-                    local_variables,
-                    global_table,
-                    global_tracker,
-                )?);
-            }
+            asm.other(&compile_struct_init_attributes(
+                fid,
+                struct_init,
+                &struct_type_ref,
+                &target,
+                local_variables,
+                global_table,
+                global_tracker,
+            )?);
 
             // TODO: Test
             if *struct_init.heap_alloc() {
-                let (c, ref_target) = heap_alloc(struct_type_ref, global_table, local_variables);
+                let (c, ref_target) = heap_alloc(
+                    struct_type_ref,
+                    struct_init.location(),
+                    global_table,
+                    local_variables,
+                    global_tracker,
+                );
                 asm.other(&c);
                 asm.other(&copy_to_indirect(
                     *target.local_address(),
@@ -564,7 +669,101 @@ pub fn compile_evaluable_into(
 
             asm.finish()
         }
-        EvaluableTokens::None => {
+        EvaluableTokens::Block(block) => compile_block_into(
+            fid,
+            block,
+            target,
+            local_variables,
+            global_table,
+            global_tracker,
+        )?,
+        EvaluableTokens::If(if_expression) => compile_if_expression_into(
+            fid,
+            if_expression,
+            target,
+            local_variables,
+            global_table,
+            global_tracker,
+        )?,
+        EvaluableTokens::HeapArray(heap_array) => {
+            let t = heap_array_type(heap_array, global_table)?;
+            if target.type_ref() != &t {
+                return WErr::ne(
+                    EvalErrs::ExpectedDifferentType(
+                        global_table.get_type_name(target.type_ref()),
+                        global_table.get_type_name(&t),
+                    ),
+                    heap_array.location().clone(),
+                );
+            }
+
+            let mut ab = AssemblyBuilder::new();
+            let (asm, output) = compile_heap_array_new(
+                fid,
+                heap_array,
+                local_variables,
+                global_table,
+                global_tracker,
+            )?;
+            ab.other(&asm);
+            ab.other(&copy(
+                *output.local_address(),
+                *target.local_address(),
+                global_table.get_size(target.type_ref()),
+            ));
+            ab.finish()
+        }
+        EvaluableTokens::Index { parent, index } => {
+            let t = index_type(fid, parent, local_variables, global_table, global_tracker)?;
+            if target.type_ref() != &t {
+                return WErr::ne(
+                    EvalErrs::ExpectedDifferentType(
+                        global_table.get_type_name(target.type_ref()),
+                        global_table.get_type_name(&t),
+                    ),
+                    evaluable.location().clone(),
+                );
+            }
+            if !t.can_coerce_mutability_to(target.type_ref()) {
+                return Err(mutability_error(
+                    EvalErrs::ExpectedMutable(
+                        global_table.get_type_name(target.type_ref()),
+                        global_table.get_type_name(&t),
+                    ),
+                    evaluable.location().clone(),
+                    Some(parent),
+                    None,
+                    global_table,
+                    local_variables,
+                ));
+            }
+            check_nullability_coercion(
+                &t,
+                target.type_ref(),
+                evaluable.location(),
+                global_table,
+                global_tracker,
+            )?;
+
+            let mut ab = AssemblyBuilder::new();
+            let (asm, output) = compile_index_new(
+                fid,
+                parent,
+                index,
+                evaluable.location(),
+                local_variables,
+                global_table,
+                global_tracker,
+            )?;
+            ab.other(&asm);
+            ab.other(&copy(
+                *output.local_address(),
+                *target.local_address(),
+                global_table.get_size(target.type_ref()),
+            ));
+            ab.finish()
+        }
+        EvaluableTokens::Delete(_) | EvaluableTokens::None => {
             return WErr::ne(
                 EvalErrs::ExpectedType(global_table.get_type_name(target.type_ref())),
                 evaluable.location().clone(),
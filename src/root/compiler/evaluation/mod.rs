@@ -1,8 +1,14 @@
+pub mod block;
 pub mod coerce_self;
 pub mod function_only;
+pub mod heap;
 pub mod into;
+pub mod mutability;
 pub mod new;
+pub mod nullability;
 pub mod reference;
+pub mod reference_comparison;
+pub mod struct_init;
 pub mod type_only;
 
 // Error on an empty address
@@ -0,0 +1,120 @@
+use crate::root::compiler::local_variable_table::LocalVariableTable;
+use crate::root::errors::evaluable_errors::EvalErrs;
+use crate::root::errors::WErr;
+use crate::root::name_resolver::name_resolvers::GlobalTable;
+use crate::root::parser::location::Location;
+use crate::root::parser::parse_function::parse_evaluable::{EvaluableToken, EvaluableTokens};
+use crate::root::parser::parse_function::parse_operator::OperatorTokens;
+use crate::root::parser::parse_name::SimpleNameToken;
+use crate::root::shared::common::{AddressedTypeRef, Indirection};
+use std::fmt::Display;
+
+/// Returns whether a reference to `value` (as self) would be mutable. If `value` is already a
+/// reference, its innermost reference is used, otherwise the mutability of the variable at its
+/// address
+pub fn reference_is_mutable(
+    value: &AddressedTypeRef,
+    local_variables: &LocalVariableTable,
+) -> bool {
+    if value.type_ref().indirection().has_indirection() {
+        value.type_ref().is_mutable_at(Indirection(1))
+    } else {
+        local_variables.is_mutable_place(*value.local_address())
+    }
+}
+
+/// Returns the variable a value is derived from e.g. `a` in `&a.b`
+pub fn root_variable(et: &EvaluableToken) -> Option<&SimpleNameToken> {
+    match et.token() {
+        EvaluableTokens::Name(name, _) => Some(name),
+        EvaluableTokens::DynamicAccess { parent, section: _ } => root_variable(parent),
+        EvaluableTokens::PrefixOperator(op, inner)
+            if matches!(
+                op.operator(),
+                OperatorTokens::Reference | OperatorTokens::Multiply
+            ) =>
+        {
+            root_variable(inner)
+        }
+        _ => None,
+    }
+}
+
+/// Creates an error for mutating something that isn't mutable. If the value is derived from a
+/// variable (found from `et` or, failing that, `address`) that isn't declared as mutable, a note
+/// pointing at its declaration is added
+pub fn mutability_error(
+    error: impl Display,
+    location: Location,
+    et: Option<&EvaluableToken>,
+    address: Option<&AddressedTypeRef>,
+    global_table: &GlobalTable,
+    local_variables: &LocalVariableTable,
+) -> WErr {
+    let error = WErr::n(error, location);
+
+    let variable = et
+        .and_then(root_variable)
+        .and_then(|name| local_variables.get(name.name()))
+        .or_else(|| address.cloned());
+    let Some(variable) = variable else {
+        return error;
+    };
+    let Some(binding) = local_variables.binding_at(*variable.local_address()) else {
+        return error;
+    };
+
+    let type_ref = variable.type_ref();
+    if !type_ref.indirection().has_indirection() && !binding.mutable() {
+        error.with_note(
+            format!(
+                "Variable ({}) is declared here as immutable. Use `let mut` to allow it to be mutated",
+                binding.name()
+            ),
+            binding.location().clone(),
+        )
+    } else if type_ref.indirection().has_indirection() && !type_ref.is_mutable_reference() {
+        error.with_note(
+            format!(
+                "Variable ({}) is declared here as ({}) which is not a mutable reference",
+                binding.name(),
+                global_table.get_type_name(type_ref)
+            ),
+            binding.location().clone(),
+        )
+    } else {
+        error
+    }
+}
+
+/// Errors if dereferencing `reference` (evaluated from `inner`) into `target` would make a
+/// reference mutable that isn't mutable where it is stored e.g. `*r` with `r: &&int` can't be a
+/// `&mut int`
+pub fn check_deref_mutability(
+    reference: &AddressedTypeRef,
+    target: &AddressedTypeRef,
+    evaluable: &EvaluableToken,
+    inner: &EvaluableToken,
+    global_table: &GlobalTable,
+    local_variables: &LocalVariableTable,
+) -> Result<(), WErr> {
+    // Dereferencing a non-reference is reported with its type
+    if !reference.type_ref().indirection().has_indirection() {
+        return Ok(());
+    }
+    let pointee = reference.type_ref().minus_one_indirect();
+    if pointee.can_coerce_mutability_to(target.type_ref()) {
+        return Ok(());
+    }
+    Err(mutability_error(
+        EvalErrs::ExpectedMutable(
+            global_table.get_type_name(target.type_ref()),
+            global_table.get_type_name(&pointee),
+        ),
+        evaluable.location().clone(),
+        Some(inner),
+        None,
+        global_table,
+        local_variables,
+    ))
+}
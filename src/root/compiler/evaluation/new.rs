@@ -1,18 +1,28 @@
 use either::{Left, Right};
-use itertools::Itertools;
 
 use crate::root::assembler::assembly_builder::{Assembly, AssemblyBuilder};
 use crate::root::builtin::core::referencing::{set_deref, set_reference};
+use crate::root::builtin::types::bool::BoolType;
 use crate::root::compiler::assembly::heap::heap_alloc;
 use crate::root::compiler::assembly::utils::{copy, copy_to_indirect};
 use crate::root::compiler::compile_function_call::call_function;
 use crate::root::compiler::evaluation::coerce_self::coerce_self;
+use crate::root::compiler::evaluation::heap::{
+    compile_delete, compile_heap_array_new, compile_index_new,
+};
+use crate::root::compiler::evaluation::mutability::{
+    check_deref_mutability, mutability_error, reference_is_mutable,
+};
 use crate::root::compiler::evaluation::reference::compile_evaluable_reference;
+use crate::root::compiler::evaluation::reference_comparison::{
+    compile_reference_comparison_into, reference_comparison_type,
+};
+use crate::root::compiler::evaluation::struct_init::compile_struct_init_attributes;
 use crate::root::compiler::evaluation::{function_only, into, reference, type_only};
 use crate::root::compiler::global_tracker::GlobalTracker;
 use crate::root::compiler::local_variable_table::LocalVariableTable;
 use crate::root::errors::evaluable_errors::EvalErrs;
-use crate::root::errors::evaluable_errors::EvalErrs::{ExpectedNotNone, WrongAttributeNameInInit};
+use crate::root::errors::evaluable_errors::EvalErrs::ExpectedNotNone;
 use crate::root::errors::name_resolver_errors::NRErrs;
 use crate::root::errors::WErr;
 use crate::root::name_resolver::name_resolvers::{GlobalTable, NameResult};
@@ -80,7 +90,9 @@ pub fn compile_evaluable_new(
             }
         }
         EvaluableTokens::Literal(literal) => {
-            let type_id = literal.literal().default_type();
+            let Some(type_id) = literal.literal().default_type() else {
+                return WErr::ne(EvalErrs::UntypedNull, literal.location().clone());
+            };
             // TODO: Don't use 1 element
             let address = global_table.add_local_variable_unnamed(
                 TypeRef::new(type_id, 1, Indirection(0)),
@@ -110,6 +122,22 @@ pub fn compile_evaluable_new(
                     );
                 }
 
+                // The first assignment to a variable declared without a value initialises it
+                if !into.type_ref().is_mutable_reference()
+                    && !global_tracker.is_initialising_assignment(lhs.location())
+                {
+                    return Err(mutability_error(
+                        EvalErrs::AssignThroughImmutable(
+                            global_table.get_type_name(into.type_ref()),
+                        ),
+                        lhs.location().clone(),
+                        Some(lhs),
+                        None,
+                        global_table,
+                        local_variables,
+                    ));
+                }
+
                 // Create output
                 let val = global_table.add_local_variable_unnamed(
                     into.type_ref().minus_one_indirect(),
@@ -133,6 +161,32 @@ pub fn compile_evaluable_new(
                 return Ok((asm, None));
             };
 
+            // References are compared by address
+            if let Some(operand_type) = reference_comparison_type(
+                fid,
+                lhs,
+                op,
+                rhs,
+                local_variables,
+                global_table,
+                global_tracker,
+            )? {
+                let target = global_table
+                    .add_local_variable_unnamed(BoolType::id().immediate_single(), local_variables);
+                let asm = compile_reference_comparison_into(
+                    fid,
+                    lhs,
+                    op,
+                    rhs,
+                    operand_type,
+                    target.clone(),
+                    local_variables,
+                    global_table,
+                    global_tracker,
+                )?;
+                return Ok((asm, Some(target)));
+            }
+
             let lhs_type = type_only::compile_evaluable_type_only(
                 fid,
                 lhs,
@@ -215,8 +269,9 @@ pub fn compile_evaluable_new(
                     if *val.type_ref() != lhs_type {
                         panic!()
                     }
+                    let mutable = local_variables.is_mutable_place(*val.local_address());
                     let target = global_table.add_local_variable_unnamed(
-                        val.type_ref().plus_one_indirect(),
+                        val.type_ref().plus_one_indirect_mutable(mutable),
                         local_variables,
                     );
                     asm += &set_reference(op.location(), val, target.clone(), global_table)?;
@@ -243,6 +298,14 @@ pub fn compile_evaluable_new(
                         val.type_ref().minus_one_indirect(),
                         local_variables,
                     );
+                    check_deref_mutability(
+                        &val,
+                        &target,
+                        et,
+                        lhs,
+                        global_table,
+                        local_variables,
+                    )?;
                     c += &set_deref(lhs.location(), val, target.clone(), global_table)?;
                     return Ok((c, Some(target)));
                 }
@@ -282,7 +345,7 @@ pub fn compile_evaluable_new(
                 fid,
                 operator_fn,
                 uses_self,
-                et.location(),
+                op.location(),
                 &op.operator()
                     .get_method_name(PrefixOrInfixEx::Prefix)
                     .unwrap(),
@@ -343,8 +406,10 @@ pub fn compile_evaluable_new(
                 );
             };
 
-            let target =
-                global_table.add_local_variable_unnamed(t.plus_one_indirect(), local_variables);
+            // Attributes are as mutable as the value (or reference) they're accessed through
+            let mutable = reference_is_mutable(&inner, local_variables);
+            let target = global_table
+                .add_local_variable_unnamed(t.plus_one_indirect_mutable(mutable), local_variables);
 
             if inner.type_ref().indirection().has_indirection() {
                 // If inner is a reference
@@ -463,53 +528,24 @@ pub fn compile_evaluable_new(
                 global_table.add_local_variable_unnamed(struct_type_ref.clone(), local_variables)
             /* } */;
 
-            let struct_type = global_table.get_type(*struct_type_ref.type_id());
-            let attributes = struct_type
-                .get_attributes(struct_init.location())
-                .map_err(|_| {
-                    WErr::n(
-                        EvalErrs::TypeCannotBeInitialised(struct_type.name().to_string()),
-                        struct_init.location().clone(),
-                    )
-                })?
-                .iter()
-                .cloned()
-                .collect_vec();
-            let give_attrs = struct_init.contents();
-
-            if attributes.len() != give_attrs.len() {
-                return WErr::ne(
-                    EvalErrs::WrongAttributeCount(attributes.len(), give_attrs.len()),
-                    struct_init.location().clone(),
-                );
-            }
-
-            // Create all attributes in the correct place in struct
-            for ((offset, t_name, t_type), (name, val)) in attributes.iter().zip(give_attrs.iter())
-            {
-                if t_name.name() != name.name() {
-                    return WErr::ne(
-                        WrongAttributeNameInInit(t_name.name().clone(), name.name().clone()),
-                        name.location().clone(),
-                    );
-                }
+            asm.other(&compile_struct_init_attributes(
+                fid,
+                struct_init,
+                &struct_type_ref,
+                &target,
+                local_variables,
+                global_table,
+                global_tracker,
+            )?);
 
-                let new_addr = AddressedTypeRef::new(
-                    LocalAddress(target.local_address().0 + offset.0 as isize),
-                    t_type.clone(),
-                );
-                asm.other(&into::compile_evaluable_into(
-                    fid,
-                    val,
-                    new_addr,
-                    local_variables,
+            if *struct_init.heap_alloc() {
+                let (c, ref_target) = heap_alloc(
+                    struct_type_ref,
+                    struct_init.location(),
                     global_table,
+                    local_variables,
                     global_tracker,
-                )?);
-            }
-
-            if *struct_init.heap_alloc() {
-                let (c, ref_target) = heap_alloc(struct_type_ref, global_table, local_variables);
+                );
                 asm.other(&c);
                 asm.other(&copy_to_indirect(
                     *target.local_address(),
@@ -521,6 +557,53 @@ pub fn compile_evaluable_new(
                 (asm.finish(), Some(target))
             }
         }
+        EvaluableTokens::Block(_) | EvaluableTokens::If(_) => {
+            let t = type_only::compile_evaluable_type_only(
+                fid,
+                et,
+                local_variables,
+                global_table,
+                global_tracker,
+            )?;
+            let target = global_table.add_local_variable_unnamed(t, local_variables);
+            (
+                into::compile_evaluable_into(
+                    fid,
+                    et,
+                    target.clone(),
+                    local_variables,
+                    global_table,
+                    global_tracker,
+                )?,
+                Some(target),
+            )
+        }
+        EvaluableTokens::HeapArray(heap_array) => {
+            let (asm, output) = compile_heap_array_new(
+                fid,
+                heap_array,
+                local_variables,
+                global_table,
+                global_tracker,
+            )?;
+            (asm, Some(output))
+        }
+        EvaluableTokens::Index { parent, index } => {
+            let (asm, output) = compile_index_new(
+                fid,
+                parent,
+                index,
+                et.location(),
+                local_variables,
+                global_table,
+                global_tracker,
+            )?;
+            (asm, Some(output))
+        }
+        EvaluableTokens::Delete(pointer) => (
+            compile_delete(fid, pointer, local_variables, global_table, global_tracker)?,
+            None,
+        ),
         EvaluableTokens::None => (String::new(), None),
     })
 }
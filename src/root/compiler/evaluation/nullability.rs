@@ -0,0 +1,36 @@
+use crate::root::compiler::global_tracker::GlobalTracker;
+use crate::root::errors::evaluable_errors::EvalErrs;
+use crate::root::errors::WErr;
+use crate::root::name_resolver::name_resolvers::GlobalTable;
+use crate::root::parser::location::Location;
+use crate::root::shared::common::TypeRef;
+
+/// Errors if a value of type `found` at `location` can't be used where `target` is expected
+/// because it may be null. A nullable (`?&`) reference can be used as a non-nullable one if the
+/// nullability analysis found it can't be null at `location`
+pub fn check_nullability_coercion(
+    found: &TypeRef,
+    target: &TypeRef,
+    location: &Location,
+    global_table: &GlobalTable,
+    global_tracker: &GlobalTracker,
+) -> Result<(), WErr> {
+    if found.can_coerce_nullability_to(target) {
+        return Ok(());
+    }
+
+    // Only the outermost reference is tracked by the analysis
+    if found.non_null().can_coerce_nullability_to(target)
+        && global_tracker.is_known_non_null(location)
+    {
+        return Ok(());
+    }
+
+    WErr::ne(
+        EvalErrs::ExpectedNonNull(
+            global_table.get_type_name(target),
+            global_table.get_type_name(found),
+        ),
+        location.clone(),
+    )
+}
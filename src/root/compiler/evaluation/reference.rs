@@ -88,6 +88,14 @@ pub fn compile_evaluable_reference(
             // Cannot get an address without instantiation
             compile_evaluable_new(fid, et, local_variables, global_table, global_tracker)?
         }
+        EvaluableTokens::Block(_)
+        | EvaluableTokens::If(_)
+        | EvaluableTokens::HeapArray(_)
+        | EvaluableTokens::Index { .. }
+        | EvaluableTokens::Delete(_) => {
+            // Cannot get an address without instantiation
+            compile_evaluable_new(fid, et, local_variables, global_table, global_tracker)?
+        }
         EvaluableTokens::None => (String::new(), None),
     })
 }
@@ -0,0 +1,117 @@
+use crate::root::assembler::assembly_builder::{Assembly, AssemblyBuilder};
+use crate::root::builtin::types::bool::BoolType;
+use crate::root::compiler::evaluation::into::compile_evaluable_into;
+use crate::root::compiler::evaluation::type_only::compile_evaluable_type_only;
+use crate::root::compiler::global_tracker::GlobalTracker;
+use crate::root::compiler::local_variable_table::LocalVariableTable;
+use crate::root::errors::evaluable_errors::EvalErrs;
+use crate::root::errors::WErr;
+use crate::root::name_resolver::name_resolvers::GlobalTable;
+use crate::root::parser::parse_function::parse_evaluable::{EvaluableToken, EvaluableTokens};
+use crate::root::parser::parse_function::parse_literal::LiteralTokens;
+use crate::root::parser::parse_function::parse_operator::{OperatorToken, OperatorTokens};
+use crate::root::shared::common::{AddressedTypeRef, FunctionID, TypeRef};
+
+/// Whether an evaluable is the literal `null`
+pub fn is_null_literal(et: &EvaluableToken) -> bool {
+    matches!(et.token(), EvaluableTokens::Literal(literal) if matches!(literal.literal(), LiteralTokens::Null))
+}
+
+/// Returns the type of the references being compared if `lhs op rhs` compares two references
+/// (i.e. their addresses) with `==` or `!=`. `None` if the operator method of the type should be
+/// used instead
+pub fn reference_comparison_type(
+    fid: FunctionID,
+    lhs: &EvaluableToken,
+    op: &OperatorToken,
+    rhs: &EvaluableToken,
+    local_variables: &mut LocalVariableTable,
+    global_table: &mut GlobalTable,
+    global_tracker: &mut GlobalTracker,
+) -> Result<Option<TypeRef>, WErr> {
+    if !matches!(
+        op.operator(),
+        OperatorTokens::Equals | OperatorTokens::NotEqual
+    ) {
+        return Ok(None);
+    }
+
+    // `null` takes the type of the other side
+    let typed = if is_null_literal(lhs) {
+        if is_null_literal(rhs) {
+            return WErr::ne(EvalErrs::UntypedNull, lhs.location().clone());
+        }
+        rhs
+    } else {
+        lhs
+    };
+
+    let t = compile_evaluable_type_only(fid, typed, local_variables, global_table, global_tracker)?;
+    Ok(t.indirection().has_indirection().then_some(t))
+}
+
+/// Compares two references of type `operand_type` with `==` or `!=`, putting the result into
+/// `target`
+#[allow(clippy::too_many_arguments)]
+pub fn compile_reference_comparison_into(
+    fid: FunctionID,
+    lhs: &EvaluableToken,
+    op: &OperatorToken,
+    rhs: &EvaluableToken,
+    operand_type: TypeRef,
+    target: AddressedTypeRef,
+    local_variables: &mut LocalVariableTable,
+    global_table: &mut GlobalTable,
+    global_tracker: &mut GlobalTracker,
+) -> Result<Assembly, WErr> {
+    let bool_type = BoolType::id().immediate_single();
+    if target.type_ref() != &bool_type {
+        return WErr::ne(
+            EvalErrs::OpWrongReturnType(
+                global_table.get_type_name(target.type_ref()),
+                global_table.get_type_name(&bool_type),
+            ),
+            op.location().clone(),
+        );
+    }
+
+    let mut ab = AssemblyBuilder::new();
+
+    // Only the addresses are compared so either side may be any kind of reference to the type,
+    // including `null`
+    let operand_type = operand_type.with_mutability(0).with_nullability(u64::MAX);
+    let mut addresses = Vec::with_capacity(2);
+    for side in [lhs, rhs] {
+        let address =
+            global_table.add_local_variable_unnamed(operand_type.clone(), local_variables);
+        ab.other(&compile_evaluable_into(
+            fid,
+            side,
+            address.clone(),
+            local_variables,
+            global_table,
+            global_tracker,
+        )?);
+        addresses.push(address);
+    }
+
+    let jmp_true = global_tracker.get_unique_tag(fid);
+    let jmp_end = global_tracker.get_unique_tag(fid);
+    let jump = if op.operator() == &OperatorTokens::Equals {
+        "jz"
+    } else {
+        "jnz"
+    };
+    let return_into = target.local_address();
+
+    ab.line(&format!("mov rax, qword {}", addresses[0].local_address()));
+    ab.line(&format!("cmp rax, qword {}", addresses[1].local_address()));
+    ab.line(&format!("{jump} {jmp_true}"));
+    ab.line(&format!("mov byte {return_into}, 0"));
+    ab.line(&format!("jmp {jmp_end}"));
+    ab.line(&format!("{jmp_true}:"));
+    ab.line(&format!("mov byte {return_into}, 1"));
+    ab.line(&format!("{jmp_end}:"));
+
+    Ok(ab.finish())
+}
@@ -0,0 +1,152 @@
+use itertools::Itertools;
+
+use crate::root::assembler::assembly_builder::{Assembly, AssemblyBuilder};
+use crate::root::compiler::evaluation::into::compile_evaluable_into;
+use crate::root::compiler::global_tracker::GlobalTracker;
+use crate::root::compiler::local_variable_table::LocalVariableTable;
+use crate::root::errors::evaluable_errors::EvalErrs;
+use crate::root::errors::WErr;
+use crate::root::name_resolver::name_resolvers::GlobalTable;
+use crate::root::parser::parse_function::parse_evaluable::EvaluableToken;
+use crate::root::parser::parse_function::parse_struct_init::StructInitToken;
+use crate::root::shared::common::{AddressedTypeRef, FunctionID, LocalAddress, TypeID, TypeRef};
+
+/// Evaluates the attributes of a struct initialiser into `target`. Attributes may be given in any
+/// order, and those not given are filled with their default values (evaluated at the initialiser,
+/// but resolved as if in the struct's definition)
+pub fn compile_struct_init_attributes(
+    fid: FunctionID,
+    struct_init: &StructInitToken,
+    struct_type_ref: &TypeRef,
+    target: &AddressedTypeRef,
+    local_variables: &mut LocalVariableTable,
+    global_table: &mut GlobalTable,
+    global_tracker: &mut GlobalTracker,
+) -> Result<Assembly, WErr> {
+    let struct_type = global_table.get_type(*struct_type_ref.type_id());
+    let struct_name = struct_type.name().to_string();
+    let attributes = struct_type
+        .get_attributes(struct_init.location())
+        .map_err(|_| {
+            WErr::n(
+                EvalErrs::TypeCannotBeInitialised(struct_name.clone()),
+                struct_init.location().clone(),
+            )
+        })?
+        .iter()
+        .cloned()
+        .collect_vec();
+
+    let mut asm = AssemblyBuilder::new();
+    let mut initialised = vec![false; attributes.len()];
+
+    // Create all given attributes in the correct place in struct
+    for (name, val) in struct_init.contents() {
+        let Some(position) = attributes
+            .iter()
+            .position(|(_, t_name, _)| t_name.name() == name.name())
+        else {
+            return WErr::ne(
+                EvalErrs::TypeDoesntHaveAttribute(struct_name, name.name().clone()),
+                name.location().clone(),
+            );
+        };
+
+        if initialised[position] {
+            return WErr::ne(
+                EvalErrs::DuplicateAttributeInInit(name.name().clone()),
+                name.location().clone(),
+            );
+        }
+        initialised[position] = true;
+
+        let (offset, _, t_type) = &attributes[position];
+        let new_addr = AddressedTypeRef::new(
+            LocalAddress(target.local_address().0 + offset.0 as isize),
+            t_type.clone(),
+        );
+        asm.other(&compile_evaluable_into(
+            fid,
+            val,
+            new_addr,
+            local_variables,
+            global_table,
+            global_tracker,
+        )?);
+    }
+
+    // Fill remaining attributes with defaults
+    let mut missing = Vec::new();
+    for ((offset, t_name, t_type), _) in attributes
+        .iter()
+        .zip(initialised)
+        .filter(|(_, initialised)| !initialised)
+    {
+        let Some(default) = global_table
+            .get_attribute_default(*struct_type_ref.type_id(), t_name.name())
+            .cloned()
+        else {
+            missing.push(t_name.name().clone());
+            continue;
+        };
+
+        let new_addr = AddressedTypeRef::new(
+            LocalAddress(target.local_address().0 + offset.0 as isize),
+            t_type.clone(),
+        );
+        asm.other(&compile_default(
+            fid,
+            &default,
+            *struct_type_ref.type_id(),
+            new_addr,
+            local_variables,
+            global_table,
+            global_tracker,
+        )?);
+    }
+
+    if !missing.is_empty() {
+        return WErr::ne(
+            EvalErrs::MissingAttributesInInit(struct_name, missing.join(", ")),
+            struct_init.location().clone(),
+        );
+    }
+
+    Ok(asm.finish())
+}
+
+/// Evaluates an attribute default into `target` in the namespace of the file the struct is defined
+/// in and without access to the initialiser's local variables, so only global names can be used.
+/// Temporaries are still allocated in the current stack frame, below the initialiser's variables
+fn compile_default(
+    fid: FunctionID,
+    default: &EvaluableToken,
+    struct_type: TypeID,
+    target: AddressedTypeRef,
+    local_variables: &LocalVariableTable,
+    global_table: &mut GlobalTable,
+    global_tracker: &mut GlobalTracker,
+) -> Result<Assembly, WErr> {
+    let (caller_file, caller_scope) = global_table.namespace();
+    if let Some(file) = global_table
+        .get_type_location(struct_type)
+        .and_then(|l| l.file_id())
+    {
+        let scope = global_tracker.path_storage().get_file(file).scope().clone();
+        global_table.scope_namespace(file, scope);
+    }
+
+    let mut default_variables = LocalVariableTable::new();
+    default_variables.add_new_unnamed(local_variables.stack_size());
+    let result = compile_evaluable_into(
+        fid,
+        default,
+        target,
+        &mut default_variables,
+        global_table,
+        global_tracker,
+    );
+
+    global_table.scope_namespace(caller_file, caller_scope);
+    result
+}
@@ -1,4 +1,10 @@
+use crate::root::builtin::types::bool::BoolType;
+use crate::root::compiler::evaluation::block::{
+    compile_block_type_only, compile_if_expression_type_only,
+};
 use crate::root::compiler::evaluation::function_only;
+use crate::root::compiler::evaluation::heap::{heap_array_type, index_type};
+use crate::root::compiler::evaluation::reference_comparison::reference_comparison_type;
 use crate::root::compiler::global_tracker::GlobalTracker;
 use crate::root::compiler::local_variable_table::LocalVariableTable;
 use crate::root::errors::evaluable_errors::EvalErrs;
@@ -9,7 +15,7 @@ use crate::root::name_resolver::name_resolvers::{GlobalTable, NameResult};
 use crate::root::parser::parse_function::parse_evaluable::{EvaluableToken, EvaluableTokens};
 use crate::root::parser::parse_function::parse_operator::{OperatorTokens, PrefixOrInfixEx};
 use crate::root::parser::parse_name::SimpleNameToken;
-use crate::root::shared::common::{FunctionID, Indirection, TypeRef};
+use crate::root::shared::common::{FunctionID, TypeRef};
 
 /// Helper function for correctly handling a `NameResult` when trying to get a `TypeID`
 fn handle_name_result(name: &SimpleNameToken, name_result: NameResult) -> Result<TypeRef, WErr> {
@@ -61,11 +67,27 @@ pub fn compile_evaluable_type_only(
             )?,
         )?,
         EvaluableTokens::Literal(literal) => {
-            let tid = literal.literal().default_type();
-            // TODO: Don't use 0 here
-            TypeRef::new(tid, 0, Indirection(0))
+            let Some(type_id) = literal.literal().default_type() else {
+                return WErr::ne(EvalErrs::UntypedNull, literal.location().clone());
+            };
+            type_id.immediate_single()
         }
-        EvaluableTokens::InfixOperator(lhs, op, _) => {
+        EvaluableTokens::InfixOperator(lhs, op, rhs) => {
+            // References are compared by address
+            if reference_comparison_type(
+                fid,
+                lhs,
+                op,
+                rhs,
+                local_variables,
+                global_table,
+                global_tracker,
+            )?
+            .is_some()
+            {
+                return Ok(BoolType::id().immediate_single());
+            }
+
             // if op.is_prefix_opt_t() {
             //     return Err(WErr::n(EvalErrs::FoundPrefixNotInfixOp(op.operator().to_str().to_string()), op.location().clone()));
             // }
@@ -246,7 +268,21 @@ pub fn compile_evaluable_type_only(
             }
             struct_type
         }
-        EvaluableTokens::None => {
+        EvaluableTokens::Block(block) => {
+            compile_block_type_only(fid, block, local_variables, global_table, global_tracker)?
+        }
+        EvaluableTokens::If(if_expression) => compile_if_expression_type_only(
+            fid,
+            if_expression,
+            local_variables,
+            global_table,
+            global_tracker,
+        )?,
+        EvaluableTokens::HeapArray(heap_array) => heap_array_type(heap_array, global_table)?,
+        EvaluableTokens::Index { parent, index: _ } => {
+            index_type(fid, parent, local_variables, global_table, global_tracker)?
+        }
+        EvaluableTokens::Delete(_) | EvaluableTokens::None => {
             return WErr::ne(EvalErrs::ExpectedNotNone, et.location().clone());
         }
     })
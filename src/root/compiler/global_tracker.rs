@@ -1,9 +1,22 @@
 use crate::root::assembler::assembly_builder::Assembly;
+use crate::root::parser::location::Location;
 use crate::root::parser::path_storage::PathStorage;
 use crate::root::shared::common::FunctionID;
 use derive_getters::{Dissolve, Getters};
+use derive_new::new;
 use std::collections::HashSet;
 
+/// Which checks are compiled into the program to catch errors at runtime
+#[derive(Clone, Copy, Getters, new)]
+pub struct RuntimeChecks {
+    /// Check array indices are in bounds
+    bounds: bool,
+    /// Check integer arithmetic for overflow and division by zero
+    arithmetic: bool,
+    /// Track heap allocations to detect invalid frees and report leaks
+    heap: bool,
+}
+
 /// Tracks data between function compilations, including data about files and folders
 #[derive(Dissolve, Getters)]
 pub struct GlobalTracker<'a> {
@@ -12,16 +25,33 @@ pub struct GlobalTracker<'a> {
     readonly_contents: HashSet<String>,
     readonly_data_section: Assembly,
     unique_tag_counter: usize,
+    /// Checks to emit into the compiled code
+    runtime_checks: RuntimeChecks,
+    /// Whether the shared panic routine needs to be emitted
+    panic_used: bool,
+    /// Location of the inline function call currently being compiled
+    call_location: Location,
+    /// Locations of the assignments in the function being compiled that are the first to a
+    /// variable or field declared without a value, so are allowed even if it isn't mutable
+    initialising_assignments: Vec<Location>,
+    /// Locations of the nullable references in the function being compiled that are known not to
+    /// be null where they are used, so can be used as non-nullable references
+    non_null_values: Vec<Location>,
 }
 
 impl<'a> GlobalTracker<'a> {
-    pub fn new(path_storage: &'a PathStorage) -> GlobalTracker {
+    pub fn new(path_storage: &'a PathStorage, runtime_checks: RuntimeChecks) -> GlobalTracker {
         GlobalTracker {
             path_storage,
             function_calls: Default::default(),
             readonly_contents: Default::default(),
             readonly_data_section: "".to_string(),
             unique_tag_counter: 0,
+            runtime_checks,
+            panic_used: false,
+            call_location: Location::builtin(),
+            initialising_assignments: Vec::new(),
+            non_null_values: Vec::new(),
         }
     }
 
@@ -40,6 +70,27 @@ impl<'a> GlobalTracker<'a> {
         self.function_calls = Default::default();
     }
 
+    /// Sets the assignments in the function being compiled that initialise a variable or field
+    pub fn set_initialising_assignments(&mut self, locations: Vec<Location>) {
+        self.initialising_assignments = locations;
+    }
+
+    /// Returns whether the assignment to the reference at `location` is the first to a variable
+    /// or field declared without a value
+    pub fn is_initialising_assignment(&self, location: &Location) -> bool {
+        self.initialising_assignments.contains(location)
+    }
+
+    /// Sets the nullable references in the function being compiled that are known not to be null
+    pub fn set_non_null_values(&mut self, locations: Vec<Location>) {
+        self.non_null_values = locations;
+    }
+
+    /// Returns whether the nullable reference evaluated at `location` is known not to be null
+    pub fn is_known_non_null(&self, location: &Location) -> bool {
+        self.non_null_values.contains(location)
+    }
+
     /// Adds readonly data to be appended to the assembly, ensuring data is not stored twice
     pub fn add_readonly_data(&mut self, name: &str, data: &str) {
         if !self.readonly_contents.contains(name) {
@@ -55,4 +106,15 @@ impl<'a> GlobalTracker<'a> {
         self.unique_tag_counter += 1;
         r
     }
+
+    /// Stores that the shared panic routine has been jumped to to ensure it gets emitted
+    pub fn use_panic(&mut self) {
+        self.panic_used = true;
+    }
+
+    /// Sets the location of the inline function call about to be compiled so that e.g. `panic`
+    /// can report it
+    pub fn set_call_location(&mut self, location: Location) {
+        self.call_location = location;
+    }
 }
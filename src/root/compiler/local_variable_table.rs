@@ -1,9 +1,31 @@
+use crate::root::parser::location::Location;
 use crate::root::shared::common::{AddressedTypeRef, ByteSize, LocalAddress};
+use derive_getters::Getters;
 use std::collections::HashMap;
 
+/// How a named variable was declared
+#[derive(Getters, Clone, Debug)]
+pub struct Binding {
+    name: String,
+    mutable: bool,
+    location: Location,
+}
+
+impl Binding {
+    pub fn new(name: String, mutable: bool, location: Location) -> Binding {
+        Binding {
+            name,
+            mutable,
+            location,
+        }
+    }
+}
+
 /// Function-local table of defined variables. Only used within function processing
+#[derive(Clone)]
 pub struct LocalVariableTable {
     table: Vec<HashMap<String, AddressedTypeRef>>,
+    bindings: Vec<HashMap<LocalAddress, Binding>>,
     stack_size: Vec<ByteSize>,
 }
 
@@ -17,6 +39,7 @@ impl LocalVariableTable {
     pub fn new() -> LocalVariableTable {
         LocalVariableTable {
             table: vec![Default::default()],
+            bindings: vec![Default::default()],
             stack_size: vec![ByteSize(0)],
         }
     }
@@ -30,20 +53,26 @@ impl LocalVariableTable {
     pub fn enter_scope(&mut self) {
         self.stack_size.push(*self.stack_size.last().unwrap());
         self.table.push(Default::default());
+        self.bindings.push(Default::default());
     }
 
     /// Removes the topmost variable scope
     pub fn leave_scope(&mut self) {
         self.table.pop();
+        self.bindings.pop();
         self.stack_size.pop();
     }
 
     /// Adds an allocated, named variable to the variable table and stack size
-    pub fn add_existing(&mut self, name: String, addressed_type_ref: AddressedTypeRef) {
+    pub fn add_existing(&mut self, binding: Binding, addressed_type_ref: AddressedTypeRef) {
+        self.bindings
+            .last_mut()
+            .unwrap()
+            .insert(*addressed_type_ref.local_address(), binding.clone());
         self.table
             .last_mut()
             .unwrap()
-            .insert(name, addressed_type_ref);
+            .insert(binding.name, addressed_type_ref);
     }
 
     /// Adds a variable that can't be referenced to the stack size
@@ -65,4 +94,19 @@ impl LocalVariableTable {
         }
         None
     }
+
+    /// Returns the binding of the named variable stored at an address, if there is one
+    pub fn binding_at(&self, address: LocalAddress) -> Option<&Binding> {
+        self.bindings
+            .iter()
+            .rev()
+            .find_map(|bindings| bindings.get(&address))
+    }
+
+    /// Returns whether the value at an address can be mutated. Values that aren't named variables
+    /// (temporaries) can always be mutated
+    pub fn is_mutable_place(&self, address: LocalAddress) -> bool {
+        self.binding_at(address)
+            .is_none_or(|binding| *binding.mutable())
+    }
 }
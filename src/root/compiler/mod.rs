@@ -1,6 +1,7 @@
+pub mod analysis;
 pub mod assembly;
 pub mod compile;
-mod compile_function;
+pub mod compile_function;
 pub mod compile_function_call;
 pub mod evaluation;
 pub mod global_tracker;
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::root::name_resolver::name_resolvers::GlobalTable;
+use crate::root::name_resolver::resolve_function_signatures::FunctionSignature;
+use crate::root::parser::location::Location;
+use crate::root::parser::parse_function::FunctionToken;
+use crate::root::parser::parse_parameters::SelfType;
+use crate::root::parser::path_storage::PathStorage;
+use crate::root::shared::common::{FunctionID, TypeID, TypeRef};
+
+/// Anchor that links to a type's section
+fn type_anchor(type_id: TypeID) -> String {
+    format!("type-{}", type_id.0)
+}
+
+/// Name of a type linking to its section
+fn type_link(type_ref: &TypeRef, global_table: &GlobalTable) -> String {
+    format!(
+        "[`{}`](#{})",
+        global_table.get_type_name(type_ref),
+        type_anchor(*type_ref.type_id())
+    )
+}
+
+/// Formats a function's signature with its argument and return types linked
+fn signature_line(name: &str, signature: &FunctionSignature, global_table: &GlobalTable) -> String {
+    let args = signature
+        .args()
+        .iter()
+        .enumerate()
+        .map(|(i, (arg_name, type_ref))| {
+            if i == 0 {
+                match signature.self_type() {
+                    SelfType::None => {}
+                    SelfType::CopySelf => return "`self`".to_string(),
+                    SelfType::RefSelf => return "`&self`".to_string(),
+                    SelfType::MutRefSelf => return "`&mut self`".to_string(),
+                }
+            }
+            format!(
+                "`{}`: {}",
+                arg_name.name(),
+                type_link(type_ref, global_table)
+            )
+        })
+        .join(", ");
+
+    let return_type = signature
+        .return_type()
+        .as_ref()
+        .map(|t| format!(" -> {}", type_link(t, global_table)))
+        .unwrap_or_default();
+
+    format!("`fn` **{name}**({args}){return_type}")
+}
+
+/// Writes a function's signature, where it is defined and its doc comments
+fn document_function(
+    name: &str,
+    function_id: FunctionID,
+    functions: &HashMap<FunctionID, FunctionToken>,
+    global_table: &GlobalTable,
+    path_storage: &PathStorage,
+) -> String {
+    let mut out = format!(
+        "- {}\n",
+        signature_line(
+            name,
+            global_table.get_function_signature(function_id),
+            global_table
+        )
+    );
+
+    match functions.get(&function_id) {
+        Some(function) => {
+            out += &format!(
+                "\n  *Defined at `{}`*\n",
+                function.location().short_position(path_storage)
+            );
+            if let Some(docs) = function.docs() {
+                for line in docs.lines() {
+                    out += &format!("\n  {line}");
+                }
+                out += "\n";
+            }
+        }
+        None => out += "\n  *Builtin*\n",
+    }
+    out + "\n"
+}
+
+/// Writes a type's size, fields and methods
+fn document_type(
+    type_id: TypeID,
+    functions: &HashMap<FunctionID, FunctionToken>,
+    global_table: &GlobalTable,
+    path_storage: &PathStorage,
+) -> String {
+    let t = global_table.get_type(type_id);
+    let mut out = format!(
+        "<a id=\"{}\"></a>\n\n### `{}`\n\n",
+        type_anchor(type_id),
+        t.name()
+    );
+
+    if let Some(docs) = global_table.get_type_docs(type_id) {
+        out += docs;
+        out += "\n";
+    }
+    out += &format!("Size: {} bytes\n\n", t.size().0);
+
+    // Only user types have attributes
+    if let Ok(attributes) = t.get_attributes(&Location::builtin()) {
+        if !attributes.is_empty() {
+            out += "#### Fields\n\n| Offset | Name | Type | Description |\n| --- | --- | --- | --- |\n";
+            for (offset, name, type_ref) in attributes {
+                let docs = global_table
+                    .get_attribute_docs(type_id, name.name())
+                    .map(|d| d.trim_end().replace('\n', "<br>"))
+                    .unwrap_or_default();
+                out += &format!(
+                    "| {} | `{}` | {} | {} |\n",
+                    offset.0,
+                    name.name(),
+                    type_link(type_ref, global_table),
+                    docs
+                );
+            }
+            out += "\n";
+        }
+    }
+
+    if let Some(methods) = global_table.get_impl_functions(type_id) {
+        if !methods.is_empty() {
+            out += "#### Methods\n\n";
+            for (name, function_id) in methods.iter().sorted_by_key(|(name, _)| *name) {
+                out +=
+                    &document_function(name, *function_id, functions, global_table, path_storage);
+            }
+        }
+    }
+
+    out
+}
+
+/// Renders a Markdown reference of every type - with its fields, size and methods - and every
+/// function that isn't a method, including doc comments and links between types
+pub fn generate_docs(
+    global_table: &GlobalTable,
+    functions: &HashMap<FunctionID, FunctionToken>,
+    path_storage: &PathStorage,
+) -> String {
+    let mut out = "# Reference\n\n## Types\n\n".to_string();
+
+    let type_ids = global_table
+        .get_type_ids()
+        .into_iter()
+        .sorted_by_key(|id| (global_table.get_type(*id).name().to_string(), id.0))
+        .collect_vec();
+    for type_id in type_ids {
+        out += &document_type(type_id, functions, global_table, path_storage);
+    }
+
+    // Functions that aren't methods
+    let methods = global_table
+        .get_type_ids()
+        .into_iter()
+        .filter_map(|id| global_table.get_impl_functions(id))
+        .flat_map(|m| m.values().copied())
+        .collect_vec();
+    let user_functions = functions
+        .iter()
+        .filter(|(id, _)| !methods.contains(id))
+        .map(|(id, ft)| (ft.name().name().clone(), *id));
+    let builtin_functions = global_table
+        .get_builtin_functions()
+        .iter()
+        .map(|(name, id)| (name.clone(), *id));
+
+    out += "## Functions\n\n";
+    for (name, function_id) in user_functions
+        .chain(builtin_functions)
+        .sorted_by_key(|(name, id)| (name.clone(), id.0))
+    {
+        out += &document_function(&name, function_id, functions, global_table, path_storage);
+    }
+
+    out
+}
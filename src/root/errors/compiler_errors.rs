@@ -17,4 +17,12 @@ pub enum CompErrs {
     ExpectedNoReturn,
     #[error("Cannot break - not in a loop")]
     CannotBreak,
+    #[error("Cannot use ({0}) within a block used as a value")]
+    ControlFlowInBlockExpression(String),
+    #[error("Variable ({0}) may be used before it is initialised")]
+    UseOfUninitialised(String),
+    #[error("Reference to a value on the stack may outlive the function it is created in")]
+    ReferenceEscapes,
+    #[error("Reference of type ({0}) may be null - compare it against `null` before dereferencing it, indexing it or accessing its attributes")]
+    MaybeNull(String),
 }
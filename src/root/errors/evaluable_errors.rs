@@ -46,12 +46,30 @@ pub enum EvalErrs {
     TypeCannotBeInitialisedByLiteral(String),
     #[error("Type ({0}) doesn't have method ({1})")]
     TypeDoesntHaveMethod(String, String),
-    #[error("Expected attribute ({0}) to be initialised next, not ({1})")]
-    WrongAttributeNameInInit(String, String),
-    #[error("Expected ({0}) attributes to be initialised - found ({1})")]
-    WrongAttributeCount(usize, usize),
+    #[error("Attribute ({0}) is initialised more than once")]
+    DuplicateAttributeInInit(String),
+    #[error("Type ({0}) is missing attributes ({1}) which have no default values")]
+    MissingAttributesInInit(String, String),
+    #[error("All branches of an if used as a value must have the same type. Expected ({0}) but found ({1})")]
+    IfBranchTypeMismatch(String, String),
+    #[error("Cannot assign through ({0}) as it is not a mutable reference")]
+    AssignThroughImmutable(String),
+    #[error("Method ({0}) takes `&mut self` so must be called on a mutable value or through a mutable reference")]
+    ImmutableSelf(String),
+    #[error("Expected type ({0}) but found ({1}) which is not mutable")]
+    ExpectedMutable(String, String),
     #[error("Expected type, not imported file ({0})")]
     ExpectedTypeNotImportedFile(String),
     #[error("Cannot evaluate a standalone imported file ({0})")]
     CannotEvaluateStandaloneImportedFile(String),
+    #[error("Cannot infer the type of `null` - it must be used where a reference is expected")]
+    UntypedNull,
+    #[error("Expected type ({0}) but found `null` which can only be used as a nullable (`?&`) reference")]
+    NullNotReference(String),
+    #[error("Expected type ({0}) but found ({1}) which may be null - compare it against `null` first")]
+    ExpectedNonNull(String, String),
+    #[error("Cannot delete ({0}) - only heap array references (`&[T]`) allocated with `new [T; n]` can be deleted")]
+    DeleteNotHeap(String),
+    #[error("Cannot index ({0}) - only heap array references (`&[T]`) allocated with `new [T; n]` can be indexed")]
+    IndexNotHeap(String),
 }
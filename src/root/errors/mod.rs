@@ -3,6 +3,7 @@ use std::backtrace::Backtrace;
 use std::fmt::{Display, Formatter};
 
 use color_print::cformat;
+use derive_getters::Getters;
 
 use crate::root::parser::location::Location;
 use crate::root::parser::path_storage::PathStorage;
@@ -15,10 +16,15 @@ pub mod name_resolver_errors;
 pub mod parser_errors;
 
 /// Universal error for Whython-8
-#[derive(Debug)]
+#[derive(Debug, Getters)]
 pub struct WErr {
     error: String,
     location: Option<Location>, // ! Important, don't do file reads unless necessary (i.e. Display)
+    /// Additional information pointing at other locations e.g. a declaration
+    notes: Vec<(String, Location)>,
+    /// The most relevant part of an error made of several others e.g. when every alternative
+    /// parser failed, for tools that can only show one location
+    summary: Option<Box<WErr>>,
 }
 
 impl WErr {
@@ -29,6 +35,8 @@ impl WErr {
         let w = WErr {
             error: format!("{error}"),
             location: Some(location),
+            notes: Vec::new(),
+            summary: None,
         };
         #[cfg(debug_assertions)]
         if DEBUG_ON_ERROR {
@@ -45,6 +53,8 @@ impl WErr {
         let w = WErr {
             error: format!("{error}"),
             location: Some(location),
+            notes: Vec::new(),
+            summary: None,
         };
         #[cfg(debug_assertions)]
         if DEBUG_ON_ERROR {
@@ -59,6 +69,8 @@ impl WErr {
         Err(WErr {
             error: format!("{error}"),
             location: None,
+            notes: Vec::new(),
+            summary: None,
         })
     }
 
@@ -67,9 +79,31 @@ impl WErr {
         WErr {
             error: format!("{error}"),
             location: None,
+            notes: Vec::new(),
+            summary: None,
         }
     }
 
+    /// Sets the most relevant part of an error made of several others
+    pub fn with_summary(mut self, summary: WErr) -> WErr {
+        self.summary = Some(Box::new(summary));
+        self
+    }
+
+    /// Returns the most relevant part of the error - itself if it isn't made of several others
+    pub fn summarised(&self) -> &WErr {
+        match &self.summary {
+            Some(summary) => summary.summarised(),
+            None => self,
+        }
+    }
+
+    /// Adds a note pointing at another location to the error
+    pub fn with_note(mut self, note: impl Display, location: Location) -> WErr {
+        self.notes.push((format!("{note}"), location));
+        self
+    }
+
     pub fn with_context<'a>(&'a self, path_storage: &'a PathStorage) -> WErrContext {
         WErrContext {
             err: self,
@@ -87,7 +121,15 @@ impl WErr {
         } else {
             cformat!("<r,bold>Error:</>\n    {}", self.error)
         };
-        f.write_str(&text)
+        f.write_str(&text)?;
+        for (note, location) in &self.notes {
+            f.write_str(&cformat!(
+                "<y,bold>Note:</>\n    {}\n{}\n",
+                note,
+                location.clone().into_warning().with_context(path_storage)
+            ))?;
+        }
+        Ok(())
     }
 }
 
@@ -34,8 +34,18 @@ pub enum NRErrs {
     OpCantBePrefix(String),
     #[error("Operator ({0}) cannot be used as an infix operator")]
     OpCantBeInfix(String),
+    #[error("Method ({0}) cannot be defined for type ({1}) as it already has a builtin method of the same name")]
+    BuiltinMethodCollision(String, String),
     #[error("Size of type ({0}) cannot be determined due to circular definition with no indirection ({1})")]
     CircularType(String, String),
-    #[error("Circular import [{0}]")]
-    CircularImport(String),
+    #[error("Type alias ({0}) refers to itself")]
+    CircularTypeAlias(String),
+    #[error("Type alias ({0}) is a reference so cannot be used to access methods")]
+    IndirectAliasAccess(String),
+    #[error("Method ({0}) cannot be a test - only top-level functions can be marked '#[test]'")]
+    TestMethod(String),
+    #[error("Test ({0}) must take no parameters and return nothing")]
+    InvalidTestSignature(String),
+    #[error("The main function cannot be marked '#[test]'")]
+    MainTest,
 }
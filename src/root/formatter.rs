@@ -0,0 +1,797 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use color_print::cprintln;
+use itertools::Itertools;
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take_till};
+use nom::character::complete::{anychar, char};
+use nom::InputTake;
+use walkdir::WalkDir;
+
+use crate::root::parser::location::Location;
+use crate::root::parser::parse::{parse_files, ErrorTree, Span};
+use crate::root::parser::parse_blocks::{
+    parse_default_terminator_content, BRACE_TERMINATOR, BRACKET_TERMINATOR,
+};
+use crate::root::parser::parse_function::parse_block::BlockToken;
+use crate::root::parser::parse_function::parse_evaluable::{
+    EvaluableToken, EvaluableTokens, FullNameToken, FullNameTokens, UnresolvedTypeRefToken,
+};
+use crate::root::parser::parse_function::parse_if::IfExpressionToken;
+use crate::root::parser::parse_function::parse_line::LineTokens;
+use crate::root::parser::parse_function::parse_literal::LiteralTokens;
+use crate::root::parser::parse_function::parse_operator::OperatorTokens;
+use crate::root::parser::parse_function::FunctionToken;
+use crate::root::parser::parse_parameters::SelfType;
+use crate::root::parser::parse_toplevel::TopLevelTokens;
+use crate::root::parser::parse_util::discard_ignored;
+use crate::root::parser::path_storage::{FileID, PathStorage};
+use crate::root::Args;
+
+/// Number of spaces per level of indentation
+const INDENT: usize = 4;
+
+/// A comment in the source. Comments are discarded by the parser so are found in the text and
+/// placed back between the lines printed from the tokens
+struct Comment {
+    /// Offset from the start of the file in bytes
+    offset: usize,
+    /// Text of the comment, without trailing whitespace
+    text: String,
+    /// Whether the comment follows code on the same line, rather than being on its own line
+    trailing: bool,
+}
+
+/// Finds every comment in the text. Whython has no string literals so `//` and `/*` always start a
+/// comment
+fn find_comments(text: &str) -> Vec<Comment> {
+    let mut comments = Vec::new();
+    let mut offset = 0;
+
+    while let Some(c) = text[offset..].chars().next() {
+        let rest = &text[offset..];
+        let end = if rest.starts_with("//") {
+            rest.find('\n').unwrap_or(rest.len())
+        } else if rest.starts_with("/*") {
+            rest.find("*/").map_or(rest.len(), |e| e + 2)
+        } else {
+            offset += c.len_utf8();
+            continue;
+        };
+
+        let line_start = text[..offset].rfind('\n').map_or(0, |l| l + 1);
+        comments.push(Comment {
+            offset,
+            text: rest[..end].trim_end().to_string(),
+            trailing: !text[line_start..offset].trim().is_empty(),
+        });
+        offset += end;
+    }
+
+    comments
+}
+
+/// Reads an import from the start of `s` the same way as `parse_imports`, returning the text after
+/// it, its offset and its text e.g. `use std/linked_list;`
+fn read_import(s: Span) -> Option<(Span, usize, String)> {
+    let (s, _) = discard_ignored(s).ok()?;
+    let (s, keyword) = alt((tag::<_, _, ErrorTree>("use"), tag("import")))(s).ok()?;
+    let (s, _) = discard_ignored(s).ok()?;
+    let (s, path) = take_till::<_, _, ErrorTree>(|c| c == ';')(s).ok()?;
+    let (s, _) = char::<_, ErrorTree>(';')(s).ok()?;
+
+    Some((
+        s,
+        keyword.location_offset(),
+        format!("{} {};", keyword.fragment(), path.trim()),
+    ))
+}
+
+/// Finds the imports at the start of a file as their offsets and text
+fn find_imports(text: &str) -> Vec<(usize, String)> {
+    let mut s = Span::new_extra(text, FileID::MAIN_FILE);
+    let mut imports = Vec::new();
+    while let Some((ns, offset, import)) = read_import(s) {
+        imports.push((offset, import));
+        s = ns;
+    }
+    imports
+}
+
+/// Where an evaluable is printed within the evaluable containing it, which determines whether it
+/// needs brackets
+#[derive(Copy, Clone, PartialEq)]
+enum Position {
+    /// The evaluable is the whole of e.g. a line, argument or condition
+    Whole,
+    /// Nothing is printed after the evaluable
+    Last,
+    /// Something is printed after the evaluable
+    Inner,
+}
+
+/// Gets the offset of the first token of an evaluable
+fn evaluable_start(evaluable: &EvaluableToken) -> Option<usize> {
+    match evaluable.token() {
+        EvaluableTokens::InfixOperator(lhs, _, _) => evaluable_start(lhs),
+        EvaluableTokens::FunctionCall { function, .. } => evaluable_start(function),
+        EvaluableTokens::StaticAccess { parent, .. }
+        | EvaluableTokens::DynamicAccess { parent, .. }
+        | EvaluableTokens::Index { parent, .. } => evaluable_start(parent),
+        _ => evaluable.location().offset(),
+    }
+}
+
+/// Gets the offset of the first token of a line
+fn line_start(line: &LineTokens) -> Option<usize> {
+    match line {
+        LineTokens::Initialisation(it) => it.location().offset(),
+        LineTokens::If(it) => it.location().offset(),
+        LineTokens::While(wt) => wt.location().offset(),
+        LineTokens::Return(rt) => rt.location().offset(),
+        LineTokens::Break(bt) => bt.location().offset(),
+        LineTokens::NoOp(et) => evaluable_start(et),
+        #[cfg(debug_assertions)]
+        LineTokens::Marker(_) => None,
+    }
+}
+
+/// Whether an evaluable must be bracketed to be parsed the same way when printed at `position`
+fn needs_brackets(evaluable: &EvaluableToken, position: Position) -> bool {
+    match evaluable.token() {
+        // `delete` is only parsed at the start of an evaluable and takes the rest of it
+        EvaluableTokens::Delete(_) => position != Position::Whole,
+        // `from` takes the rest of the evaluable
+        EvaluableTokens::HeapArray(ht) => ht.from().is_some() && position == Position::Inner,
+        _ => false,
+    }
+}
+
+/// Whether an evaluable must be bracketed to have a section accessed, be called or be indexed
+fn needs_postfix_brackets(evaluable: &EvaluableToken) -> bool {
+    matches!(
+        evaluable.token(),
+        EvaluableTokens::InfixOperator(..) | EvaluableTokens::PrefixOperator(..)
+    ) || needs_brackets(evaluable, Position::Inner)
+}
+
+/// Gets the priority of an evaluable if it is an infix operation
+fn infix_priority(evaluable: &EvaluableToken) -> Option<usize> {
+    match evaluable.token() {
+        EvaluableTokens::InfixOperator(_, op, _) => Some(op.get_priority()),
+        _ => None,
+    }
+}
+
+/// Formats a name e.g. `a`, `a::b`
+fn format_full_name(name: &FullNameToken) -> String {
+    match name.token() {
+        FullNameTokens::Name { name, .. } => name.name().clone(),
+        FullNameTokens::StaticAccess { inner, name } => {
+            format!("{}::{}", format_full_name(inner), name.name())
+        }
+        FullNameTokens::DynamicAccess { inner, name } => {
+            format!("{}.{}", format_full_name(inner), name.name())
+        }
+    }
+}
+
+/// Formats a type e.g. `?&mut Node` or `&mut [int]`
+fn format_type(type_ref: &UnresolvedTypeRefToken) -> String {
+    let mut out = String::new();
+    // Outermost reference first
+    for i in (0..type_ref.indirection().0).rev() {
+        if (type_ref.nullability() >> i) & 1 == 1 {
+            out.push('?');
+        }
+        out.push('&');
+        if (type_ref.mutability() >> i) & 1 == 1 {
+            out += "mut ";
+        }
+        if (type_ref.heap() >> i) & 1 == 1 {
+            out.push('[');
+        }
+    }
+    out += &format_full_name(type_ref.inner());
+    out + &"]".repeat(type_ref.heap().count_ones() as usize)
+}
+
+/// Prints tokens with canonical indentation, spacing and blank lines, placing comments back between
+/// the printed lines
+struct Printer<'a> {
+    text: &'a str,
+    /// Comments not yet printed, in reverse order
+    comments: Vec<Comment>,
+    out: String,
+    depth: usize,
+    /// Whether the last line printed opened a block, so no blank line should follow it
+    opened: bool,
+    /// Whether the next line must be separated from the previous by a blank line
+    separate: bool,
+}
+
+impl<'a> Printer<'a> {
+    fn new(text: &'a str) -> Printer<'a> {
+        let mut comments = find_comments(text);
+        comments.reverse();
+        Printer {
+            text,
+            comments,
+            out: String::new(),
+            depth: 0,
+            opened: false,
+            separate: false,
+        }
+    }
+
+    fn push(&mut self, text: &str) {
+        self.out += text;
+    }
+
+    /// Whether the source has a blank line before the code at `offset`
+    fn blank_before(&self, offset: usize) -> bool {
+        let before = &self.text[..offset];
+        let code = before.trim_end_matches(|c: char| c.is_whitespace() || c == '(' || c == '{');
+        before[code.len()..].matches('\n').count() > 1
+    }
+
+    /// Starts a line at the current indentation, after a blank line if the code at `offset` has one
+    /// before it in the source. Comments before the line are printed first
+    fn start_line(&mut self, offset: Option<usize>) {
+        if let Some(offset) = offset {
+            self.print_comments(offset);
+        }
+        let blank = self.separate || offset.is_some_and(|o| self.blank_before(o));
+        if blank && !self.out.is_empty() && !self.opened {
+            self.out.push('\n');
+        }
+        self.out += &" ".repeat(self.depth * INDENT);
+        self.opened = false;
+        self.separate = false;
+    }
+
+    fn end_line(&mut self) {
+        self.opened = self.out.ends_with('{');
+        self.out.push('\n');
+    }
+
+    /// Whether there is a comment before `offset` that hasn't been printed
+    fn has_comment_before(&self, offset: Option<usize>) -> bool {
+        offset.is_some_and(|o| self.comments.last().is_some_and(|c| c.offset < o))
+    }
+
+    /// Prints the comments before `offset`. Trailing comments are added to the end of the last line
+    fn print_comments(&mut self, offset: usize) {
+        while self.comments.last().is_some_and(|c| c.offset < offset) {
+            let comment = self.comments.pop().unwrap();
+            if comment.trailing && self.out.ends_with('\n') {
+                self.out.pop();
+                self.out.push(' ');
+                self.out += &comment.text;
+                self.out.push('\n');
+                continue;
+            }
+
+            let blank = self.separate || self.blank_before(comment.offset);
+            if blank && !self.out.is_empty() && !self.opened {
+                self.out.push('\n');
+            }
+            self.out += &" ".repeat(self.depth * INDENT);
+            self.out += &comment.text;
+            self.out.push('\n');
+            self.opened = false;
+            self.separate = false;
+        }
+    }
+
+    /// Finds the offset of the closing brace of the first block at or after `from`, skipping
+    /// bracketed sections e.g. conditions
+    fn next_block_end(&self, from: Option<usize>) -> Option<usize> {
+        let (mut s, _) = Span::new_extra(self.text, FileID::MAIN_FILE).take_split(from?);
+        loop {
+            s = discard_ignored(s).ok()?.0;
+            if char::<_, ErrorTree>('{')(s).is_ok() {
+                let (after, _) = parse_default_terminator_content(s, &BRACE_TERMINATOR).ok()?;
+                return Some(after.location_offset() - 1);
+            }
+            s = if char::<_, ErrorTree>('(')(s).is_ok() {
+                parse_default_terminator_content(s, &BRACKET_TERMINATOR)
+                    .ok()?
+                    .0
+            } else {
+                anychar::<_, ErrorTree>(s).ok()?.0
+            };
+        }
+    }
+
+    /// Prints lines in braces ending at `end`, or `{}` if there are no lines or comments
+    fn print_body(&mut self, lines: &[LineTokens], end: Option<usize>) {
+        let lines = lines
+            .iter()
+            .filter(|l| !matches!(l, LineTokens::NoOp(et) if matches!(et.token(), EvaluableTokens::None)))
+            .collect_vec();
+        if lines.is_empty() && !self.has_comment_before(end) {
+            self.push("{}");
+            return;
+        }
+
+        self.push("{");
+        self.end_line();
+        self.depth += 1;
+        for line in lines {
+            self.print_line(line);
+        }
+        self.close_block(end);
+    }
+
+    /// Prints the closing brace at `end` of a block, after the comments in it
+    fn close_block(&mut self, end: Option<usize>) {
+        if let Some(end) = end {
+            self.print_comments(end);
+        }
+        self.depth -= 1;
+        self.start_line(None);
+        self.push("}");
+    }
+
+    fn print_file(&mut self, imports: &[(usize, String)], items: &[TopLevelTokens]) {
+        for (offset, import) in imports {
+            self.start_line(Some(*offset));
+            self.push(import);
+            self.end_line();
+        }
+
+        for item in items {
+            self.separate = true;
+            match item {
+                TopLevelTokens::Struct(st) => {
+                    let offset = st.location().offset();
+                    self.start_line(offset);
+                    self.push(&format!("struct {} ", st.name().name()));
+                    let end = self.next_block_end(offset);
+                    if st.attributes().is_empty() && !self.has_comment_before(end) {
+                        self.push("{}");
+                    } else {
+                        self.push("{");
+                        self.end_line();
+                        self.depth += 1;
+                        for (i, (name, type_name, default, _)) in st.attributes().iter().enumerate()
+                        {
+                            self.start_line(name.location().offset());
+                            self.push(&format!("{}: {}", name.name(), format_type(type_name)));
+                            if let Some(default) = default {
+                                self.push(" = ");
+                                self.print_evaluable(default, Position::Whole);
+                            }
+                            if i + 1 < st.attributes().len() {
+                                self.push(",");
+                            }
+                            self.end_line();
+                        }
+                        self.close_block(end);
+                    }
+                    self.end_line();
+                }
+                TopLevelTokens::Impl(it) => {
+                    let offset = it.location().offset();
+                    self.start_line(offset);
+                    self.push(&format!("impl {} ", it.name().name()));
+                    let end = self.next_block_end(offset);
+                    if it.functions().is_empty() && !self.has_comment_before(end) {
+                        self.push("{}");
+                    } else {
+                        self.push("{");
+                        self.end_line();
+                        self.depth += 1;
+                        for function in it.functions() {
+                            self.separate = true;
+                            self.print_function(function);
+                        }
+                        self.close_block(end);
+                    }
+                    self.end_line();
+                }
+                TopLevelTokens::Function(ft) => self.print_function(ft),
+                TopLevelTokens::TypeAlias(tt) => {
+                    self.start_line(tt.location().offset());
+                    self.push(&format!(
+                        "type {} = {};",
+                        tt.name().name(),
+                        format_type(tt.target())
+                    ));
+                    self.end_line();
+                }
+            }
+        }
+
+        self.print_comments(usize::MAX);
+    }
+
+    fn print_function(&mut self, function: &FunctionToken) {
+        let offset = function.location().offset();
+        if *function.is_test() {
+            // The attribute is before the function's location
+            self.start_line(offset.map(|o| self.text[..o].rfind("#[test]").unwrap_or(o)));
+            self.push("#[test]");
+            self.end_line();
+            self.start_line(None);
+        } else {
+            self.start_line(offset);
+        }
+
+        let parameters = function
+            .parameters()
+            .iter()
+            .enumerate()
+            .map(|(i, (name, type_name))| match function.self_type() {
+                SelfType::CopySelf if i == 0 => "self".to_string(),
+                SelfType::RefSelf if i == 0 => "&self".to_string(),
+                SelfType::MutRefSelf if i == 0 => "&mut self".to_string(),
+                _ => format!("{}: {}", name.name(), format_type(type_name)),
+            })
+            .join(", ");
+        self.push(&format!("fn {}({parameters}) ", function.name().name()));
+        if let Some(return_type) = function.return_type() {
+            self.push(&format!("-> {} ", format_type(return_type)));
+        }
+
+        self.print_body(function.lines(), function.end_location().offset());
+        self.end_line();
+    }
+
+    fn print_line(&mut self, line: &LineTokens) {
+        self.start_line(line_start(line));
+        match line {
+            LineTokens::Initialisation(it) => {
+                self.push("let ");
+                if *it.mutable() {
+                    self.push("mut ");
+                }
+                self.push(&format!(
+                    "{}: {}",
+                    it.name().name(),
+                    format_type(it.type_name())
+                ));
+                if let Some(value) = it.value() {
+                    self.push(" = ");
+                    self.print_evaluable(value, Position::Whole);
+                }
+                self.push(";");
+            }
+            LineTokens::If(it) => {
+                let mut end = self.next_block_end(it.location().offset());
+                self.push("if (");
+                self.print_evaluable(it.if_condition(), Position::Whole);
+                self.push(") ");
+                self.print_body(it.if_contents(), end);
+
+                for (condition, contents) in it.elif_condition_contents() {
+                    end = self.next_block_end(end.map(|e| e + 1));
+                    self.push(" else if (");
+                    self.print_evaluable(condition, Position::Whole);
+                    self.push(") ");
+                    self.print_body(contents, end);
+                }
+
+                if let Some(contents) = it.else_contents() {
+                    end = self.next_block_end(end.map(|e| e + 1));
+                    self.push(" else ");
+                    self.print_body(contents, end);
+                }
+            }
+            LineTokens::While(wt) => {
+                let end = self.next_block_end(wt.location().offset());
+                self.push("while (");
+                self.print_evaluable(wt.condition(), Position::Whole);
+                self.push(") ");
+                self.print_body(wt.contents(), end);
+            }
+            LineTokens::Return(rt) => {
+                self.push("return");
+                if let Some(value) = rt.return_value() {
+                    self.push(" ");
+                    self.print_evaluable(value, Position::Whole);
+                }
+                self.push(";");
+            }
+            LineTokens::Break(_) => self.push("break;"),
+            LineTokens::NoOp(et) => {
+                self.print_evaluable(et, Position::Whole);
+                self.push(";");
+            }
+            #[cfg(debug_assertions)]
+            LineTokens::Marker(mt) => self.push(&format!("@{}", mt.value().trim_end())),
+        }
+        self.end_line();
+    }
+
+    /// Prints an evaluable, bracketing it if `brackets` is set or it needs them at `position`
+    fn print_operand(&mut self, evaluable: &EvaluableToken, brackets: bool, position: Position) {
+        if brackets || needs_brackets(evaluable, position) {
+            self.push("(");
+            self.print_evaluable(evaluable, Position::Whole);
+            self.push(")");
+        } else {
+            self.print_evaluable(evaluable, position);
+        }
+    }
+
+    /// Prints an evaluable that has a section accessed, is called or is indexed
+    fn print_postfix_parent(&mut self, evaluable: &EvaluableToken) {
+        self.print_operand(
+            evaluable,
+            needs_postfix_brackets(evaluable),
+            Position::Inner,
+        );
+    }
+
+    fn print_evaluable(&mut self, evaluable: &EvaluableToken, position: Position) {
+        match evaluable.token() {
+            EvaluableTokens::Name(name, _) => self.push(name.name()),
+            EvaluableTokens::StaticAccess { parent, section } => {
+                self.print_postfix_parent(parent);
+                self.push(&format!("::{}", section.name()));
+            }
+            EvaluableTokens::DynamicAccess { parent, section } => {
+                self.print_postfix_parent(parent);
+                self.push(&format!(".{}", section.name()));
+            }
+            EvaluableTokens::FunctionCall { function, args } => {
+                self.print_postfix_parent(function);
+                self.push("(");
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        self.push(", ");
+                    }
+                    self.print_evaluable(arg, Position::Whole);
+                }
+                self.push(")");
+            }
+            EvaluableTokens::Literal(lt) => match lt.literal() {
+                LiteralTokens::Bool(b) => self.push(&b.to_string()),
+                LiteralTokens::Int(i) => self.push(&i.to_string()),
+                LiteralTokens::Null => self.push("null"),
+            },
+            EvaluableTokens::StructInitialiser(st) => {
+                if *st.heap_alloc() {
+                    self.push("new ");
+                }
+                self.push(&format_type(st.name()));
+                if st.contents().is_empty() {
+                    self.push(" {}");
+                    return;
+                }
+                self.push(" { ");
+                for (i, (name, value)) in st.contents().iter().enumerate() {
+                    if i > 0 {
+                        self.push(", ");
+                    }
+                    self.push(name.name());
+                    // Shorthand values share the location of the name
+                    if value.location() != name.location() {
+                        self.push(": ");
+                        self.print_evaluable(value, Position::Whole);
+                    }
+                }
+                self.push(" }");
+            }
+            EvaluableTokens::InfixOperator(lhs, op, rhs) => {
+                // Operators with the same priority are applied left to right
+                let priority = op.get_priority();
+                let lhs_brackets = infix_priority(lhs).is_some_and(|p| p > priority);
+                let rhs_brackets = infix_priority(rhs).is_some_and(|p| p >= priority);
+                let rhs_position = match position {
+                    Position::Inner => Position::Inner,
+                    _ => Position::Last,
+                };
+                self.print_operand(lhs, lhs_brackets, Position::Inner);
+                self.push(&format!(" {} ", op.operator().to_str()));
+                self.print_operand(rhs, rhs_brackets, rhs_position);
+            }
+            EvaluableTokens::PrefixOperator(op, operand) => {
+                self.push(op.operator().to_str());
+                // `& &a` would otherwise be read as `&&`
+                if *op.operator() == OperatorTokens::Reference
+                    && matches!(operand.token(), EvaluableTokens::PrefixOperator(inner, _)
+                        if *inner.operator() == OperatorTokens::Reference)
+                {
+                    self.push(" ");
+                }
+                let operand_position = match position {
+                    Position::Inner => Position::Inner,
+                    _ => Position::Last,
+                };
+                self.print_operand(operand, infix_priority(operand).is_some(), operand_position);
+            }
+            EvaluableTokens::Block(bt) => self.print_block(bt),
+            EvaluableTokens::If(it) => self.print_if_expression(it),
+            EvaluableTokens::HeapArray(ht) => {
+                self.push(&format!("new [{}; ", format_type(ht.element())));
+                self.print_evaluable(ht.length(), Position::Whole);
+                self.push("]");
+                if let Some(from) = ht.from() {
+                    self.push(" from ");
+                    self.print_evaluable(from, Position::Whole);
+                }
+            }
+            EvaluableTokens::Index { parent, index } => {
+                self.print_postfix_parent(parent);
+                self.push("[");
+                self.print_evaluable(index, Position::Whole);
+                self.push("]");
+            }
+            EvaluableTokens::Delete(pointer) => {
+                self.push("delete ");
+                self.print_evaluable(pointer, Position::Whole);
+            }
+            EvaluableTokens::None => {}
+        }
+    }
+
+    /// Prints a block used as a value, on one line if it is only its value
+    fn print_block(&mut self, block: &BlockToken) {
+        // The location of a block is the start of its contents
+        let end = self.next_block_end(block.location().offset().map(|o| o - 1));
+        if block.lines().is_empty() && !self.has_comment_before(end) {
+            self.push("{ ");
+            self.print_evaluable(block.value(), Position::Whole);
+            self.push(" }");
+            return;
+        }
+
+        self.push("{");
+        self.end_line();
+        self.depth += 1;
+        for line in block.lines() {
+            self.print_line(line);
+        }
+        self.start_line(evaluable_start(block.value()));
+        self.print_evaluable(block.value(), Position::Whole);
+        self.end_line();
+        self.close_block(end);
+    }
+
+    fn print_if_expression(&mut self, if_expression: &IfExpressionToken) {
+        self.push("if (");
+        self.print_evaluable(if_expression.if_condition(), Position::Whole);
+        self.push(") ");
+        self.print_block(if_expression.if_block());
+        for (condition, block) in if_expression.elif_condition_blocks() {
+            self.push(" else if (");
+            self.print_evaluable(condition, Position::Whole);
+            self.push(") ");
+            self.print_block(block);
+        }
+        self.push(" else ");
+        self.print_block(if_expression.else_block());
+    }
+}
+
+/// Formats the text of a Whython file from its tokens with canonical indentation, spacing, brace
+/// placement and blank lines, keeping its comments
+fn format_text(text: &str, items: &[TopLevelTokens]) -> String {
+    let mut printer = Printer::new(text);
+    printer.print_file(&find_imports(text), items);
+    printer.out
+}
+
+/// Finds every `.why` file in the given files and directories
+fn collect_files(paths: &[String]) -> Vec<PathBuf> {
+    paths
+        .iter()
+        .flat_map(|path| {
+            WalkDir::new(path)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .map(|e| e.into_path())
+                .filter(|p| p.is_file() && p.extension().is_some_and(|e| e == "why"))
+                .sorted()
+        })
+        .collect_vec()
+}
+
+/// Parses `text` as the contents of `file`, returning its tokens. Files it imports are read from
+/// disk so that imports resolve the same way
+fn parse_text(file: &Path, text: &str, args: &Args) -> Result<Vec<TopLevelTokens>, String> {
+    let mut path_storage = PathStorage::new(&file.to_string_lossy(), &args.search_paths())
+        .map_err(|e| format!("{e:?}"))?;
+    let path = path_storage.reconstruct_file(FileID::MAIN_FILE);
+    path_storage.set_contents(path, text.to_string());
+    let queue = vec![(FileID::MAIN_FILE, Location::builtin())];
+    parse_files(&mut path_storage, queue, HashMap::new(), false)
+        .map(|mut files| files.remove(&FileID::MAIN_FILE).unwrap_or_default())
+        .map_err(|e| e.with_context(&path_storage).to_string())
+}
+
+/// Checks formatted text keeps the meaning of the original by parsing it and formatting it again,
+/// which must not change it or its comments
+fn check_formatted(file: &Path, text: &str, formatted: &str, args: &Args) -> bool {
+    let Ok(items) = parse_text(file, formatted, args) else {
+        return false;
+    };
+    let comments = |text: &str| {
+        find_comments(text)
+            .into_iter()
+            .map(|c| c.text)
+            .collect_vec()
+    };
+    format_text(formatted, &items) == formatted && comments(text) == comments(formatted)
+}
+
+/// Formats the given files and directories, or checks that they are formatted if `check` is set.
+/// Files that fail to parse are reported and left unchanged. Exits with a non-zero code if any file
+/// fails to parse or, when checking, would be changed
+pub fn format_files(args: &Args, paths: &[String], check: bool) -> Result<(), String> {
+    let files = if paths.is_empty() {
+        vec![PathBuf::from(&args.input)]
+    } else {
+        collect_files(paths)
+    };
+
+    let mut changed = Vec::new();
+    let mut failed = Vec::new();
+
+    for file in files {
+        let text = fs::read_to_string(&file)
+            .map_err(|e| format!("Failed to read file ({}): {e}", file.display()))?;
+
+        let items = match parse_text(&file, &text, args) {
+            Ok(items) => items,
+            Err(e) => {
+                cprintln!(
+                    "\n<r,bold>Not formatting {} as it doesn't parse</>\n{}",
+                    file.display(),
+                    e
+                );
+                failed.push(file);
+                continue;
+            }
+        };
+
+        let formatted = format_text(&text, &items);
+        if formatted != text && !check_formatted(&file, &text, &formatted, args) {
+            cprintln!(
+                "<r,bold>Not formatting {} as formatting would change its meaning</>",
+                file.display()
+            );
+            failed.push(file);
+            continue;
+        }
+
+        if formatted != text {
+            if !check {
+                fs::write(&file, formatted)
+                    .map_err(|e| format!("Failed to write file ({}): {e}", file.display()))?;
+            }
+            changed.push(file);
+        }
+    }
+
+    let verb = if check {
+        "Would reformat"
+    } else {
+        "Reformatted"
+    };
+    for file in &changed {
+        println!("{verb} {}", file.display());
+    }
+    println!(
+        "{} file{} {}, {} failed to parse",
+        changed.len(),
+        if changed.len() == 1 { "" } else { "s" },
+        if check {
+            "not formatted"
+        } else {
+            "reformatted"
+        },
+        failed.len()
+    );
+
+    if !failed.is_empty() || (check && !changed.is_empty()) {
+        process::exit(1);
+    }
+    Ok(())
+}
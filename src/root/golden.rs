@@ -0,0 +1,262 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use color_print::cprintln;
+use itertools::Itertools;
+
+use crate::root::runner::run_captured;
+use crate::root::utils::strip_ansi;
+use crate::root::{build, check, Args, Built};
+
+/// Line of output the program must print, in order
+const EXPECT_STDOUT: &str = "// expect-stdout:";
+/// Exit code the program must return
+const EXPECT_EXIT: &str = "// expect-exit:";
+/// Text the compilation error must contain
+const EXPECT_ERROR: &str = "// expect-error:";
+/// Text the program's stderr must contain e.g. a panic message
+const EXPECT_STDERR: &str = "// expect-stderr:";
+/// Options the program is built with, from `--track-heap`, `--release` and `--no-bounds-checks`
+const FLAGS: &str = "// flags:";
+
+/// What a sample program is expected to do, read from its comments
+#[derive(Default)]
+struct Expectations {
+    stdout: Option<String>,
+    exit: Option<i32>,
+    errors: Vec<String>,
+    stderr: Vec<String>,
+    flags: Vec<String>,
+}
+
+impl Expectations {
+    fn is_empty(&self) -> bool {
+        self.stdout.is_none()
+            && self.exit.is_none()
+            && self.errors.is_empty()
+            && self.stderr.is_empty()
+    }
+}
+
+/// Reads the `// expect-...` directives from a file
+fn parse_expectations(text: &str) -> Result<Expectations, String> {
+    let mut expectations = Expectations::default();
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim_start();
+        // A single space after the colon is for readability
+        let value = |directive: &str| {
+            let value = &line[directive.len()..];
+            value.strip_prefix(' ').unwrap_or(value).to_string()
+        };
+
+        if line.starts_with(EXPECT_STDOUT) {
+            let stdout = expectations.stdout.get_or_insert_with(String::new);
+            *stdout += &value(EXPECT_STDOUT);
+            stdout.push('\n');
+        } else if line.starts_with(EXPECT_EXIT) {
+            let code = value(EXPECT_EXIT);
+            expectations.exit =
+                Some(code.trim().parse().map_err(|_| {
+                    format!("Invalid exit code ({}) on line {}", code.trim(), i + 1)
+                })?);
+        } else if line.starts_with(EXPECT_ERROR) {
+            expectations.errors.push(value(EXPECT_ERROR));
+        } else if line.starts_with(EXPECT_STDERR) {
+            expectations.stderr.push(value(EXPECT_STDERR));
+        } else if line.starts_with(FLAGS) {
+            for flag in value(FLAGS).split_whitespace() {
+                if !["--track-heap", "--release", "--no-bounds-checks"].contains(&flag) {
+                    return Err(format!("Unsupported flag ({flag}) on line {}", i + 1));
+                }
+                expectations.flags.push(flag.to_string());
+            }
+        }
+    }
+
+    Ok(expectations)
+}
+
+/// Removes the source lines shown with an error's location so that directives in the source
+/// can't match themselves
+fn strip_source_excerpts(error: &str) -> String {
+    error
+        .lines()
+        .filter(|line| {
+            let line = line.trim_start();
+            !line
+                .split_once(" |  ")
+                .is_some_and(|(gutter, _)| !gutter.is_empty() && !gutter.contains(' '))
+        })
+        .join("\n")
+}
+
+/// Formats a line-by-line diff of `expected` and `actual`
+fn diff(expected: &str, actual: &str) -> String {
+    let expected = expected.lines().collect_vec();
+    let actual = actual.lines().collect_vec();
+
+    // Longest common subsequence of lines from each position
+    let mut lcs = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lcs[i][j] = if expected[i] == actual[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            out += &format!("      {}\n", expected[i]);
+            i += 1;
+            j += 1;
+        } else if j == actual.len() || (i < expected.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out += &format!("    - {}\n", expected[i]);
+            i += 1;
+        } else {
+            out += &format!("    + {}\n", actual[j]);
+            j += 1;
+        }
+    }
+    out
+}
+
+/// Builds and runs a sample program, or checks and interprets it if interpreting, returning why it
+/// failed to meet its expectations
+fn check_file(
+    args: &Args,
+    file: &Path,
+    output: &str,
+    expectations: &Expectations,
+) -> Result<(), String> {
+    let mut args = args.clone();
+    for flag in &expectations.flags {
+        match flag.as_str() {
+            "--track-heap" => args.track_heap = true,
+            "--release" => args.release = true,
+            _ => args.no_bounds_checks = true,
+        }
+    }
+    let input = file.to_string_lossy();
+    let built = if args.interpret {
+        check(&args, &input, false, &mut Vec::new()).map(|p| Built::Interpreted(Box::new(p)))
+    } else {
+        build(&args, &input, output, false, &mut Vec::new()).map(|()| Built::Executable)
+    };
+
+    if !expectations.errors.is_empty() {
+        let Err(e) = built else {
+            return Err("Expected compilation to fail".to_string());
+        };
+        let e = strip_source_excerpts(&strip_ansi(&e));
+        for expected in &expectations.errors {
+            if !e.contains(expected.as_str()) {
+                return Err(format!(
+                    "Expected an error containing ({expected}), found:\n{e}"
+                ));
+            }
+        }
+        return Ok(());
+    }
+
+    let (code, captured) = match built.map_err(|e| format!("Compilation failed:\n{e}"))? {
+        Built::Executable => run_captured(output)?,
+        Built::Interpreted(mut program) => program.run_captured()?,
+    };
+
+    let mut mismatches = Vec::new();
+    if let Some(expected) = &expectations.stdout {
+        if captured.stdout != *expected {
+            mismatches.push(format!(
+                "Output differs (- expected, + actual):\n{}",
+                diff(expected, &captured.stdout)
+            ));
+        }
+    }
+    for expected in &expectations.stderr {
+        if !captured.stderr.contains(expected.as_str()) {
+            mismatches.push(format!(
+                "Expected stderr containing ({expected}), found:\n{}",
+                captured.stderr
+            ));
+        }
+    }
+    if let Some(expected) = expectations.exit {
+        if code != Some(expected) {
+            let code = code.map_or("none".to_string(), |c| c.to_string());
+            mismatches.push(format!("Expected exit code {expected}, found {code}"));
+        }
+    }
+
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(mismatches.join("\n"))
+    }
+}
+
+/// Builds and runs (or interprets) every `.why` file in `directory` with expectations, reporting
+/// those whose output, exit code or compilation error doesn't match. Exits with a non-zero code if
+/// any don't
+pub fn check_golden(args: &Args, directory: &str) -> Result<(), String> {
+    let files = fs::read_dir(directory)
+        .map_err(|e| format!("Failed to read directory ({directory}): {e}"))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == "why"))
+        .sorted()
+        .collect_vec();
+
+    let mut passed = 0usize;
+    let mut skipped = 0usize;
+    let mut failed = Vec::new();
+
+    for file in files {
+        let text = fs::read_to_string(&file)
+            .map_err(|e| format!("Failed to read file ({}): {e}", file.display()))?;
+        let expectations =
+            parse_expectations(&text).map_err(|e| format!("In file ({}): {e}", file.display()))?;
+        if expectations.is_empty() {
+            skipped += 1;
+            continue;
+        }
+
+        cprintln!("\n<s>Checking {}</>", file.display());
+        // Build each sample next to the normal output
+        let stem = file.file_stem().unwrap().to_string_lossy();
+        let output = PathBuf::from(&args.output)
+            .with_file_name(format!("golden_{stem}"))
+            .to_string_lossy()
+            .to_string();
+
+        match check_file(args, &file, &output, &expectations) {
+            Ok(()) => {
+                passed += 1;
+                cprintln!("\n<g,bold>ok</>");
+            }
+            Err(reason) => {
+                cprintln!("\n<r,bold>FAILED</>\n{}", reason);
+                failed.push(file);
+            }
+        }
+    }
+
+    println!(
+        "\n{passed} passed; {} failed; {skipped} without expectations",
+        failed.len()
+    );
+    if !failed.is_empty() {
+        cprintln!("<r,bold>Failing samples:</>");
+        for file in &failed {
+            println!("    {}", file.display());
+        }
+        process::exit(1);
+    }
+
+    Ok(())
+}
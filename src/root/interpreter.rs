@@ -0,0 +1,548 @@
+use std::collections::HashMap;
+use std::io;
+use std::io::Write;
+use std::time::Instant;
+
+use itertools::Itertools;
+
+use crate::root::builtin::types::bool::BoolType;
+use crate::root::builtin::types::int::IntType;
+use crate::root::compiler::assembly::heap::HEAP_HEADER_SIZE;
+use crate::root::compiler::assembly::panic::PANIC_EXIT_CODE;
+use crate::root::compiler::global_tracker::{GlobalTracker, RuntimeChecks};
+use crate::root::errors::WErr;
+use crate::root::interpreter::memory::{FreeError, Memory, MemoryError};
+use crate::root::name_resolver::name_resolvers::GlobalTable;
+use crate::root::parser::location::Location;
+use crate::root::parser::parse_function::FunctionToken;
+use crate::root::parser::path_storage::{FileID, PathStorage};
+use crate::root::runner::{print_exited, Captured};
+use crate::root::shared::common::{AddressedTypeRef, FunctionID, LocalAddress, TypeRef};
+
+mod evaluate;
+mod execute;
+pub mod memory;
+
+/// Why interpretation stopped before the function being run returned
+pub enum Halt {
+    /// `exit` was called with the exit code
+    Exit(i64),
+    /// A runtime check failed. The message includes the location
+    Panic(String),
+    /// The program couldn't be evaluated. Boxed to keep results small, as they are returned
+    /// through every frame of the interpreter's recursion
+    Error(Box<WErr>),
+}
+
+impl From<WErr> for Halt {
+    fn from(value: WErr) -> Self {
+        Halt::Error(Box::new(value))
+    }
+}
+
+/// Executes resolved functions directly using simulated memory, without generating assembly. The
+/// program is expected to have been checked by compiling it first
+///
+/// Values are laid out in memory as in compiled code - locals are stored at `LocalAddress`es
+/// relative to the base of the current stack frame, which builtins read and write using the
+/// methods below
+pub struct Interpreter<'a> {
+    global_table: &'a mut GlobalTable,
+    functions: &'a HashMap<FunctionID, FunctionToken>,
+    global_tracker: GlobalTracker<'a>,
+    memory: Memory,
+    /// Base of the current stack frame
+    rbp: u64,
+    /// Function being executed
+    function: FunctionID,
+    /// File the global table's namespace is scoped to
+    file: Option<FileID>,
+    /// Address of the interpreter's own stack when it was created, to measure how much of it is
+    /// used
+    host_stack_base: usize,
+    /// Location of the code being executed, reported when panicking
+    location: Location,
+    /// Where output is kept if it isn't printed
+    captured: Option<Captured>,
+}
+
+/// Stack size of the thread programs are interpreted on. The interpreter recurses for every
+/// function call, using more of its own stack than the simulated stack frame (several KiB in
+/// debug builds), so needs far more than the default
+pub const INTERPRETER_STACK_SIZE: usize = 1024 * 1024 * 1024;
+
+/// Space left on the interpreter's own stack when calls report a stack overflow, for builtins and
+/// reporting the panic
+const HOST_STACK_MARGIN: usize = 16 * 1024 * 1024;
+
+impl<'a> Interpreter<'a> {
+    pub fn new(
+        global_table: &'a mut GlobalTable,
+        functions: &'a HashMap<FunctionID, FunctionToken>,
+        path_storage: &'a PathStorage,
+        runtime_checks: RuntimeChecks,
+    ) -> Interpreter<'a> {
+        Interpreter::with_memory(
+            global_table,
+            functions,
+            path_storage,
+            runtime_checks,
+            Memory::new(),
+        )
+    }
+
+    /// Creates an interpreter that continues with the memory of a previous one e.g. so the
+    /// variables of a REPL session outlive the global table they were created with
+    pub fn with_memory(
+        global_table: &'a mut GlobalTable,
+        functions: &'a HashMap<FunctionID, FunctionToken>,
+        path_storage: &'a PathStorage,
+        runtime_checks: RuntimeChecks,
+        memory: Memory,
+    ) -> Interpreter<'a> {
+        Interpreter {
+            global_table,
+            functions,
+            global_tracker: GlobalTracker::new(path_storage, runtime_checks),
+            rbp: memory.stack_end(),
+            memory,
+            function: FunctionID::MAIN_FUNCTION,
+            file: None,
+            host_stack_base: host_stack_address(),
+            location: Location::builtin(),
+            captured: None,
+        }
+    }
+
+    /// Stops interpreting, returning the memory so it can be used by another interpreter
+    pub fn into_memory(self) -> Memory {
+        self.memory
+    }
+
+    /// Whether the interpreter's own stack is nearly used up. Programs with very small stack frames
+    /// can use it up before the simulated stack
+    fn host_stack_exhausted(&self) -> bool {
+        let used = self.host_stack_base.saturating_sub(host_stack_address());
+        used > INTERPRETER_STACK_SIZE - HOST_STACK_MARGIN
+    }
+
+    /// Returns the absolute address of a value in the current stack frame
+    fn address(&self, address: LocalAddress) -> u64 {
+        self.rbp.wrapping_add_signed(address.0 as i64)
+    }
+
+    /// Formats a location as `file:line:column`
+    fn position(&self, location: &Location) -> String {
+        location.short_position(self.global_tracker.path_storage())
+    }
+
+    /// Reads `size` bytes from `address`, panicking if they can't be accessed
+    fn read(&self, address: u64, size: usize) -> Result<&[u8], Halt> {
+        self.memory
+            .read(address, size)
+            .map_err(|e| self.memory_error(e))
+    }
+
+    /// Writes `bytes` to `address`, panicking if they can't be accessed
+    fn write(&mut self, address: u64, bytes: &[u8]) -> Result<(), Halt> {
+        self.memory
+            .write(address, bytes)
+            .map_err(|e| self.memory_error(e))
+    }
+
+    /// Copies `size` bytes from `from` to `to`, panicking if they can't be accessed
+    fn copy(&mut self, from: u64, to: u64, size: usize) -> Result<(), Halt> {
+        self.memory
+            .copy(from, to, size)
+            .map_err(|e| self.memory_error(e))
+    }
+
+    /// Panics describing an invalid memory access
+    fn memory_error(&self, error: MemoryError) -> Halt {
+        self.panic(&match error {
+            MemoryError::Null => "dereference of null reference".to_string(),
+            MemoryError::Freed { allocated, freed } => format!(
+                "use of memory allocated at {} after it was freed at {}",
+                self.position(&allocated),
+                self.position(&freed)
+            ),
+            MemoryError::Unallocated => "access of memory that isn't allocated".to_string(),
+        })
+    }
+
+    /// Panics describing an invalid free, using the same messages as tracked heap allocations
+    fn free_error(&self, error: FreeError) -> Halt {
+        self.panic(&match error {
+            FreeError::DoubleFree { allocated, freed } => format!(
+                "double free of memory allocated at {} and already freed at {}",
+                self.position(&allocated),
+                self.position(&freed)
+            ),
+            FreeError::NotHeap => "free of memory that isn't heap allocated".to_string(),
+        })
+    }
+
+    /// Returns a panic with `message` at the location being executed
+    pub fn panic(&self, message: &str) -> Halt {
+        Halt::Panic(format!(
+            "Panicked at {}: {message}",
+            self.position(&self.location)
+        ))
+    }
+
+    /// Reads an integer from the current stack frame
+    pub fn int(&self, address: LocalAddress) -> Result<i64, Halt> {
+        self.int_at(self.address(address))
+    }
+
+    /// Reads the integer a reference points to
+    pub fn int_at(&self, pointer: u64) -> Result<i64, Halt> {
+        Ok(i64::from_le_bytes(
+            self.read(pointer, 8)?.try_into().unwrap(),
+        ))
+    }
+
+    /// Writes an integer to the current stack frame
+    pub fn set_int(&mut self, address: LocalAddress, value: i64) -> Result<(), Halt> {
+        self.set_int_at(self.address(address), value)
+    }
+
+    /// Writes an integer to where a reference points
+    pub fn set_int_at(&mut self, pointer: u64, value: i64) -> Result<(), Halt> {
+        self.write(pointer, &value.to_le_bytes())
+    }
+
+    /// Reads a boolean from the current stack frame
+    pub fn bool(&self, address: LocalAddress) -> Result<bool, Halt> {
+        self.bool_at(self.address(address))
+    }
+
+    /// Reads the boolean a reference points to
+    pub fn bool_at(&self, pointer: u64) -> Result<bool, Halt> {
+        Ok(self.read(pointer, 1)?[0] != 0)
+    }
+
+    /// Writes a boolean to the current stack frame
+    pub fn set_bool(&mut self, address: LocalAddress, value: bool) -> Result<(), Halt> {
+        self.set_bool_at(self.address(address), value)
+    }
+
+    /// Writes a boolean to where a reference points
+    pub fn set_bool_at(&mut self, pointer: u64, value: bool) -> Result<(), Halt> {
+        self.write(pointer, &[value as u8])
+    }
+
+    /// Reads a reference from the current stack frame, returning the address it points to
+    pub fn pointer(&self, address: LocalAddress) -> Result<u64, Halt> {
+        self.pointer_at(self.address(address))
+    }
+
+    /// Reads the reference a reference points to
+    fn pointer_at(&self, pointer: u64) -> Result<u64, Halt> {
+        Ok(u64::from_le_bytes(
+            self.read(pointer, 8)?.try_into().unwrap(),
+        ))
+    }
+
+    /// Writes a reference to the current stack frame
+    pub fn set_pointer(&mut self, address: LocalAddress, value: u64) -> Result<(), Halt> {
+        self.write(self.address(address), &value.to_le_bytes())
+    }
+
+    /// Returns the result of an integer operation given by e.g. `i64::overflowing_add`, panicking
+    /// if it overflowed and arithmetic is checked. Unchecked arithmetic wraps
+    pub fn arithmetic(
+        &self,
+        operation: &str,
+        (result, overflowed): (i64, bool),
+    ) -> Result<i64, Halt> {
+        if overflowed && *self.global_tracker.runtime_checks().arithmetic() {
+            return Err(self.panic(&format!("attempt to {operation} with overflow")));
+        }
+        Ok(result)
+    }
+
+    /// Keeps output in memory instead of printing it
+    fn capture(&mut self) {
+        self.captured = Some(Captured::default());
+    }
+
+    /// Writes program output to stdout
+    pub fn print(&mut self, text: &str) {
+        match &mut self.captured {
+            Some(captured) => captured.stdout += text,
+            None => print!("{text}"),
+        }
+    }
+
+    /// Writes a line of output about the program, such as a panic message, to stderr
+    fn print_error(&mut self, line: &str) {
+        match &mut self.captured {
+            Some(captured) => {
+                captured.stderr += line;
+                captured.stderr.push('\n');
+            }
+            None => {
+                // Keep earlier output before the message
+                io::stdout().flush().ok();
+                eprintln!("{line}");
+            }
+        }
+    }
+
+    /// Formats a value in the current stack frame e.g. for the REPL. References are shown as
+    /// their address and structs with each of their attributes
+    pub fn display(&mut self, value: &AddressedTypeRef) -> Result<String, Halt> {
+        self.display_at(self.address(*value.local_address()), value.type_ref())
+    }
+
+    /// Returns the name of a type as it would be written
+    pub fn type_name(&self, t: &TypeRef) -> String {
+        self.global_table.get_type_name(t)
+    }
+
+    /// Formats the value of type `t` at `address`
+    fn display_at(&mut self, address: u64, t: &TypeRef) -> Result<String, Halt> {
+        if t.indirection().has_indirection() {
+            return Ok(match self.pointer_at(address)? {
+                0 => "null".to_string(),
+                pointer => format!("{pointer:#x}"),
+            });
+        }
+        if *t.type_id() == IntType::id() {
+            return Ok(self.int_at(address)?.to_string());
+        }
+        if *t.type_id() == BoolType::id() {
+            return Ok(self.bool_at(address)?.to_string());
+        }
+
+        let attributes = self
+            .global_table
+            .get_type(*t.type_id())
+            .get_attributes(&self.location)?
+            .to_vec();
+        let mut fields = Vec::with_capacity(attributes.len());
+        for (offset, name, t) in attributes {
+            let value = self.display_at(address + offset.0 as u64, &t)?;
+            fields.push(format!("{}: {value}", name.name()));
+        }
+        Ok(format!(
+            "{} {{ {} }}",
+            self.global_table.get_type_name(&t.type_id().immediate_single()),
+            fields.join(", ")
+        ))
+    }
+
+    /// Frees the heap allocation a reference points to, panicking if it isn't a heap allocation
+    /// or has already been freed. Freeing null does nothing
+    pub fn free(&mut self, pointer: u64) -> Result<(), Halt> {
+        let location = self.location.clone();
+        self.memory
+            .free(allocation_start(pointer), &location)
+            .map_err(|e| self.free_error(e))
+    }
+
+    /// Runs a function without parameters at the top of the stack, returning the address of its
+    /// return value
+    fn run_entry(&mut self, function: FunctionID) -> Result<u64, Halt> {
+        let return_size = if function.is_main() {
+            self.global_table
+                .get_size(&IntType::id().immediate_single())
+        } else {
+            match self
+                .global_table
+                .get_function_signature(function)
+                .return_type()
+                .clone()
+            {
+                Some(t) => self.global_table.get_size(&t),
+                None => Default::default(),
+            }
+        };
+        let rbp = self.memory.stack_end() - return_size.0 as u64 - 16;
+        self.execute_function(function, rbp)?;
+        Ok(rbp + 16)
+    }
+
+    /// Reports how a program run ended, returning its exit code. Heap memory that was never freed
+    /// is reported if the heap is checked and the program didn't panic
+    pub fn finish(&mut self, result: Result<i64, Halt>) -> Result<i32, WErr> {
+        let code = match result {
+            Ok(code) | Err(Halt::Exit(code)) => code,
+            Err(Halt::Panic(message)) => {
+                self.print_error(&message);
+                return Ok(PANIC_EXIT_CODE as i32);
+            }
+            Err(Halt::Error(e)) => return Err(*e),
+        };
+
+        let leaks = self
+            .memory
+            .leaks()
+            .into_iter()
+            .map(|(size, site)| {
+                format!(
+                    "    {} bytes allocated at {}",
+                    size - HEAP_HEADER_SIZE,
+                    self.position(site)
+                )
+            })
+            .collect_vec();
+        if *self.global_tracker.runtime_checks().heap() && !leaks.is_empty() {
+            self.print_error("Leak report: heap memory was never freed");
+            for leak in leaks {
+                self.print_error(&leak);
+            }
+        }
+
+        // Only the lowest byte of an exit code is kept
+        Ok(code as u8 as i32)
+    }
+}
+
+/// Returns the address of the top of the caller's frame on the interpreter's own stack
+#[inline(never)]
+fn host_stack_address() -> usize {
+    let marker = 0u8;
+    &marker as *const u8 as usize
+}
+
+/// Returns the start of the heap allocation a reference to its first value points into, before
+/// the header. Null is left as it is
+fn allocation_start(pointer: u64) -> u64 {
+    if pointer == 0 {
+        0
+    } else {
+        pointer.wrapping_sub(HEAP_HEADER_SIZE as u64)
+    }
+}
+
+/// A checked program held in memory, ready to be interpreted
+pub struct Program {
+    global_table: GlobalTable,
+    functions: HashMap<FunctionID, FunctionToken>,
+    path_storage: PathStorage,
+    runtime_checks: RuntimeChecks,
+    /// Whether to run the functions marked `#[test]` instead of `main`
+    test: bool,
+}
+
+impl Program {
+    pub fn new(
+        global_table: GlobalTable,
+        functions: HashMap<FunctionID, FunctionToken>,
+        path_storage: PathStorage,
+        runtime_checks: RuntimeChecks,
+        test: bool,
+    ) -> Program {
+        Program {
+            global_table,
+            functions,
+            path_storage,
+            runtime_checks,
+            test,
+        }
+    }
+
+    /// Interprets the program, returning its exit code if it could be run
+    pub fn run(&mut self) -> Option<i32> {
+        let start = Instant::now();
+        let result = if self.test {
+            self.run_tests()
+        } else {
+            self.run_main(false).map(|(code, _)| code)
+        };
+
+        match result {
+            Ok(code) => {
+                print_exited(code, start.elapsed());
+                Some(code)
+            }
+            Err(e) => {
+                println!("\n{}", e.with_context(&self.path_storage));
+                None
+            }
+        }
+    }
+
+    /// Interprets `main` without printing anything, returning its exit code and what it wrote to
+    /// stdout and stderr
+    pub fn run_captured(&mut self) -> Result<(Option<i32>, Captured), String> {
+        let (code, captured) = self
+            .run_main(true)
+            .map_err(|e| e.with_context(&self.path_storage).to_string())?;
+        Ok((Some(code), captured.unwrap()))
+    }
+
+    /// Interprets `main`, returning its exit code and, if `capture` is set, its output instead of
+    /// printing it
+    fn run_main(&mut self, capture: bool) -> Result<(i32, Option<Captured>), WErr> {
+        let mut interpreter = Interpreter::new(
+            &mut self.global_table,
+            &self.functions,
+            &self.path_storage,
+            self.runtime_checks,
+        );
+        if capture {
+            interpreter.capture();
+        }
+        let code = interpreter
+            .run_entry(FunctionID::MAIN_FUNCTION)
+            .and_then(|address| interpreter.int_at(address));
+        let code = interpreter.finish(code)?;
+        Ok((code, interpreter.captured.take()))
+    }
+
+    /// Runs every test with its own memory so that a failing test doesn't affect the others,
+    /// reporting results as the compiled test harness does. Returns 1 if any test failed
+    fn run_tests(&mut self) -> Result<i32, WErr> {
+        let tests = self
+            .functions
+            .iter()
+            .filter(|(_, ft)| *ft.is_test())
+            .map(|(id, ft)| (*id, ft.name().name().clone(), ft.location().clone()))
+            .sorted_by_key(|(id, _, _)| id.0)
+            .collect_vec();
+
+        println!(
+            "\nrunning {} test{}",
+            tests.len(),
+            if tests.len() == 1 { "" } else { "s" }
+        );
+        let mut failures = Vec::new();
+        for (id, name, location) in tests.iter() {
+            let mut interpreter = Interpreter::new(
+                &mut self.global_table,
+                &self.functions,
+                &self.path_storage,
+                self.runtime_checks,
+            );
+            let result = interpreter.run_entry(*id).map(|_| 0);
+            let code = interpreter.finish(result)?;
+            if code == 0 {
+                println!("test {name} ... ok");
+            } else {
+                println!("test {name} ... FAILED (exit code {code})");
+                failures.push((name, location));
+            }
+        }
+
+        if !failures.is_empty() {
+            println!("\nfailures:");
+            for (name, location) in &failures {
+                println!(
+                    "    {name} at {}",
+                    location.short_position(&self.path_storage)
+                );
+            }
+        }
+        println!(
+            "\ntest result: {}. {} passed; {} failed",
+            if failures.is_empty() { "ok" } else { "FAILED" },
+            tests.len() - failures.len(),
+            failures.len()
+        );
+
+        Ok(if failures.is_empty() { 0 } else { 1 })
+    }
+}
@@ -0,0 +1,963 @@
+use either::{Either, Left, Right};
+use itertools::Itertools;
+
+use crate::root::builtin::types::bool::BoolType;
+use crate::root::builtin::types::int::IntType;
+use crate::root::compiler::assembly::heap::HEAP_HEADER_SIZE;
+use crate::root::compiler::evaluation::function_only::compile_evaluable_function_only;
+use crate::root::compiler::evaluation::mutability::reference_is_mutable;
+use crate::root::compiler::evaluation::reference_comparison::reference_comparison_type;
+use crate::root::compiler::evaluation::type_only::compile_evaluable_type_only;
+use crate::root::compiler::local_variable_table::LocalVariableTable;
+use crate::root::errors::evaluable_errors::EvalErrs;
+use crate::root::errors::WErr;
+use crate::root::interpreter::{allocation_start, Halt, Interpreter};
+use crate::root::name_resolver::name_resolvers::NameResult;
+use crate::root::parser::location::Location;
+use crate::root::parser::parse_function::parse_block::BlockToken;
+use crate::root::parser::parse_function::parse_evaluable::{EvaluableToken, EvaluableTokens};
+use crate::root::parser::parse_function::parse_heap::HeapArrayToken;
+use crate::root::parser::parse_function::parse_if::IfExpressionToken;
+use crate::root::parser::parse_function::parse_literal::{LiteralToken, LiteralTokens};
+use crate::root::parser::parse_function::parse_operator::{
+    OperatorToken, OperatorTokens, PrefixOrInfixEx,
+};
+use crate::root::parser::parse_function::parse_struct_init::StructInitToken;
+use crate::root::parser::parse_name::SimpleNameToken;
+use crate::root::parser::parse_parameters::SelfType;
+use crate::root::shared::common::{
+    AddressedTypeRef, FunctionID, Indirection, LocalAddress, TypeRef,
+};
+
+/// Returns the error for an evaluable that was expected to have a value
+fn expected_not_none(et: &EvaluableToken) -> Halt {
+    WErr::n(EvalErrs::ExpectedNotNone, et.location().clone()).into()
+}
+
+impl Interpreter<'_> {
+    /// Adds an unnamed variable of type `t` to the current stack frame
+    fn temporary(
+        &mut self,
+        t: TypeRef,
+        local_variables: &mut LocalVariableTable,
+    ) -> AddressedTypeRef {
+        self.global_table
+            .add_local_variable_unnamed(t, local_variables)
+    }
+
+    /// Returns the size of a type in bytes
+    fn size(&mut self, t: &TypeRef) -> usize {
+        self.global_table.get_size(t).0
+    }
+
+    /// Copies a value in the current stack frame
+    fn copy_local(&mut self, from: &AddressedTypeRef, to: &AddressedTypeRef) -> Result<(), Halt> {
+        let size = self.size(to.type_ref());
+        self.copy(
+            self.address(*from.local_address()),
+            self.address(*to.local_address()),
+            size,
+        )
+    }
+
+    /// Follows `count` references starting from a value in the current stack frame, returning the
+    /// address reached
+    fn dereference(&self, address: LocalAddress, count: usize) -> Result<u64, Halt> {
+        let mut address = self.address(address);
+        for _ in 0..count {
+            address = self.pointer_at(address)?;
+        }
+        Ok(address)
+    }
+
+    /// Evaluates `et` into `target`
+    pub(super) fn evaluate_into(
+        &mut self,
+        et: &EvaluableToken,
+        target: AddressedTypeRef,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<(), Halt> {
+        self.location = et.location().clone();
+
+        match et.token() {
+            EvaluableTokens::Literal(literal) => self.literal_into(literal, target),
+            EvaluableTokens::Block(block) => self.block_into(block, target, local_variables),
+            EvaluableTokens::If(if_expression) => {
+                self.if_into(if_expression, target, local_variables)
+            }
+            _ => {
+                if let Some(value) = self.evaluate_new(et, local_variables)? {
+                    self.copy_local(&value, &target)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Writes a literal into `target`
+    fn literal_into(
+        &mut self,
+        literal: &LiteralToken,
+        target: AddressedTypeRef,
+    ) -> Result<(), Halt> {
+        // `null` coerces to any reference
+        let bytes = if matches!(literal.literal(), LiteralTokens::Null) {
+            vec![0; 8]
+        } else {
+            self.global_table
+                .get_type(*target.type_ref().type_id())
+                .literal_bytes(literal)?
+        };
+        self.write(self.address(*target.local_address()), &bytes)
+    }
+
+    /// Evaluates the block of the first branch of an if expression whose condition is true into
+    /// `target`
+    fn if_into(
+        &mut self,
+        if_expression: &IfExpressionToken,
+        target: AddressedTypeRef,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<(), Halt> {
+        let conditions = [(
+            if_expression.if_condition().as_ref(),
+            if_expression.if_block(),
+        )]
+        .into_iter()
+        .chain(
+            if_expression
+                .elif_condition_blocks()
+                .iter()
+                .map(|(c, b)| (c, b)),
+        );
+
+        for (condition, block) in conditions {
+            if self.condition(condition, local_variables)? {
+                return self.block_into(block, target, local_variables);
+            }
+        }
+        self.block_into(if_expression.else_block(), target, local_variables)
+    }
+
+    /// Evaluates a block into `target`
+    fn block_into(
+        &mut self,
+        block: &BlockToken,
+        target: AddressedTypeRef,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<(), Halt> {
+        // Lines and the trailing value share a scope
+        local_variables.enter_scope();
+        self.execute_lines_in_scope(block.lines(), &None, local_variables)?;
+        self.evaluate_into(block.value(), target, local_variables)?;
+        local_variables.leave_scope();
+        Ok(())
+    }
+
+    /// Evaluates a boolean condition
+    pub(super) fn condition(
+        &mut self,
+        condition: &EvaluableToken,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<bool, Halt> {
+        let address = self.temporary(BoolType::id().immediate_single(), local_variables);
+        self.evaluate_into(condition, address.clone(), local_variables)?;
+        self.bool(*address.local_address())
+    }
+
+    /// Evaluates `et` into a new address
+    ///
+    /// Each kind of evaluable is evaluated by its own method. This frame stays on the host stack
+    /// for every nested call being interpreted, so keeping it small lets programs recurse as deeply
+    /// as the simulated stack allows
+    pub(super) fn evaluate_new(
+        &mut self,
+        et: &EvaluableToken,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<Option<AddressedTypeRef>, Halt> {
+        self.location = et.location().clone();
+
+        match et.token() {
+            EvaluableTokens::Name(_, _) => self.copy_variable(et, local_variables),
+            EvaluableTokens::Literal(literal) => self.new_literal(et, literal, local_variables),
+            EvaluableTokens::InfixOperator(lhs, op, rhs) => {
+                if op.operator() == &OperatorTokens::Assign {
+                    self.assign(lhs, rhs, local_variables).map(|()| None)
+                } else {
+                    self.infix_operator(lhs, op, rhs, local_variables)
+                }
+            }
+            EvaluableTokens::PrefixOperator(op, operand) => match op.operator() {
+                OperatorTokens::Reference => self.reference(operand, local_variables).map(Some),
+                OperatorTokens::Multiply => {
+                    self.dereference_value(operand, local_variables).map(Some)
+                }
+                _ => self.operator_call(
+                    operand,
+                    op,
+                    PrefixOrInfixEx::Prefix,
+                    &[Left(operand)],
+                    local_variables,
+                ),
+            },
+            EvaluableTokens::DynamicAccess { parent, section } => self
+                .dynamic_access(parent, section, local_variables)
+                .map(Some),
+            // Checked when compiling - constant attributes don't exist
+            EvaluableTokens::StaticAccess { .. } => Err(expected_not_none(et)),
+            EvaluableTokens::FunctionCall { function, args } => {
+                self.function_call(et, function, args, local_variables)
+            }
+            EvaluableTokens::StructInitialiser(struct_init) => {
+                self.struct_init(struct_init, local_variables).map(Some)
+            }
+            EvaluableTokens::Block(_) | EvaluableTokens::If(_) => {
+                self.new_value(et, local_variables)
+            }
+            EvaluableTokens::HeapArray(heap_array) => {
+                self.heap_array(heap_array, local_variables).map(Some)
+            }
+            EvaluableTokens::Index { parent, index } => {
+                self.index(et, parent, index, local_variables).map(Some)
+            }
+            EvaluableTokens::Delete(pointer) => {
+                self.delete(pointer, local_variables).map(|()| None)
+            }
+            EvaluableTokens::None => Ok(None),
+        }
+    }
+
+    /// Evaluates a literal into a variable of its default type
+    fn new_literal(
+        &mut self,
+        et: &EvaluableToken,
+        literal: &LiteralToken,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<Option<AddressedTypeRef>, Halt> {
+        let Some(type_id) = literal.literal().default_type() else {
+            return Err(WErr::n(EvalErrs::UntypedNull, literal.location().clone()).into());
+        };
+        self.evaluate_into_temporary(et, type_id.immediate_single(), local_variables)
+            .map(Some)
+    }
+
+    /// Evaluates a block or if expression into a variable of the type of its value
+    fn new_value(
+        &mut self,
+        et: &EvaluableToken,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<Option<AddressedTypeRef>, Halt> {
+        let t = compile_evaluable_type_only(
+            self.function,
+            et,
+            local_variables,
+            self.global_table,
+            &mut self.global_tracker,
+        )?;
+        self.evaluate_into_temporary(et, t, local_variables)
+            .map(Some)
+    }
+
+    /// Evaluates a name into a copy of the variable it refers to
+    fn copy_variable(
+        &mut self,
+        et: &EvaluableToken,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<Option<AddressedTypeRef>, Halt> {
+        let Some(variable) = self.evaluate_reference(et, local_variables)? else {
+            return Ok(None);
+        };
+        let target = self.temporary(variable.type_ref().clone(), local_variables);
+        self.copy_local(&variable, &target)?;
+        Ok(Some(target))
+    }
+
+    /// Evaluates `et` into a new variable of type `t`
+    fn evaluate_into_temporary(
+        &mut self,
+        et: &EvaluableToken,
+        t: TypeRef,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<AddressedTypeRef, Halt> {
+        let target = self.temporary(t, local_variables);
+        self.evaluate_into(et, target.clone(), local_variables)?;
+        Ok(target)
+    }
+
+    /// Evaluates an evaluable that must have a value, returning the address of an existing
+    /// variable if it is one
+    fn evaluate_some_reference(
+        &mut self,
+        et: &EvaluableToken,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<AddressedTypeRef, Halt> {
+        match self.evaluate_reference(et, local_variables)? {
+            Some(value) => Ok(value),
+            None => Err(expected_not_none(et)),
+        }
+    }
+
+    /// Evaluates `lhs = rhs`, writing `rhs` to where the reference `lhs` points
+    fn assign(
+        &mut self,
+        lhs: &EvaluableToken,
+        rhs: &EvaluableToken,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<(), Halt> {
+        let Some(into) = self.evaluate_new(lhs, local_variables)? else {
+            return Err(WErr::n(EvalErrs::ExpectedNotNone, lhs.location().clone()).into());
+        };
+        let value = self.evaluate_into_temporary(
+            rhs,
+            into.type_ref().minus_one_indirect(),
+            local_variables,
+        )?;
+
+        self.location = lhs.location().clone();
+        let size = self.size(value.type_ref());
+        let pointer = self.pointer(*into.local_address())?;
+        self.copy(self.address(*value.local_address()), pointer, size)
+    }
+
+    /// Evaluates an infix operator other than assignment
+    fn infix_operator(
+        &mut self,
+        lhs: &EvaluableToken,
+        op: &OperatorToken,
+        rhs: &EvaluableToken,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<Option<AddressedTypeRef>, Halt> {
+        // References are compared by address
+        match reference_comparison_type(
+            self.function,
+            lhs,
+            op,
+            rhs,
+            local_variables,
+            self.global_table,
+            &mut self.global_tracker,
+        )? {
+            Some(operand_type) => self
+                .compare_references(lhs, op, rhs, operand_type, local_variables)
+                .map(Some),
+            None => self.operator_call(
+                lhs,
+                op,
+                PrefixOrInfixEx::Infix,
+                &[Left(lhs), Left(rhs)],
+                local_variables,
+            ),
+        }
+    }
+
+    /// Evaluates `lhs == rhs` or `lhs != rhs` where both are references of `operand_type`,
+    /// comparing their addresses
+    fn compare_references(
+        &mut self,
+        lhs: &EvaluableToken,
+        op: &OperatorToken,
+        rhs: &EvaluableToken,
+        operand_type: TypeRef,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<AddressedTypeRef, Halt> {
+        let operand_type = operand_type.with_mutability(0).with_nullability(u64::MAX);
+        let mut addresses = Vec::with_capacity(2);
+        for side in [lhs, rhs] {
+            let address =
+                self.evaluate_into_temporary(side, operand_type.clone(), local_variables)?;
+            addresses.push(self.pointer(*address.local_address())?);
+        }
+
+        let equal = addresses[0] == addresses[1];
+        let target = self.temporary(BoolType::id().immediate_single(), local_variables);
+        self.set_bool(
+            *target.local_address(),
+            equal == (op.operator() == &OperatorTokens::Equals),
+        )?;
+        Ok(target)
+    }
+
+    /// Calls the function implementing an operator for the type of `operand`
+    fn operator_call(
+        &mut self,
+        operand: &EvaluableToken,
+        op: &OperatorToken,
+        kind: PrefixOrInfixEx,
+        arguments: &[Either<&EvaluableToken, AddressedTypeRef>],
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<Option<AddressedTypeRef>, Halt> {
+        let operand_type = compile_evaluable_type_only(
+            self.function,
+            operand,
+            local_variables,
+            self.global_table,
+            &mut self.global_tracker,
+        )?;
+        let operator_fn =
+            self.global_table
+                .get_operator_function(*operand_type.type_id(), op, kind)?;
+        let uses_self = self
+            .global_table
+            .get_function_signature(operator_fn)
+            .self_type()
+            .uses_self();
+
+        self.call(
+            operator_fn,
+            uses_self,
+            op.location(),
+            arguments,
+            local_variables,
+        )
+    }
+
+    /// Evaluates `&operand`
+    fn reference(
+        &mut self,
+        operand: &EvaluableToken,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<AddressedTypeRef, Halt> {
+        let value = self.evaluate_some_reference(operand, local_variables)?;
+        let mutable = local_variables.is_mutable_place(*value.local_address());
+        let target = self.temporary(
+            value.type_ref().plus_one_indirect_mutable(mutable),
+            local_variables,
+        );
+        self.set_pointer(
+            *target.local_address(),
+            self.address(*value.local_address()),
+        )?;
+        Ok(target)
+    }
+
+    /// Evaluates `*operand`, copying the value the reference points to
+    fn dereference_value(
+        &mut self,
+        operand: &EvaluableToken,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<AddressedTypeRef, Halt> {
+        let value = self.evaluate_some_reference(operand, local_variables)?;
+        let target = self.temporary(value.type_ref().minus_one_indirect(), local_variables);
+        let size = self.size(target.type_ref());
+
+        self.location = operand.location().clone();
+        let pointer = self.pointer(*value.local_address())?;
+        self.copy(pointer, self.address(*target.local_address()), size)?;
+        Ok(target)
+    }
+
+    /// Evaluates `parent.access`, a reference to the attribute
+    fn dynamic_access(
+        &mut self,
+        parent: &EvaluableToken,
+        access: &SimpleNameToken,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<AddressedTypeRef, Halt> {
+        let inner = self.evaluate_some_reference(parent, local_variables)?;
+        let inner = if inner.type_ref().indirection().0 > 1 {
+            self.coerce_self(inner, SelfType::RefSelf, local_variables)?
+        } else {
+            inner
+        };
+
+        let inner_type = self.global_table.get_type(*inner.type_ref().type_id());
+        let Some((offset, t)) = inner_type
+            .get_attributes(access.location())?
+            .iter()
+            .find(|(_, name, _)| name.name() == access.name())
+            .map(|(offset, _, t)| (*offset, t.clone()))
+        else {
+            return Err(WErr::n(
+                EvalErrs::TypeDoesntHaveAttribute(
+                    inner_type.name().to_string(),
+                    access.name().clone(),
+                ),
+                access.location().clone(),
+            )
+            .into());
+        };
+
+        // Attributes are as mutable as the value (or reference) they're accessed through
+        let mutable = reference_is_mutable(&inner, local_variables);
+        let target = self.temporary(t.plus_one_indirect_mutable(mutable), local_variables);
+
+        let base = if inner.type_ref().indirection().has_indirection() {
+            self.pointer(*inner.local_address())?
+        } else {
+            self.address(*inner.local_address())
+        };
+        self.set_pointer(*target.local_address(), base.wrapping_add(offset.0 as u64))?;
+        Ok(target)
+    }
+
+    /// Evaluates a call (`et`) of `function` with `args`
+    fn function_call(
+        &mut self,
+        et: &EvaluableToken,
+        function: &EvaluableToken,
+        args: &[EvaluableToken],
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<Option<AddressedTypeRef>, Halt> {
+        let (slf, function_id, _) = compile_evaluable_function_only(
+            self.function,
+            function,
+            local_variables,
+            self.global_table,
+            &mut self.global_tracker,
+        )?;
+        let uses_self = self
+            .global_table
+            .get_function_signature(function_id)
+            .self_type()
+            .uses_self();
+
+        let mut arguments = Vec::with_capacity(args.len() + 1);
+        if let Some(slf) = slf {
+            arguments.push(Right(self.evaluate_some_reference(slf, local_variables)?));
+        }
+        arguments.extend(args.iter().map(Left));
+
+        self.call(
+            function_id,
+            uses_self,
+            et.location(),
+            &arguments,
+            local_variables,
+        )
+    }
+
+    /// Evaluates `delete pointer`, freeing the heap allocation it refers to
+    fn delete(
+        &mut self,
+        pointer: &EvaluableToken,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<(), Halt> {
+        let address = self.evaluate_some_reference(pointer, local_variables)?;
+        let pointer_value = self.pointer(*address.local_address())?;
+        self.location = pointer.location().clone();
+        self.free(pointer_value)
+    }
+
+    /// Evaluates `et`, returning the address of an existing variable if it is one instead of
+    /// copying it
+    pub(super) fn evaluate_reference(
+        &mut self,
+        et: &EvaluableToken,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<Option<AddressedTypeRef>, Halt> {
+        match et.token() {
+            EvaluableTokens::Name(name, containing_class) => self
+                .variable(name, containing_class, local_variables)
+                .map(Some),
+            _ => self.evaluate_new(et, local_variables),
+        }
+    }
+
+    /// Returns the variable a name refers to
+    fn variable(
+        &mut self,
+        name: &SimpleNameToken,
+        containing_class: &Option<SimpleNameToken>,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<AddressedTypeRef, Halt> {
+        match self.global_table.resolve_name(
+            name,
+            None,
+            containing_class.as_ref(),
+            local_variables,
+            &self.global_tracker,
+        )? {
+            NameResult::Variable(address) => Ok(address),
+            // Checked when compiling - names must be values
+            _ => Err(WErr::n(
+                EvalErrs::CannotEvalStandaloneType(name.name().clone()),
+                name.location().clone(),
+            )
+            .into()),
+        }
+    }
+
+    /// Creates a struct, filling attributes that aren't given with their defaults
+    fn struct_init(
+        &mut self,
+        struct_init: &StructInitToken,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<AddressedTypeRef, Halt> {
+        let struct_type_ref = self
+            .global_table
+            .resolve_to_type_ref(struct_init.name(), None)?;
+        let target = self.temporary(struct_type_ref.clone(), local_variables);
+        let attributes = self
+            .global_table
+            .get_type(*struct_type_ref.type_id())
+            .get_attributes(struct_init.location())?
+            .iter()
+            .cloned()
+            .collect_vec();
+
+        // Given attributes are evaluated in the order they're written, then defaults
+        let given = struct_init
+            .contents()
+            .iter()
+            .filter_map(|(name, value)| {
+                attributes
+                    .iter()
+                    .find(|(_, attribute, _)| attribute.name() == name.name())
+                    .map(|attribute| (attribute.clone(), value.clone()))
+            })
+            .collect_vec();
+        let mut defaults = Vec::new();
+        for attribute in &attributes {
+            if given
+                .iter()
+                .any(|((_, name, _), _)| name.name() == attribute.1.name())
+            {
+                continue;
+            }
+            let Some(default) = self
+                .global_table
+                .get_attribute_default(*struct_type_ref.type_id(), attribute.1.name())
+                .cloned()
+            else {
+                return Err(WErr::n(
+                    EvalErrs::MissingAttributesInInit(
+                        self.global_table.get_type_name(&struct_type_ref),
+                        attribute.1.name().clone(),
+                    ),
+                    struct_init.location().clone(),
+                )
+                .into());
+            };
+            defaults.push((attribute.clone(), default));
+        }
+
+        for ((offset, _, t), value) in given {
+            let address =
+                AddressedTypeRef::new(*target.local_address() + LocalAddress(offset.0 as isize), t);
+            self.evaluate_into(&value, address, local_variables)?;
+        }
+
+        // Defaults are evaluated as if in the struct's definition, without the initialiser's
+        // variables
+        let caller_file = self.file;
+        if let Some(file) = self
+            .global_table
+            .get_type_location(*struct_type_ref.type_id())
+            .and_then(|l| l.file_id())
+        {
+            self.scope_to(file);
+        }
+        let mut default_variables = LocalVariableTable::new();
+        default_variables.add_new_unnamed(local_variables.stack_size());
+        let result = defaults.into_iter().try_for_each(|((offset, _, t), value)| {
+            let address =
+                AddressedTypeRef::new(*target.local_address() + LocalAddress(offset.0 as isize), t);
+            self.evaluate_into(&value, address, &mut default_variables)
+        });
+        if let Some(file) = caller_file {
+            self.scope_to(file);
+        }
+        result?;
+
+        if !*struct_init.heap_alloc() {
+            return Ok(target);
+        }
+
+        let size = self.size(&struct_type_ref);
+        self.location = struct_init.location().clone();
+        let pointer = self.allocate(1, size as i64, None)?;
+        self.copy(self.address(*target.local_address()), pointer, size)?;
+
+        let reference = self.temporary(
+            struct_type_ref.plus_one_indirect_mutable(true),
+            local_variables,
+        );
+        self.set_pointer(*reference.local_address(), pointer)?;
+        Ok(reference)
+    }
+
+    /// Allocates a heap array, or reallocates the one it is `from`
+    fn heap_array(
+        &mut self,
+        heap_array: &HeapArrayToken,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<AddressedTypeRef, Halt> {
+        let element = self
+            .global_table
+            .resolve_to_type_ref(heap_array.element(), None)?;
+
+        let length = self.temporary(IntType::id().immediate_single(), local_variables);
+        self.evaluate_into(heap_array.length(), length.clone(), local_variables)?;
+        let length = self.int(*length.local_address())?;
+
+        let from = match heap_array.from() {
+            Some(from) => {
+                let Some(from) = self.evaluate_reference(from, local_variables)? else {
+                    return Err(WErr::n(EvalErrs::ExpectedNotNone, from.location().clone()).into());
+                };
+                Some(self.pointer(*from.local_address())?)
+            }
+            None => None,
+        };
+
+        let size = self.size(&element) as i64;
+        self.location = heap_array.location().clone();
+        let pointer = self.allocate(length, size, from)?;
+
+        let target = self.temporary(element.plus_one_indirect_heap(true), local_variables);
+        self.set_pointer(*target.local_address(), pointer)?;
+        Ok(target)
+    }
+
+    /// Evaluates `parent[index]` (`et`), a reference to the value at `index` in the heap allocation
+    /// `parent` refers to. Panics if the index is out of bounds
+    fn index(
+        &mut self,
+        et: &EvaluableToken,
+        parent: &EvaluableToken,
+        index: &EvaluableToken,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<AddressedTypeRef, Halt> {
+        let t = compile_evaluable_type_only(
+            self.function,
+            et,
+            local_variables,
+            self.global_table,
+            &mut self.global_tracker,
+        )?;
+
+        let Some(reference) = self.evaluate_reference(parent, local_variables)? else {
+            return Err(WErr::n(EvalErrs::ExpectedNotNone, parent.location().clone()).into());
+        };
+        let pointer = self.pointer(*reference.local_address())?;
+
+        let position = self.temporary(IntType::id().immediate_single(), local_variables);
+        self.evaluate_into(index, position.clone(), local_variables)?;
+        let position = self.int(*position.local_address())?;
+
+        // The number of values is in the header before the first. Negative indices are as large
+        // as they are when compared unsigned in compiled code
+        if *self.global_tracker.runtime_checks().bounds() {
+            let length = self.int_at(pointer.wrapping_sub(HEAP_HEADER_SIZE as u64))?;
+            if position as u64 >= length as u64 {
+                self.location = et.location().clone();
+                return Err(self.panic("index out of bounds"));
+            }
+        }
+
+        let size = self.size(&t.minus_one_indirect()) as i64;
+        let target = self.temporary(t, local_variables);
+        self.set_pointer(
+            *target.local_address(),
+            pointer.wrapping_add(position.wrapping_mul(size) as u64),
+        )?;
+        Ok(target)
+    }
+
+    /// Allocates `length` values of `size` bytes on the heap at the current location, reallocating
+    /// `from` if given, and returns a reference to the first value. Checked arithmetic panics on a
+    /// negative length or an overflowing size
+    fn allocate(&mut self, length: i64, size: i64, from: Option<u64>) -> Result<u64, Halt> {
+        if length < 0 && *self.global_tracker.runtime_checks().arithmetic() {
+            return Err(self.panic("attempt to allocate a negative number of values"));
+        }
+        let bytes = self
+            .arithmetic("allocate", length.overflowing_mul(size))
+            .map_err(|_| self.panic("attempt to allocate with overflow"))?;
+        let bytes = self
+            .arithmetic("allocate", bytes.overflowing_add(HEAP_HEADER_SIZE as i64))
+            .map_err(|_| self.panic("attempt to allocate with overflow"))?;
+
+        // Unchecked negative sizes are as large as they are when passed to `malloc`
+        let bytes = bytes as u64;
+        let location = self.location.clone();
+        let start = match from {
+            Some(from) => self
+                .memory
+                .reallocate(allocation_start(from), bytes, &location)
+                .map_err(|e| self.free_error(e))?,
+            None => self.memory.allocate(bytes, &location),
+        };
+        if start == 0 {
+            return Ok(0);
+        }
+        self.set_int_at(start, length)?;
+        Ok(start + HEAP_HEADER_SIZE as u64)
+    }
+
+    /// Coerces a value used as `self` into the form the method takes it in
+    fn coerce_self(
+        &mut self,
+        current_self: AddressedTypeRef,
+        self_type: SelfType,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<AddressedTypeRef, Halt> {
+        let indirection = current_self.type_ref().indirection().0;
+        Ok(match self_type {
+            SelfType::None => current_self,
+            SelfType::CopySelf => {
+                if indirection == 0 {
+                    return Ok(current_self);
+                }
+                let new_self = self.temporary(current_self.type_ref().immediate(), local_variables);
+                let size = self.size(new_self.type_ref());
+                let from = self.dereference(*current_self.local_address(), indirection)?;
+                self.copy(from, self.address(*new_self.local_address()), size)?;
+                new_self
+            }
+            SelfType::RefSelf | SelfType::MutRefSelf => {
+                if indirection == 0 {
+                    let mutable = local_variables.is_mutable_place(*current_self.local_address());
+                    let new_self = self.temporary(
+                        current_self.type_ref().plus_one_indirect_mutable(mutable),
+                        local_variables,
+                    );
+                    self.set_pointer(
+                        *new_self.local_address(),
+                        self.address(*current_self.local_address()),
+                    )?;
+                    new_self
+                } else if indirection == 1 {
+                    current_self
+                } else {
+                    let new_self = self.temporary(
+                        current_self.type_ref().with_indirection(Indirection(1)),
+                        local_variables,
+                    );
+                    // Want a reference, not the inner value
+                    let from = self.dereference(*current_self.local_address(), indirection - 1)?;
+                    self.copy(from, self.address(*new_self.local_address()), 8)?;
+                    new_self
+                }
+            }
+        })
+    }
+
+    /// Calls a function, evaluating its arguments in the current stack frame. Right arguments have
+    /// already been evaluated (only possible for `self`). Returns the address of the return value
+    pub(super) fn call(
+        &mut self,
+        function: FunctionID,
+        uses_self: bool,
+        location: &Location,
+        arguments: &[Either<&EvaluableToken, AddressedTypeRef>],
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<Option<AddressedTypeRef>, Halt> {
+        let args = self.evaluate_arguments(function, uses_self, arguments, local_variables)?;
+
+        let return_type = self
+            .global_table
+            .get_function_signature(function)
+            .return_type()
+            .clone();
+        let return_into = return_type.map(|t| self.temporary(t, local_variables));
+        self.location = location.clone();
+
+        if let Some(interpret) = self.global_table.get_interpreted_function(function) {
+            let args = args.iter().map(|a| *a.local_address()).collect_vec();
+            interpret(
+                &args,
+                return_into.as_ref().map(|r| *r.local_address()),
+                self,
+            )?;
+        } else {
+            self.call_defined(function, &args, &return_into, local_variables)?;
+        }
+        Ok(return_into)
+    }
+
+    /// Evaluates the arguments of a call to `function` into the current stack frame, coercing
+    /// `self` into the form the function takes it in
+    fn evaluate_arguments(
+        &mut self,
+        function: FunctionID,
+        uses_self: bool,
+        arguments: &[Either<&EvaluableToken, AddressedTypeRef>],
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<Vec<AddressedTypeRef>, Halt> {
+        let signature_args = self
+            .global_table
+            .get_function_signature(function)
+            .args()
+            .iter()
+            .map(|(_, t)| t.clone())
+            .collect_vec();
+
+        let mut args = Vec::with_capacity(arguments.len());
+        for (i, (argument, t)) in arguments.iter().zip(signature_args).enumerate() {
+            let arg = match argument {
+                _ if i == 0 && uses_self => {
+                    self.evaluate_self(function, argument, t, local_variables)?
+                }
+                Left(et) => self.evaluate_into_temporary(et, t, local_variables)?,
+                Right(arg) => arg.clone(),
+            };
+            args.push(arg);
+        }
+        Ok(args)
+    }
+
+    /// Evaluates `self` for a call to `function` (taking it as type `t`) in the form the function
+    /// takes it in
+    fn evaluate_self(
+        &mut self,
+        function: FunctionID,
+        argument: &Either<&EvaluableToken, AddressedTypeRef>,
+        t: TypeRef,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<AddressedTypeRef, Halt> {
+        let self_type = *self
+            .global_table
+            .get_function_signature(function)
+            .self_type();
+        let slf = match argument {
+            Left(et) if self_type.is_reference() => {
+                self.evaluate_some_reference(et, local_variables)?
+            }
+            Left(et) => self.evaluate_into_temporary(et, t, local_variables)?,
+            Right(slf) => slf.clone(),
+        };
+        self.coerce_self(slf, self_type, local_variables)
+    }
+
+    /// Calls a function defined in code with arguments in the current stack frame, copying its
+    /// return value into `return_into`
+    fn call_defined(
+        &mut self,
+        function: FunctionID,
+        args: &[AddressedTypeRef],
+        return_into: &Option<AddressedTypeRef>,
+        local_variables: &LocalVariableTable,
+    ) -> Result<(), Halt> {
+        // Lay out the callee's frame below everything in use, as a native call would
+        let args_size: usize = args.iter().map(|a| self.size(a.type_ref())).sum();
+        let return_size = return_into
+            .as_ref()
+            .map(|r| self.size(r.type_ref()))
+            .unwrap_or_default();
+        let bottom = self.address(LocalAddress(-(local_variables.stack_size().0 as isize)));
+        let callee_rbp = bottom.wrapping_sub((args_size + return_size + 16) as u64);
+
+        let mut offset = 16;
+        for arg in args {
+            let size = self.size(arg.type_ref());
+            self.copy(
+                self.address(*arg.local_address()),
+                callee_rbp.wrapping_add(offset),
+                size,
+            )?;
+            offset += size as u64;
+        }
+
+        self.execute_function(function, callee_rbp)?;
+
+        if let Some(return_into) = return_into {
+            self.copy(
+                callee_rbp.wrapping_add(offset),
+                self.address(*return_into.local_address()),
+                return_size,
+            )?;
+        }
+        Ok(())
+    }
+}
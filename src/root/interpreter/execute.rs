@@ -0,0 +1,286 @@
+use std::slice;
+
+#[cfg(debug_assertions)]
+use color_print::cprintln;
+
+use crate::root::builtin::types::int::IntType;
+use crate::root::compiler::local_variable_table::{Binding, LocalVariableTable};
+use crate::root::interpreter::{Halt, Interpreter};
+use crate::root::parser::parse_function::parse_if::IfToken;
+use crate::root::parser::parse_function::parse_initialisation::InitialisationToken;
+use crate::root::parser::parse_function::parse_line::LineTokens;
+use crate::root::parser::parse_function::parse_while::WhileToken;
+use crate::root::parser::parse_function::FunctionToken;
+use crate::root::parser::path_storage::FileID;
+use crate::root::shared::common::{AddressedTypeRef, FunctionID, LocalAddress};
+
+/// How execution continues after running lines
+pub(super) enum Flow {
+    /// The lines finished
+    Continue,
+    /// A `break` was reached
+    Break,
+    /// A `return` was reached, with the return value written to the return variable
+    Return,
+}
+
+/// Space a stack frame must have below its base for a function to be called
+const FRAME_MARGIN: u64 = 64 * 1024;
+
+impl Interpreter<'_> {
+    /// Scopes the global table's namespace to a file, if it isn't already
+    pub(super) fn scope_to(&mut self, file: FileID) {
+        if self.file == Some(file) {
+            return;
+        }
+        self.file = Some(file);
+        let scope = self
+            .global_tracker
+            .path_storage()
+            .get_file(file)
+            .scope()
+            .clone();
+        self.global_table.scope_namespace(file, scope);
+    }
+
+    /// Runs a function defined in code with its frame based at `rbp`. Arguments must already be
+    /// in place above the base, and the return value is written after them
+    pub(super) fn execute_function(&mut self, function: FunctionID, rbp: u64) -> Result<(), Halt> {
+        // The simulated stack overflows as a native one would, which normally happens well before
+        // the interpreter's own stack runs out
+        if !self.memory.in_stack(rbp.wrapping_sub(FRAME_MARGIN)) || self.host_stack_exhausted() {
+            return Err(self.panic("stack overflow"));
+        }
+
+        let functions = self.functions;
+        let token = &functions[&function];
+
+        let (caller_rbp, caller, caller_file) = (self.rbp, self.function, self.file);
+        self.rbp = rbp;
+        self.function = function;
+        self.global_tracker.reset_functions();
+        self.scope_to(token.location().file_id().unwrap());
+
+        let result = self.execute_body(function, token);
+
+        // Restore the caller's state even if execution stopped, so the interpreter can be reused
+        self.rbp = caller_rbp;
+        self.function = caller;
+        if let Some(file) = caller_file {
+            self.scope_to(file);
+        }
+        result
+    }
+
+    /// Runs the lines of a function in the current frame
+    fn execute_body(&mut self, function: FunctionID, token: &FunctionToken) -> Result<(), Halt> {
+        let (mut local_variables, return_variable) = self.frame_variables(function, token)?;
+        self.execute_lines(token.lines(), &return_variable, &mut local_variables)?;
+        Ok(())
+    }
+
+    /// Returns the variables of a function's frame - its parameters, which are above the base of
+    /// the frame, and the variable its return value is written to, which follows them
+    fn frame_variables(
+        &mut self,
+        function: FunctionID,
+        token: &FunctionToken,
+    ) -> Result<(LocalVariableTable, Option<AddressedTypeRef>), Halt> {
+        let mut local_variables = LocalVariableTable::new();
+
+        let mut address = LocalAddress(16);
+        for (name, t) in token.parameters() {
+            let t = self.global_table.resolve_to_type_ref(t, None)?;
+            let size = self.global_table.get_size(&t);
+            local_variables.add_existing(
+                Binding::new(name.name().clone(), false, name.location().clone()),
+                AddressedTypeRef::new(address, t),
+            );
+            address += LocalAddress(size.0 as isize);
+        }
+
+        // `main` returns an integer exit code
+        let return_type = if function.is_main() {
+            Some(IntType::id().immediate_single())
+        } else {
+            match token.return_type() {
+                Some(t) => Some(self.global_table.resolve_to_type_ref(t, None)?),
+                None => None,
+            }
+        };
+        let return_variable = return_type.map(|t| AddressedTypeRef::new(address, t));
+
+        Ok((local_variables, return_variable))
+    }
+
+    /// Runs a line typed into a REPL in the session frame, which is based at the top of the stack.
+    /// Returns the value of a bare expression, if it has one. `return` ends the session, as
+    /// returning from `main` would
+    pub fn execute_statement(
+        &mut self,
+        line: &LineTokens,
+        file: FileID,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<Option<AddressedTypeRef>, Halt> {
+        self.scope_to(file);
+        match line {
+            LineTokens::NoOp(et) => self.evaluate_reference(et, local_variables),
+            LineTokens::Return(rt) => {
+                let code = match rt.return_value() {
+                    Some(value) => {
+                        let code = self.global_table.add_local_variable_unnamed(
+                            IntType::id().immediate_single(),
+                            local_variables,
+                        );
+                        self.evaluate_into(value, code.clone(), local_variables)?;
+                        self.int(*code.local_address())?
+                    }
+                    None => 0,
+                };
+                Err(Halt::Exit(code))
+            }
+            _ => {
+                self.execute_lines_in_scope(slice::from_ref(line), &None, local_variables)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Runs lines in a new variable scope
+    pub(super) fn execute_lines(
+        &mut self,
+        lines: &[LineTokens],
+        return_variable: &Option<AddressedTypeRef>,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<Flow, Halt> {
+        local_variables.enter_scope();
+        let flow = self.execute_lines_in_scope(lines, return_variable, local_variables);
+        local_variables.leave_scope();
+        flow
+    }
+
+    /// Runs lines in the current variable scope, leaving any variables they define in scope
+    ///
+    /// Lines other than `break` and `return` are run by their own methods, keeping this frame
+    /// (which is on the host stack for every nested call being interpreted) small
+    pub(super) fn execute_lines_in_scope(
+        &mut self,
+        lines: &[LineTokens],
+        return_variable: &Option<AddressedTypeRef>,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<Flow, Halt> {
+        for line in lines {
+            let flow = match line {
+                LineTokens::Initialisation(it) => {
+                    self.initialise(it, local_variables)?;
+                    Flow::Continue
+                }
+                LineTokens::If(if_token) => {
+                    self.execute_if(if_token, return_variable, local_variables)?
+                }
+                LineTokens::While(while_token) => {
+                    self.execute_while(while_token, return_variable, local_variables)?
+                }
+                LineTokens::Return(rt) => {
+                    if let (Some(value), Some(return_variable)) =
+                        (rt.return_value(), return_variable)
+                    {
+                        self.evaluate_into(value, return_variable.clone(), local_variables)?;
+                    }
+                    Flow::Return
+                }
+                LineTokens::Break(_) => Flow::Break,
+                LineTokens::NoOp(et) => {
+                    self.evaluate_reference(et, local_variables)?;
+                    Flow::Continue
+                }
+                // Debug tool
+                #[cfg(debug_assertions)]
+                LineTokens::Marker(value) => {
+                    cprintln!("\n<s><m!>At Interpreter Marker:</> '{}'", value.value());
+                    Flow::Continue
+                }
+            };
+
+            if !matches!(flow, Flow::Continue) {
+                return Ok(flow);
+            }
+        }
+        Ok(Flow::Continue)
+    }
+
+    /// Adds the variable a `let` line declares, evaluating its value into it if it has one
+    fn initialise(
+        &mut self,
+        it: &InitialisationToken,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<(), Halt> {
+        let address = self.global_table.add_local_variable_named(
+            Binding::new(
+                it.name().name().clone(),
+                *it.mutable(),
+                it.name().location().clone(),
+            ),
+            it.type_name(),
+            local_variables,
+        )?;
+        if let Some(value) = it.value() {
+            self.evaluate_into(value, address, local_variables)?;
+        }
+        Ok(())
+    }
+
+    /// Runs the contents of the first branch of an if statement whose condition is true
+    fn execute_if(
+        &mut self,
+        if_token: &IfToken,
+        return_variable: &Option<AddressedTypeRef>,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<Flow, Halt> {
+        let branches = [(if_token.if_condition(), if_token.if_contents())]
+            .into_iter()
+            .chain(
+                if_token
+                    .elif_condition_contents()
+                    .iter()
+                    .map(|(c, l)| (c, l)),
+            );
+
+        let mut taken = None;
+        for (condition, contents) in branches {
+            if self.condition(condition, local_variables)? {
+                taken = Some(contents);
+                break;
+            }
+        }
+        match taken.or(if_token.else_contents().as_ref()) {
+            Some(contents) => self.execute_lines(contents, return_variable, local_variables),
+            None => Ok(Flow::Continue),
+        }
+    }
+
+    /// Runs the contents of a while loop until its condition is false, it breaks or it returns
+    fn execute_while(
+        &mut self,
+        while_token: &WhileToken,
+        return_variable: &Option<AddressedTypeRef>,
+        local_variables: &mut LocalVariableTable,
+    ) -> Result<Flow, Halt> {
+        loop {
+            // Evaluate the condition in its own scope so each iteration reuses the same stack
+            // space
+            local_variables.enter_scope();
+            let condition = self.condition(while_token.condition(), local_variables);
+            local_variables.leave_scope();
+            if !condition? {
+                return Ok(Flow::Continue);
+            }
+
+            match self.execute_lines(while_token.contents(), return_variable, local_variables)? {
+                Flow::Continue => {}
+                Flow::Break => return Ok(Flow::Continue),
+                Flow::Return => return Ok(Flow::Return),
+            }
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+
+use crate::root::parser::location::Location;
+
+/// Size of the simulated stack, matching the default Linux stack size
+const STACK_SIZE: usize = 8 * 1024 * 1024;
+/// Lowest address of the simulated stack
+const STACK_START: u64 = 0x7000_0000_0000;
+/// Address of the first heap allocation
+const HEAP_START: u64 = 0x1000_0000;
+/// Space left between heap allocations so that overruns don't reach the next allocation
+const HEAP_GAP: u64 = 16;
+/// Largest allocation that succeeds - larger allocations return null, as `malloc` would
+const MAX_ALLOCATION: u64 = 1 << 32;
+
+/// Why a memory access failed
+pub enum MemoryError {
+    /// The address is null or just past null e.g. an attribute of a null reference
+    Null,
+    /// The address is in a heap allocation that has been freed
+    Freed {
+        allocated: Location,
+        freed: Location,
+    },
+    /// The address isn't on the stack or in a heap allocation
+    Unallocated,
+}
+
+/// Why freeing memory failed
+pub enum FreeError {
+    /// The allocation has already been freed
+    DoubleFree {
+        allocated: Location,
+        freed: Location,
+    },
+    /// The address isn't the start of a heap allocation
+    NotHeap,
+}
+
+/// A heap allocation. Allocations are kept after being freed so that use-after-free and double
+/// frees can be reported
+struct Allocation {
+    bytes: Vec<u8>,
+    site: Location,
+    freed: Option<Location>,
+}
+
+/// Simulated memory of an interpreted program, made up of a stack and a heap addressed like native
+/// memory. Heap addresses are never reused
+pub struct Memory {
+    stack: Vec<u8>,
+    heap: BTreeMap<u64, Allocation>,
+    next_allocation: u64,
+}
+
+impl Default for Memory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Memory {
+    pub fn new() -> Memory {
+        Memory {
+            stack: vec![0; STACK_SIZE],
+            heap: BTreeMap::new(),
+            next_allocation: HEAP_START,
+        }
+    }
+
+    /// Address just past the top of the stack. The stack grows downwards
+    pub fn stack_end(&self) -> u64 {
+        STACK_START + STACK_SIZE as u64
+    }
+
+    /// Returns whether a stack frame can extend down to `address`
+    pub fn in_stack(&self, address: u64) -> bool {
+        address >= STACK_START
+    }
+
+    /// Finds the bytes `size` bytes from `address` are stored in, returning the backing buffer and
+    /// the offset of `address` within it
+    fn locate(&self, address: u64, size: usize) -> Result<(&[u8], usize), MemoryError> {
+        let end = address
+            .checked_add(size as u64)
+            .ok_or(MemoryError::Unallocated)?;
+        if address < HEAP_START {
+            return Err(MemoryError::Null);
+        }
+        if address >= STACK_START {
+            if end > self.stack_end() {
+                return Err(MemoryError::Unallocated);
+            }
+            return Ok((&self.stack, (address - STACK_START) as usize));
+        }
+
+        let Some((start, allocation)) = self.heap.range(..=address).next_back() else {
+            return Err(MemoryError::Unallocated);
+        };
+        if end > start + allocation.bytes.len() as u64 {
+            return Err(MemoryError::Unallocated);
+        }
+        if let Some(freed) = &allocation.freed {
+            return Err(MemoryError::Freed {
+                allocated: allocation.site.clone(),
+                freed: freed.clone(),
+            });
+        }
+        Ok((&allocation.bytes, (address - start) as usize))
+    }
+
+    /// Reads `size` bytes from `address`
+    pub fn read(&self, address: u64, size: usize) -> Result<&[u8], MemoryError> {
+        let (bytes, offset) = self.locate(address, size)?;
+        Ok(&bytes[offset..offset + size])
+    }
+
+    /// Writes `bytes` to `address`
+    pub fn write(&mut self, address: u64, bytes: &[u8]) -> Result<(), MemoryError> {
+        // Check the write is valid without holding a mutable borrow
+        self.locate(address, bytes.len())?;
+
+        let target = if address >= STACK_START {
+            let offset = (address - STACK_START) as usize;
+            &mut self.stack[offset..offset + bytes.len()]
+        } else {
+            let (start, allocation) = self.heap.range_mut(..=address).next_back().unwrap();
+            let offset = (address - start) as usize;
+            &mut allocation.bytes[offset..offset + bytes.len()]
+        };
+        target.copy_from_slice(bytes);
+        Ok(())
+    }
+
+    /// Copies `size` bytes from `from` to `to`
+    pub fn copy(&mut self, from: u64, to: u64, size: usize) -> Result<(), MemoryError> {
+        let bytes = self.read(from, size)?.to_vec();
+        self.write(to, &bytes)
+    }
+
+    /// Allocates `size` bytes on the heap, recording where the allocation was made. Returns null
+    /// if the allocation is too large
+    pub fn allocate(&mut self, size: u64, site: &Location) -> u64 {
+        if size > MAX_ALLOCATION {
+            return 0;
+        }
+        let address = self.next_allocation;
+        // Keep allocations 16 byte aligned, as `malloc` does
+        self.next_allocation += (size + HEAP_GAP).next_multiple_of(16);
+        self.heap.insert(
+            address,
+            Allocation {
+                bytes: vec![0; size as usize],
+                site: site.clone(),
+                freed: None,
+            },
+        );
+        address
+    }
+
+    /// Resizes the heap allocation at `address` to `size` bytes, moving it to a new address. A null
+    /// `address` allocates, as with `realloc`
+    pub fn reallocate(
+        &mut self,
+        address: u64,
+        size: u64,
+        site: &Location,
+    ) -> Result<u64, FreeError> {
+        if address == 0 {
+            return Ok(self.allocate(size, site));
+        }
+        let Some(allocation) = self.heap.get(&address).filter(|a| a.freed.is_none()) else {
+            // Report the problem as freeing would
+            return Err(self.free(address, site).unwrap_err());
+        };
+        let bytes = allocation.bytes.clone();
+
+        let new = self.allocate(size, site);
+        if new == 0 {
+            return Ok(0);
+        }
+        let kept = bytes.len().min(size as usize);
+        self.write(new, &bytes[..kept]).ok();
+        self.free(address, site)?;
+        Ok(new)
+    }
+
+    /// Frees the heap allocation at `address`, recording where it was freed. Freeing null does
+    /// nothing
+    pub fn free(&mut self, address: u64, site: &Location) -> Result<(), FreeError> {
+        if address == 0 {
+            return Ok(());
+        }
+        let Some(allocation) = self.heap.get_mut(&address) else {
+            return Err(FreeError::NotHeap);
+        };
+        if let Some(freed) = &allocation.freed {
+            return Err(FreeError::DoubleFree {
+                allocated: allocation.site.clone(),
+                freed: freed.clone(),
+            });
+        }
+        allocation.freed = Some(site.clone());
+        Ok(())
+    }
+
+    /// Returns the size and allocation site of every allocation that hasn't been freed, most
+    /// recent first
+    pub fn leaks(&self) -> Vec<(usize, &Location)> {
+        self.heap
+            .values()
+            .rev()
+            .filter(|a| a.freed.is_none())
+            .map(|a| (a.bytes.len(), &a.site))
+            .collect()
+    }
+}
@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::io::{BufReader, Write};
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use serde_json::{json, Value};
+
+use crate::root::errors::WErr;
+use crate::root::lsp::analysis::{analyse, Analysis, CompletionKind, Diagnostic};
+use crate::root::lsp::protocol::{
+    identifier_end, offset_to_position, path_to_uri, position_to_offset, read_message, uri_to_path,
+    write_message,
+};
+use crate::root::parser::location::Location;
+use crate::root::utils::strip_ansi;
+use crate::root::Args;
+
+pub mod analysis;
+pub mod protocol;
+
+/// JSON-RPC error code for an unknown method
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// A file open in the editor
+struct Document {
+    /// Path in the form used by `PathStorage` e.g. `std/linked_list.why`
+    path: String,
+    text: String,
+    /// Result of the last analysis that got past name resolution. Kept while the file doesn't parse
+    /// so that completion works while typing
+    analysis: Option<Analysis>,
+}
+
+/// Language server state
+struct Server<'a> {
+    args: &'a Args,
+    /// Directory paths are made relative to, so that imports and the prelude are found as when
+    /// building
+    root: PathBuf,
+    documents: HashMap<String, Document>,
+    output: Box<dyn Write>,
+    shutdown: bool,
+}
+
+/// Runs a Language Server Protocol server over stdin and stdout. Opened and changed files are
+/// checked, publishing errors as diagnostics, and go-to-definition, hover and completion of methods
+/// are offered
+pub fn run_server(args: &Args) -> Result<(), String> {
+    let output = protocol_output().map_err(|e| format!("Failed to set up output: {e}"))?;
+    let mut server = Server {
+        args,
+        root: env::current_dir().map_err(|e| e.to_string())?,
+        documents: HashMap::new(),
+        output,
+        shutdown: false,
+    };
+
+    let mut input = BufReader::new(io::stdin().lock());
+    while let Some(message) =
+        read_message(&mut input).map_err(|e| format!("Failed to read message: {e}"))?
+    {
+        server
+            .handle(&message)
+            .map_err(|e| format!("Failed to write message: {e}"))?;
+    }
+    Ok(())
+}
+
+/// Returns a writer for protocol messages. The compiler prints progress to stdout, so on Unix
+/// stdout is redirected to stderr and messages are written to the original stdout
+fn protocol_output() -> io::Result<Box<dyn Write>> {
+    #[cfg(unix)]
+    {
+        use std::fs::File;
+        use std::os::fd::FromRawFd;
+
+        // SAFETY: `dup` returns a new descriptor owned by the `File`, and redirecting stdout doesn't
+        // affect it
+        unsafe {
+            let protocol = libc::dup(libc::STDOUT_FILENO);
+            if protocol < 0 || libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Box::new(File::from_raw_fd(protocol)))
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        Ok(Box::new(io::stdout()))
+    }
+}
+
+impl Server<'_> {
+    /// Handles a request or notification
+    fn handle(&mut self, message: &Value) -> io::Result<()> {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        let id = message.get("id");
+
+        let result = match method {
+            "initialize" => json!({
+                "capabilities": {
+                    // Full text is sent on every change
+                    "textDocumentSync": 1,
+                    "definitionProvider": true,
+                    "hoverProvider": true,
+                    "completionProvider": { "triggerCharacters": [".", ":"] },
+                },
+                "serverInfo": { "name": "whython", "version": env!("CARGO_PKG_VERSION") },
+            }),
+            "shutdown" => {
+                self.shutdown = true;
+                Value::Null
+            }
+            "exit" => process::exit(if self.shutdown { 0 } else { 1 }),
+            "textDocument/didOpen" => {
+                let document = &params["textDocument"];
+                let text = document["text"].as_str().unwrap_or_default().to_string();
+                return self.update(document["uri"].as_str().unwrap_or_default(), text);
+            }
+            "textDocument/didChange" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let Some(text) = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                else {
+                    return Ok(());
+                };
+                return self.update(uri, text.to_string());
+            }
+            "textDocument/didClose" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                self.documents.remove(uri);
+                return self.notify(
+                    "textDocument/publishDiagnostics",
+                    json!({ "uri": uri, "diagnostics": [] }),
+                );
+            }
+            "textDocument/definition" => self.definition(params),
+            "textDocument/hover" => self.hover(params),
+            "textDocument/completion" => self.completion(params),
+            _ => {
+                // Unknown notifications are ignored
+                let Some(id) = id else {
+                    return Ok(());
+                };
+                return write_message(
+                    &mut self.output,
+                    &json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": METHOD_NOT_FOUND, "message": format!("Unknown method '{method}'") },
+                    }),
+                );
+            }
+        };
+
+        match id {
+            Some(id) => write_message(
+                &mut self.output,
+                &json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Sends a notification
+    fn notify(&mut self, method: &str, params: Value) -> io::Result<()> {
+        write_message(
+            &mut self.output,
+            &json!({ "jsonrpc": "2.0", "method": method, "params": params }),
+        )
+    }
+
+    /// Converts a URI to a path in the form used by `PathStorage` - relative to the root if it is
+    /// inside it
+    fn uri_to_storage_path(&self, uri: &str) -> Option<String> {
+        let path = uri_to_path(uri)?;
+        let path = path.strip_prefix(&self.root).unwrap_or(&path);
+        Some(path.to_string_lossy().to_string())
+    }
+
+    /// Stores the new text of a document, checks it and publishes the errors found
+    fn update(&mut self, uri: &str, text: String) -> io::Result<()> {
+        let Some(path) = self.uri_to_storage_path(uri) else {
+            return Ok(());
+        };
+        let document = self
+            .documents
+            .entry(uri.to_string())
+            .or_insert_with(|| Document {
+                path: path.clone(),
+                text: String::new(),
+                analysis: None,
+            });
+        document.text = text;
+
+        if !path.ends_with(".why") {
+            return Ok(());
+        }
+
+        let contents = self
+            .documents
+            .values()
+            .map(|d| (d.path.clone(), d.text.clone()))
+            .collect();
+        let args = self.args;
+        let (errors, analysis) =
+            panic::catch_unwind(AssertUnwindSafe(|| analyse(args, &path, &contents)))
+                .unwrap_or_else(|_| {
+                    let error =
+                        WErr::locationless("Internal compiler error while checking this file");
+                    (
+                        vec![Diagnostic {
+                            error,
+                            path: None,
+                            note_paths: Vec::new(),
+                        }],
+                        None,
+                    )
+                });
+
+        let diagnostics = errors
+            .iter()
+            .map(|d| self.diagnostic(d, &path))
+            .collect::<Vec<_>>();
+
+        if let Some(analysis) = analysis {
+            self.documents.get_mut(uri).unwrap().analysis = Some(analysis);
+        }
+
+        self.notify(
+            "textDocument/publishDiagnostics",
+            json!({ "uri": uri, "diagnostics": diagnostics }),
+        )
+    }
+
+    /// Converts an error found while checking the file at `path` to an LSP diagnostic
+    fn diagnostic(&self, diagnostic: &Diagnostic, path: &str) -> Value {
+        let error = diagnostic.error.summarised();
+        let mut message = strip_ansi(error.error());
+        let mut range = json!({
+            "start": { "line": 0, "character": 0 },
+            "end": { "line": 0, "character": 0 },
+        });
+
+        if let Some(location) = error.location() {
+            match &diagnostic.path {
+                Some(error_path) if error_path == path => {
+                    if let Some(text) = self.file_text(path) {
+                        range = location_range(location, &text);
+                    }
+                }
+                // Errors in other files are shown at the start of the file
+                Some(error_path) => {
+                    message = format!(
+                        "In {error_path}:{}: {message}",
+                        location.line().unwrap_or(1)
+                    );
+                }
+                None => {}
+            }
+        }
+
+        let related = error
+            .notes()
+            .iter()
+            .zip(&diagnostic.note_paths)
+            .filter_map(|((note, location), note_path)| {
+                Some(json!({
+                    "location": self.location(location, note_path.as_ref()?)?,
+                    "message": strip_ansi(note),
+                }))
+            })
+            .collect::<Vec<_>>();
+
+        json!({
+            "range": range,
+            "severity": 1,
+            "source": "whython",
+            "message": message,
+            "relatedInformation": related,
+        })
+    }
+
+    /// Returns the text of a file, preferring the editor's version if it is open
+    fn file_text(&self, path: &str) -> Option<String> {
+        self.documents
+            .values()
+            .find(|d| d.path == path)
+            .map(|d| d.text.clone())
+            .or_else(|| fs::read_to_string(self.root.join(path)).ok())
+    }
+
+    /// Converts a location in the file at `path` to an LSP location
+    fn location(&self, location: &Location, path: &str) -> Option<Value> {
+        let text = self.file_text(path)?;
+        Some(json!({
+            "uri": path_to_uri(&self.root.join(Path::new(path))),
+            "range": location_range(location, &text),
+        }))
+    }
+
+    /// Returns the document and byte offset a request refers to
+    fn request_position(&self, params: &Value) -> Option<(&Document, usize)> {
+        let document = self
+            .documents
+            .get(params["textDocument"]["uri"].as_str()?)?;
+        let offset = position_to_offset(&document.text, &params["position"]);
+        Some((document, offset))
+    }
+
+    /// Finds where the name at a position is defined
+    fn definition(&self, params: &Value) -> Value {
+        let Some((document, offset)) = self.request_position(params) else {
+            return Value::Null;
+        };
+        let Some(analysis) = &document.analysis else {
+            return Value::Null;
+        };
+        analysis
+            .definition(offset)
+            .and_then(|location| {
+                let path = analysis
+                    .path_storage()
+                    .reconstruct_file(location.file_id()?);
+                self.location(location, &path)
+            })
+            .unwrap_or(Value::Null)
+    }
+
+    /// Describes the name at a position
+    fn hover(&self, params: &Value) -> Value {
+        let Some((document, offset)) = self.request_position(params) else {
+            return Value::Null;
+        };
+        let Some(hover) = document.analysis.as_ref().and_then(|a| a.hover(offset)) else {
+            return Value::Null;
+        };
+        json!({ "contents": { "kind": "markdown", "value": hover } })
+    }
+
+    /// Suggests methods and fields after `.` or `::`
+    fn completion(&self, params: &Value) -> Value {
+        let Some((document, offset)) = self.request_position(params) else {
+            return Value::Null;
+        };
+        let Some(analysis) = &document.analysis else {
+            return Value::Null;
+        };
+        let items = analysis
+            .completions(&document.text, offset)
+            .into_iter()
+            .map(|c| {
+                json!({
+                    "label": c.label,
+                    "kind": match c.kind {
+                        CompletionKind::Method => 2,
+                        CompletionKind::Function => 3,
+                        CompletionKind::Field => 5,
+                    },
+                    "detail": c.detail,
+                })
+            })
+            .collect::<Vec<_>>();
+        json!(items)
+    }
+}
+
+/// Converts a location to an LSP range covering the name starting there
+fn location_range(location: &Location, text: &str) -> Value {
+    let start = location.offset().unwrap_or(0);
+    json!({
+        "start": offset_to_position(text, start),
+        "end": offset_to_position(text, identifier_end(text, start)),
+    })
+}
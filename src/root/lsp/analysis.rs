@@ -0,0 +1,455 @@
+use std::collections::HashMap;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+
+use itertools::Itertools;
+
+use crate::root::compiler::compile_function::compile_function;
+use crate::root::compiler::global_tracker::GlobalTracker;
+use crate::root::errors::parser_errors::ParseError;
+use crate::root::errors::WErr;
+use crate::root::name_resolver::name_resolvers::{GlobalTable, NameTarget, NameUse};
+use crate::root::name_resolver::resolve::resolve;
+use crate::root::name_resolver::resolve_function_signatures::FunctionSignature;
+use crate::root::parser::location::Location;
+use crate::root::parser::parse::parse;
+use crate::root::parser::parse_parameters::SelfType;
+use crate::root::parser::path_storage::{FileID, PathStorage};
+use crate::root::shared::common::{FunctionID, TypeID, TypeRef};
+use crate::root::{Args, DEFAULT_PRELUDE};
+
+/// Name and doc comments of a user function
+struct FunctionInfo {
+    name: String,
+    docs: Option<String>,
+}
+
+/// Kind of a completion item
+pub enum CompletionKind {
+    Method,
+    Function,
+    Field,
+}
+
+/// A suggested completion
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    pub detail: String,
+}
+
+/// An error found while checking a file, with the paths of the files the locations of its summary
+/// are in
+pub struct Diagnostic {
+    pub error: WErr,
+    pub path: Option<String>,
+    pub note_paths: Vec<Option<String>>,
+}
+
+impl Diagnostic {
+    fn new(error: WErr, path_storage: &PathStorage) -> Diagnostic {
+        let path_of = |location: &Location| {
+            location
+                .file_id()
+                .map(|file| path_storage.reconstruct_file(file))
+        };
+        // Paths are of the locations shown
+        let summary = error.summarised();
+        Diagnostic {
+            path: summary.location().as_ref().and_then(path_of),
+            note_paths: summary.notes().iter().map(|(_, l)| path_of(l)).collect(),
+            error,
+        }
+    }
+}
+
+/// What is known about a file after it has been resolved and its functions compiled
+pub struct Analysis {
+    global_table: GlobalTable,
+    path_storage: PathStorage,
+    /// Names used in the file, including where functions, types and variables are defined
+    name_uses: Vec<NameUse>,
+    functions: HashMap<FunctionID, FunctionInfo>,
+}
+
+/// Parses and resolves `path`, then compiles every function in it to find type errors. Files open in
+/// the editor are read from `contents` rather than disk.
+///
+/// Returns every error found and, if the file could be resolved, what is known about it
+pub fn analyse(
+    args: &Args,
+    path: &str,
+    contents: &HashMap<String, String>,
+) -> (Vec<Diagnostic>, Option<Analysis>) {
+    let mut path_storage = match PathStorage::new(path, &args.search_paths()) {
+        Ok(path_storage) => path_storage,
+        Err(e) => {
+            return (
+                vec![Diagnostic {
+                    error: e,
+                    path: None,
+                    note_paths: Vec::new(),
+                }],
+                None,
+            )
+        }
+    };
+    for (path, text) in contents {
+        path_storage.set_contents(path.clone(), text.clone());
+    }
+    if !args.no_prelude {
+        let prelude = args.prelude.as_deref().unwrap_or(DEFAULT_PRELUDE);
+        if let Err(tried) = path_storage.set_prelude(prelude) {
+            // Only error if the prelude was explicitly specified
+            if args.prelude.is_some() {
+                let error = WErr::locationless(ParseError::FailedToOpenFile(tried.join(", ")));
+                return (vec![Diagnostic::new(error, &path_storage)], None);
+            }
+        }
+    }
+
+    let toplevel_tokens = match parse(&mut path_storage) {
+        Ok(toplevel_tokens) => toplevel_tokens,
+        Err(e) => return (vec![Diagnostic::new(e, &path_storage)], None),
+    };
+    let (mut global_table, unprocessed_functions) =
+        match resolve(toplevel_tokens, &path_storage, false) {
+            Ok(resolved) => resolved,
+            Err(e) => return (vec![Diagnostic::new(e, &path_storage)], None),
+        };
+
+    let functions: HashMap<_, _> = unprocessed_functions
+        .iter()
+        .map(|(id, ft)| {
+            (
+                *id,
+                FunctionInfo {
+                    name: ft.name().name().clone(),
+                    docs: ft.docs().clone(),
+                },
+            )
+        })
+        .collect();
+
+    // Every function in the file is compiled, not just those reachable from main, so that all type
+    // errors are found
+    global_table.record_name_uses();
+    let mut errors = Vec::new();
+    let mut global_tracker = GlobalTracker::new(
+        &path_storage,
+        args.runtime_checks(),
+    );
+    for (id, function) in unprocessed_functions
+        .into_iter()
+        .filter(|(_, ft)| ft.location().file_id() == Some(FileID::MAIN_FILE))
+        .sorted_by_key(|(id, _)| id.0)
+    {
+        global_tracker.reset_functions();
+        global_table.scope_namespace(
+            FileID::MAIN_FILE,
+            path_storage.get_file(FileID::MAIN_FILE).scope().clone(),
+        );
+
+        let location = function.name().location().clone();
+        // An unfinished part of the compiler shouldn't stop the rest of the file being checked
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            compile_function(id, function, &mut global_table, &mut global_tracker)
+        }));
+        match result {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => errors.push(e),
+            Err(_) => errors.push(WErr::n(
+                "Internal compiler error while checking this function",
+                location,
+            )),
+        }
+    }
+    drop(global_tracker);
+    let errors = errors
+        .into_iter()
+        .map(|e| Diagnostic::new(e, &path_storage))
+        .collect();
+
+    let mut name_uses = global_table.take_name_uses();
+    // Definitions can be hovered like uses
+    for type_id in global_table.get_type_ids() {
+        if let Some(location) = global_table.get_type_location(type_id) {
+            name_uses.push(NameUse::new(
+                location.clone(),
+                global_table.get_type(type_id).name().to_string(),
+                NameTarget::Type(type_id),
+            ));
+        }
+    }
+    for (id, info) in &functions {
+        if let Some(location) = global_table.get_function_location(*id) {
+            name_uses.push(NameUse::new(
+                location.clone(),
+                info.name.clone(),
+                NameTarget::Function(*id),
+            ));
+        }
+    }
+    name_uses.retain(|u| u.location().file_id() == Some(FileID::MAIN_FILE));
+
+    (
+        errors,
+        Some(Analysis {
+            global_table,
+            path_storage,
+            name_uses,
+            functions,
+        }),
+    )
+}
+
+impl Analysis {
+    pub fn path_storage(&self) -> &PathStorage {
+        &self.path_storage
+    }
+
+    /// Finds the name at a byte offset in the analysed file
+    fn name_use_at(&self, offset: usize) -> Option<&NameUse> {
+        self.name_uses.iter().find(|u| {
+            u.location()
+                .offset()
+                .is_some_and(|start| start <= offset && offset < start + u.name().len())
+        })
+    }
+
+    /// Returns where the name at a byte offset is defined, if it is defined in a file
+    pub fn definition(&self, offset: usize) -> Option<&Location> {
+        match self.name_use_at(offset)?.target() {
+            NameTarget::Function(f) => self.global_table.get_function_location(*f),
+            NameTarget::Type(t) => self.global_table.get_type_location(*t),
+            NameTarget::Variable { definition, .. } => Some(definition),
+        }
+    }
+
+    /// Describes the name at a byte offset in Markdown
+    pub fn hover(&self, offset: usize) -> Option<String> {
+        let name_use = self.name_use_at(offset)?;
+        let (code, docs) = match name_use.target() {
+            NameTarget::Function(f) => (
+                self.signature(*f),
+                self.functions.get(f).and_then(|f| f.docs.as_ref()),
+            ),
+            NameTarget::Type(t) => (
+                format!(
+                    "{} // {} bytes",
+                    self.global_table.get_type(*t).name(),
+                    self.global_table.get_type(*t).size().0
+                ),
+                self.global_table.get_type_docs(*t),
+            ),
+            NameTarget::Variable { type_ref, .. } => (
+                format!(
+                    "{}: {}",
+                    name_use.name(),
+                    self.global_table.get_type_name(type_ref)
+                ),
+                None,
+            ),
+        };
+
+        let mut hover = format!("```whython\n{code}\n```");
+        if let Some(docs) = docs {
+            hover += "\n\n";
+            hover += docs;
+        }
+        Some(hover)
+    }
+
+    /// Formats a function's signature
+    fn signature(&self, function_id: FunctionID) -> String {
+        let signature = self.global_table.get_function_signature(function_id);
+        let name = self
+            .functions
+            .get(&function_id)
+            .map(|f| f.name.clone())
+            .or_else(|| self.method_name(function_id))
+            .unwrap_or_else(|| "?".to_string());
+        format_signature(&name, signature, &self.global_table)
+    }
+
+    /// Finds the name of a builtin function
+    fn method_name(&self, function_id: FunctionID) -> Option<String> {
+        self.global_table
+            .get_builtin_functions()
+            .iter()
+            .chain(
+                self.global_table
+                    .get_type_ids()
+                    .into_iter()
+                    .filter_map(|t| self.global_table.get_impl_functions(t))
+                    .flatten(),
+            )
+            .find(|(_, id)| **id == function_id)
+            .map(|(name, _)| name.clone())
+    }
+
+    /// Suggests methods (and fields) of the value accessed by the text before `offset`, which must
+    /// end with `a.b.` or `Type::` followed by the start of a name
+    pub fn completions(&self, text: &str, offset: usize) -> Vec<Completion> {
+        let before = &text[..offset.min(text.len())];
+        // Remove the part of the name already typed
+        let before = before.trim_end_matches(|c: char| c.is_alphanumeric() || c == '_');
+
+        if let Some(before) = before.strip_suffix("::") {
+            let Some(type_id) = self.type_by_name(trailing_identifier(before)) else {
+                return Vec::new();
+            };
+            return self.methods(type_id, false);
+        }
+
+        let Some(before) = before.strip_suffix('.') else {
+            return Vec::new();
+        };
+        let Some(type_ref) = self.chain_type(before, offset) else {
+            return Vec::new();
+        };
+
+        let mut completions = self.methods(*type_ref.type_id(), true);
+        if let Ok(attributes) = self
+            .global_table
+            .get_type(*type_ref.type_id())
+            .get_attributes(&Location::builtin())
+        {
+            completions.extend(attributes.iter().map(|(_, name, t)| Completion {
+                label: name.name().clone(),
+                kind: CompletionKind::Field,
+                detail: self.global_table.get_type_name(t),
+            }));
+        }
+        completions
+    }
+
+    /// Finds the type of a chain of names e.g. `self.first.next` ending at the end of `text`
+    fn chain_type(&self, text: &str, offset: usize) -> Option<TypeRef> {
+        let mut sections = Vec::new();
+        let mut rest = text;
+        loop {
+            let name = trailing_identifier(rest);
+            if name.is_empty() {
+                return None;
+            }
+            sections.push(name);
+            rest = &rest[..rest.len() - name.len()];
+            match rest.strip_suffix('.') {
+                Some(r) => rest = r,
+                None => break,
+            }
+        }
+
+        let mut sections = sections.into_iter().rev();
+        let mut type_ref = self.variable_type(sections.next()?, offset)?;
+        for section in sections {
+            let attributes = self
+                .global_table
+                .get_type(*type_ref.type_id())
+                .get_attributes(&Location::builtin())
+                .ok()?;
+            type_ref = attributes
+                .iter()
+                .find(|(_, name, _)| name.name() == section)?
+                .2
+                .clone();
+        }
+        Some(type_ref)
+    }
+
+    /// Finds the type of the variable named `name` most recently defined before `offset`
+    fn variable_type(&self, name: &str, offset: usize) -> Option<TypeRef> {
+        self.name_uses
+            .iter()
+            .filter_map(|u| match u.target() {
+                NameTarget::Variable {
+                    definition,
+                    type_ref,
+                } if u.name() == name => Some((definition.offset()?, type_ref)),
+                _ => None,
+            })
+            .filter(|(definition, _)| *definition < offset)
+            .max_by_key(|(definition, _)| *definition)
+            .map(|(_, type_ref)| type_ref.clone())
+    }
+
+    /// Finds a type by name
+    fn type_by_name(&self, name: &str) -> Option<TypeID> {
+        self.global_table
+            .get_type_ids()
+            .into_iter()
+            .find(|t| self.global_table.get_type(*t).name() == name)
+    }
+
+    /// Lists the methods of a type, only including those taking `self` if `dynamic` is set
+    fn methods(&self, type_id: TypeID, dynamic: bool) -> Vec<Completion> {
+        let Some(methods) = self.global_table.get_impl_functions(type_id) else {
+            return Vec::new();
+        };
+        methods
+            .iter()
+            .sorted_by_key(|(name, _)| *name)
+            .filter_map(|(name, id)| {
+                let signature = self.global_table.get_function_signature(*id);
+                let takes_self = !matches!(signature.self_type(), SelfType::None);
+                if dynamic && !takes_self {
+                    return None;
+                }
+                Some(Completion {
+                    label: name.clone(),
+                    kind: if takes_self {
+                        CompletionKind::Method
+                    } else {
+                        CompletionKind::Function
+                    },
+                    detail: format_signature(name, signature, &self.global_table),
+                })
+            })
+            .collect_vec()
+    }
+}
+
+/// Returns the identifier at the end of `text`
+fn trailing_identifier(text: &str) -> &str {
+    let start = text
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .map_or(0, |i| i + 1);
+    &text[start..]
+}
+
+/// Formats a function's signature as it would be written
+fn format_signature(
+    name: &str,
+    signature: &FunctionSignature,
+    global_table: &GlobalTable,
+) -> String {
+    let args = signature
+        .args()
+        .iter()
+        .enumerate()
+        .map(|(i, (arg_name, type_ref))| {
+            if i == 0 {
+                match signature.self_type() {
+                    SelfType::None => {}
+                    SelfType::CopySelf => return "self".to_string(),
+                    SelfType::RefSelf => return "&self".to_string(),
+                    SelfType::MutRefSelf => return "&mut self".to_string(),
+                }
+            }
+            format!(
+                "{}: {}",
+                arg_name.name(),
+                global_table.get_type_name(type_ref)
+            )
+        })
+        .join(", ");
+
+    let return_type = signature
+        .return_type()
+        .as_ref()
+        .map(|t| format!(" -> {}", global_table.get_type_name(t)))
+        .unwrap_or_default();
+
+    format!("fn {name}({args}){return_type}")
+}
@@ -0,0 +1,136 @@
+use std::io;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+
+/// Reads a JSON-RPC message framed by a `Content-Length` header. Returns `None` at the end of the
+/// input
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        // A blank line separates the headers from the content
+        if header.is_empty() {
+            if length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let mut content = vec![0; length.unwrap()];
+    reader.read_exact(&mut content)?;
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Writes a JSON-RPC message with a `Content-Length` header
+pub fn write_message(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+    let content = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{content}", content.len())?;
+    writer.flush()
+}
+
+/// Converts a `file://` URI to a path
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+
+    // Decode percent-encoded bytes
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let Some((&b, next)) = rest.split_first() {
+        if b == b'%' && next.len() >= 2 {
+            if let Ok(decoded) =
+                u8::from_str_radix(std::str::from_utf8(&next[..2]).unwrap_or(""), 16)
+            {
+                bytes.push(decoded);
+                rest = &next[2..];
+                continue;
+            }
+        }
+        bytes.push(b);
+        rest = next;
+    }
+
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+/// Converts an absolute path to a `file://` URI
+pub fn path_to_uri(path: &Path) -> String {
+    let mut uri = "file://".to_string();
+    for b in path.to_string_lossy().bytes() {
+        if b.is_ascii_alphanumeric() || b"/-_.~".contains(&b) {
+            uri.push(b as char);
+        } else {
+            uri += &format!("%{b:02X}");
+        }
+    }
+    uri
+}
+
+/// Converts a byte offset in `text` to an LSP position - a line and a column in UTF-16 code units,
+/// both counted from 0
+pub fn offset_to_position(text: &str, offset: usize) -> Value {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+
+    let before = &text[..offset];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let character: usize = before[line_start..].chars().map(char::len_utf16).sum();
+
+    json!({ "line": line, "character": character })
+}
+
+/// Converts an LSP position to a byte offset in `text`
+pub fn position_to_offset(text: &str, position: &Value) -> usize {
+    let line = position["line"].as_u64().unwrap_or(0) as usize;
+    let character = position["character"].as_u64().unwrap_or(0) as usize;
+
+    let mut line_start = 0;
+    for _ in 0..line {
+        match text[line_start..].find('\n') {
+            Some(i) => line_start += i + 1,
+            None => return text.len(),
+        }
+    }
+
+    let mut units = 0;
+    for (i, c) in text[line_start..].char_indices() {
+        if units >= character || c == '\n' {
+            return line_start + i;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+/// Returns the byte offset of the end of the identifier starting at `offset`, or of the next
+/// character on the line if there isn't one, so that a location can be shown as a range
+pub fn identifier_end(text: &str, offset: usize) -> usize {
+    let Some(rest) = text.get(offset..) else {
+        return offset;
+    };
+    let length = rest
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len());
+    match rest.chars().next() {
+        _ if length > 0 => offset + length,
+        // Ranges shouldn't extend onto the next line
+        Some(c) if c != '\n' && c != '\r' => offset + c.len_utf8(),
+        _ => offset,
+    }
+}
@@ -1,33 +1,58 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+use derive_getters::Getters;
+use derive_new::new;
 use itertools::Itertools;
 
-use crate::root::builtin::{BuiltinInlineFunction, InlineFnGenerator};
+use crate::root::builtin::{BuiltinInlineFunction, InlineFnGenerator, InterpretFn};
 use crate::root::compiler::assembly::heap::free_function;
 use crate::root::compiler::assembly::null::{is_null_function, null_function};
 use crate::root::compiler::global_tracker::GlobalTracker;
-use crate::root::compiler::local_variable_table::LocalVariableTable;
+use crate::root::compiler::local_variable_table::{Binding, LocalVariableTable};
 use crate::root::errors::name_resolver_errors::NRErrs;
 use crate::root::errors::WErr;
 use crate::root::name_resolver::resolve_function_signatures::FunctionSignature;
 use crate::root::parser::location::Location;
 use crate::root::parser::parse_function::parse_evaluable::{
-    FullNameToken, FullNameTokens, UnresolvedTypeRefToken,
+    EvaluableToken, FullNameToken, FullNameTokens, UnresolvedTypeRefToken,
 };
 use crate::root::parser::parse_function::parse_operator::{OperatorToken, PrefixOrInfixEx};
 use crate::root::parser::parse_function::FunctionToken;
 use crate::root::parser::parse_name::SimpleNameToken;
 use crate::root::parser::parse_struct::StructToken;
+use crate::root::parser::parse_type_alias::TypeAliasToken;
 use crate::root::parser::path_storage::{FileID, Scope};
-use crate::root::shared::common::{AddressedTypeRef, ByteSize, FunctionID, TypeID, TypeRef};
+use crate::root::shared::common::{
+    AddressedTypeRef, ByteSize, FunctionID, Indirection, TypeAliasID, TypeID, TypeRef,
+};
 use crate::root::shared::types::Type;
 use crate::root::POINTER_SIZE;
 
-/// An entry in the `NameTree` that identifies either a type or functions
-#[derive(Debug)]
+/// An entry in the `NameTree` that identifies either a type, functions or a type alias
+#[derive(Debug, Copy, Clone)]
 enum NameTreeEntry {
     Type(TypeID),
     Function(FunctionID),
+    Alias(TypeAliasID),
+}
+
+/// A type alias e.g. `type NodePtr = &Node;`. The target is resolved on first use from the file
+/// the alias is declared in
+struct TypeAlias {
+    name: String,
+    location: Location,
+    target: UnresolvedTypeRefToken,
+    file: FileID,
+    scope: Scope,
+    resolved: AliasState,
+}
+
+/// Resolution state of a `TypeAlias`
+enum AliasState {
+    Unresolved,
+    /// Currently being resolved - encountering the alias again means it refers to itself
+    Resolving,
+    Resolved(TypeRef),
 }
 
 #[derive(Default, Debug)]
@@ -68,6 +93,25 @@ pub enum NameResult {
     File(FileID),
 }
 
+/// What a name written in the source refers to
+#[derive(Clone, Debug)]
+pub enum NameTarget {
+    Function(FunctionID),
+    Type(TypeID),
+    Variable {
+        definition: Location,
+        type_ref: TypeRef,
+    },
+}
+
+/// A name written in the source and what it resolved to, recorded for editor tooling
+#[derive(Clone, Debug, Getters, new)]
+pub struct NameUse {
+    location: Location,
+    name: String,
+    target: NameTarget,
+}
+
 /// Tables containing all global, unchanging definitions from name resolution step
 // #[derive(Getters)]
 pub struct GlobalTable {
@@ -79,6 +123,18 @@ pub struct GlobalTable {
     builtin_type_name_table: HashMap<String, TypeID>,
     builtin_function_name_table: HashMap<String, FunctionID>,
     builtin_inline_functions: HashMap<FunctionID, InlineFnGenerator>,
+    builtin_interpreted_functions: HashMap<FunctionID, InterpretFn>,
+    diverging_functions: HashSet<FunctionID>,
+    attribute_defaults: HashMap<TypeID, HashMap<String, EvaluableToken>>,
+    type_docs: HashMap<TypeID, String>,
+    attribute_docs: HashMap<TypeID, HashMap<String, String>>,
+    type_aliases: Vec<TypeAlias>,
+    /// Where user types are defined
+    type_locations: HashMap<TypeID, Location>,
+    /// Where user functions are defined
+    function_locations: HashMap<FunctionID, Location>,
+    /// Every name resolved, if recording is enabled
+    name_uses: Option<Vec<NameUse>>,
     current_file: FileID,
     scope: Scope,
 }
@@ -100,6 +156,15 @@ impl GlobalTable {
             builtin_type_name_table: Default::default(),
             builtin_function_name_table: Default::default(),
             builtin_inline_functions: Default::default(),
+            builtin_interpreted_functions: Default::default(),
+            diverging_functions: Default::default(),
+            attribute_defaults: Default::default(),
+            type_docs: Default::default(),
+            attribute_docs: Default::default(),
+            type_aliases: Vec::new(),
+            type_locations: Default::default(),
+            function_locations: Default::default(),
+            name_uses: None,
             current_file: FileID::MAIN_FILE,
             scope: Default::default(),
         }
@@ -111,6 +176,11 @@ impl GlobalTable {
         self.scope = scope;
     }
 
+    /// Returns the file the namespace is scoped to and its scope, so that they can be restored
+    pub fn namespace(&self) -> (FileID, Scope) {
+        (self.current_file, self.scope.clone())
+    }
+
     // Get a file from a folder by name
     pub fn get_file_from_folder(
         &self,
@@ -179,6 +249,11 @@ impl GlobalTable {
             .insert(inline.id(), inline.signature());
         self.builtin_inline_functions
             .insert(inline.id(), inline.inline());
+        self.builtin_interpreted_functions
+            .insert(inline.id(), inline.interpret());
+        if inline.diverges() {
+            self.diverging_functions.insert(inline.id());
+        }
 
         if let Some(parent) = inline.parent_type() {
             self.get_impl_mut(parent)
@@ -209,18 +284,118 @@ impl GlobalTable {
         let id = TypeID(self.id_counter - 1);
 
         file_level_tree.add_entry(st.name().name().clone(), NameTreeEntry::Type(id));
+        self.type_locations
+            .insert(id, st.name().location().clone());
 
         id
     }
 
+    /// Adds a type alias from a `TypeAliasToken`. `scope` is the scope of the file the alias is
+    /// declared in, used when resolving its target
+    pub fn add_from_type_alias_token(&mut self, tat: &TypeAliasToken, scope: Scope) {
+        let file = tat.location().file_id().unwrap();
+        let id = TypeAliasID(self.type_aliases.len());
+        self.type_aliases.push(TypeAlias {
+            name: tat.name().name().clone(),
+            location: tat.location().clone(),
+            target: tat.target().clone(),
+            file,
+            scope,
+            resolved: AliasState::Unresolved,
+        });
+
+        self.name_table
+            .get_tree_mut(file)
+            .add_entry(tat.name().name().clone(), NameTreeEntry::Alias(id));
+    }
+
+    /// Resolves the target of every type alias so that aliases with errors are reported even if
+    /// they are never used
+    pub fn resolve_type_aliases(&mut self) -> Result<(), WErr> {
+        for i in 0..self.type_aliases.len() {
+            self.resolve_type_alias(TypeAliasID(i))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the type an alias refers to, resolving it if it hasn't been already
+    fn resolve_type_alias(&mut self, id: TypeAliasID) -> Result<TypeRef, WErr> {
+        let alias = &mut self.type_aliases[id.0];
+        match &alias.resolved {
+            AliasState::Resolved(t) => return Ok(t.clone()),
+            AliasState::Resolving => {
+                return WErr::ne(
+                    NRErrs::CircularTypeAlias(alias.name.clone()),
+                    alias.location.clone(),
+                );
+            }
+            AliasState::Unresolved => {}
+        }
+        alias.resolved = AliasState::Resolving;
+
+        // Resolve the target from the file the alias is declared in
+        let target = alias.target.clone();
+        let scope = alias.scope.clone();
+        let previous_file = self.current_file;
+        let previous_scope = std::mem::replace(&mut self.scope, scope);
+        self.current_file = alias.file;
+
+        let result = self.resolve_to_type_ref(&target, None);
+
+        self.current_file = previous_file;
+        self.scope = previous_scope;
+
+        let t = result?;
+        self.type_aliases[id.0].resolved = AliasState::Resolved(t.clone());
+        Ok(t)
+    }
+
+    /// Expands a type alias named with extra references e.g. `&NodePtr`
+    fn expand_type_alias(
+        &mut self,
+        id: TypeAliasID,
+        indirection: Indirection,
+        mutability: u64,
+        nullability: u64,
+        heap: u64,
+    ) -> Result<TypeRef, WErr> {
+        let target = self.resolve_type_alias(id)?;
+        if !indirection.has_indirection() {
+            return Ok(target.with_alias(id));
+        }
+        let inner = target.indirection().0;
+        Ok(target
+            .with_indirection(Indirection(inner + indirection.0))
+            .with_mutability(target.mutability() | (mutability << inner))
+            .with_nullability(target.nullability() | (nullability << inner))
+            .with_heap(target.heap() | (heap << inner)))
+    }
+
     /// Adds a function from a `FunctionToken`
     ///
     /// `FunctionID` returned MUST BE USED to set a function signature
+    ///
+    /// Methods may be added to builtin types but not if they would replace a builtin method
     pub fn add_from_function_token(
         &mut self,
         ft: &FunctionToken,
         containing_class: Option<TypeID>,
-    ) -> FunctionID {
+    ) -> Result<FunctionID, WErr> {
+        if let Some(containing_class) = containing_class {
+            if let Some(existing) = self.get_impl_function_by_name(containing_class, ft.name().name())
+            {
+                if self.builtin_inline_functions.contains_key(&existing) {
+                    return WErr::ne(
+                        NRErrs::BuiltinMethodCollision(
+                            ft.name().name().clone(),
+                            self.get_type(containing_class).name().to_string(),
+                        ),
+                        ft.name().location().clone(),
+                    );
+                }
+            }
+        }
+
         let id = if ft.name().name() == "main"
             && ft
                 .location()
@@ -233,6 +408,9 @@ impl GlobalTable {
             FunctionID(self.id_counter - 1)
         };
 
+        self.function_locations
+            .insert(id, ft.name().location().clone());
+
         if let Some(containing_class) = containing_class {
             self.impl_definitions.entry(containing_class).or_default();
 
@@ -248,7 +426,7 @@ impl GlobalTable {
             file_level_tree.add_entry(ft.name().name().clone(), NameTreeEntry::Function(id));
         }
 
-        id
+        Ok(id)
     }
 
     /// Adds a function signature for a previously given `FunctionID`
@@ -261,6 +439,45 @@ impl GlobalTable {
             .insert(given_id, function_signature);
     }
 
+    /// Adds the default value of a user type's attribute, used if the attribute is not given when
+    /// the type is initialised
+    pub fn add_attribute_default(&mut self, type_id: TypeID, name: String, default: EvaluableToken) {
+        self.attribute_defaults
+            .entry(type_id)
+            .or_default()
+            .insert(name, default);
+    }
+
+    /// Gets the default value of a type's attribute, if it has one
+    pub fn get_attribute_default(&self, type_id: TypeID, name: &str) -> Option<&EvaluableToken> {
+        self.attribute_defaults
+            .get(&type_id)
+            .and_then(|d| d.get(name))
+    }
+
+    /// Adds the doc comments of a user type
+    pub fn add_type_docs(&mut self, type_id: TypeID, docs: String) {
+        self.type_docs.insert(type_id, docs);
+    }
+
+    /// Gets the doc comments of a type, if it has any
+    pub fn get_type_docs(&self, type_id: TypeID) -> Option<&String> {
+        self.type_docs.get(&type_id)
+    }
+
+    /// Adds the doc comments of a user type's attribute
+    pub fn add_attribute_docs(&mut self, type_id: TypeID, name: String, docs: String) {
+        self.attribute_docs
+            .entry(type_id)
+            .or_default()
+            .insert(name, docs);
+    }
+
+    /// Gets the doc comments of a type's attribute, if it has any
+    pub fn get_attribute_docs(&self, type_id: TypeID, name: &str) -> Option<&String> {
+        self.attribute_docs.get(&type_id).and_then(|d| d.get(name))
+    }
+
     /// Adds a type definition for a previously given `TypeID`
     pub fn add_user_type(&mut self, given_id: TypeID, definition: Box<dyn Type>) {
         self.type_definitions.insert(given_id, definition);
@@ -287,7 +504,26 @@ impl GlobalTable {
         name: &UnresolvedTypeRefToken,
         from_imported_file: Option<FileID>,
     ) -> Result<TypeRef, WErr> {
-        let (indirection, full_name) = (name.indirection(), name.inner());
+        let type_ref = self.find_type_ref(name, from_imported_file)?;
+        if let FullNameTokens::Name { name, .. } = name.inner().token() {
+            self.add_name_use(name, NameTarget::Type(*type_ref.type_id()));
+        }
+        Ok(type_ref)
+    }
+
+    /// Resolves a type name without recording its use
+    fn find_type_ref(
+        &mut self,
+        name: &UnresolvedTypeRefToken,
+        from_imported_file: Option<FileID>,
+    ) -> Result<TypeRef, WErr> {
+        let (indirection, mutability, nullability, heap, full_name) = (
+            name.indirection(),
+            name.mutability(),
+            name.nullability(),
+            name.heap(),
+            name.inner(),
+        );
 
         fn find_error_point(name: &FullNameToken, prev_location: &Location) -> Location {
             match name.token() {
@@ -311,18 +547,10 @@ impl GlobalTable {
             name
         };
 
-        let process_tree = |tree: &NameTree| -> Option<_> {
-            tree.get_entry(name.name()).map(|val| match val {
-                NameTreeEntry::Type(t) => Ok(TypeRef::new(*t, 1, *indirection)),
-                NameTreeEntry::Function(_) => WErr::ne(
-                    NRErrs::FoundFunctionNotType(name.name().clone()),
-                    full_name.location().clone(),
-                ),
-            })
-        };
+        let entry = |tree: &NameTree| tree.get_entry(name.name()).copied();
 
         // Search in imported file
-        if let Some(import_file) = from_imported_file {
+        let entry = if let Some(import_file) = from_imported_file {
             debug_assert!(self
                 .scope
                 .files_imported()
@@ -330,30 +558,53 @@ impl GlobalTable {
                 .map(|(f, _)| *f)
                 .contains(&import_file));
 
-            if let Some(r) = process_tree(self.name_table.get_tree_mut(import_file)) {
-                return r;
-            }
-
-            return WErr::ne(
-                NRErrs::TypeNotFound(name.name().clone()),
-                full_name.location().clone(),
-            );
-        }
-
-        // Search current file
-        if let Some(r) = process_tree(self.name_table.get_tree_mut(self.current_file)) {
-            return r;
-        }
+            let Some(e) = entry(self.name_table.get_tree_mut(import_file)) else {
+                return WErr::ne(
+                    NRErrs::TypeNotFound(name.name().clone()),
+                    full_name.location().clone(),
+                );
+            };
+            Some(e)
+        } else {
+            // Search current file, then used files, then the prelude
+            let mut files = vec![self.current_file];
+            files.extend(self.scope.files_used().iter().map(|(f, _)| *f));
+            files.extend(*self.scope.prelude());
+            files
+                .into_iter()
+                .find_map(|f| entry(self.name_table.get_tree_mut(f)))
+        };
 
-        // Used files
-        for (use_file, _) in self.scope.files_used() {
-            if let Some(r) = process_tree(self.name_table.get_tree_mut(*use_file)) {
-                return r;
+        match entry {
+            Some(NameTreeEntry::Type(t)) => {
+                return Ok(TypeRef::new(t, 1, *indirection)
+                    .with_mutability(*mutability)
+                    .with_nullability(*nullability)
+                    .with_heap(*heap));
+            }
+            Some(NameTreeEntry::Alias(a)) => {
+                return self.expand_type_alias(
+                    a,
+                    *indirection,
+                    *mutability,
+                    *nullability,
+                    *heap,
+                );
+            }
+            Some(NameTreeEntry::Function(_)) => {
+                return WErr::ne(
+                    NRErrs::FoundFunctionNotType(name.name().clone()),
+                    full_name.location().clone(),
+                );
             }
+            None => {}
         }
 
         if let Some(r) = self.builtin_type_name_table.get(name.name()) {
-            return Ok(TypeRef::new(*r, 1, *indirection));
+            return Ok(TypeRef::new(*r, 1, *indirection)
+                .with_mutability(*mutability)
+                .with_nullability(*nullability)
+                .with_heap(*heap));
         }
 
         if let Some(_fid) = self.builtin_function_name_table.get(name.name()) {
@@ -403,23 +654,84 @@ impl GlobalTable {
     /// Adds a local, named variable to the `LocalVariableTable` and returns the address
     pub fn add_local_variable_named(
         &mut self,
-        name: String,
+        binding: Binding,
         t: &UnresolvedTypeRefToken,
         local_variable_table: &mut LocalVariableTable,
     ) -> Result<AddressedTypeRef, WErr> {
         let t = self.resolve_to_type_ref(t, None)?;
+        self.add_variable_definition(&binding, &t);
         let size = self.get_size(&t);
         let address = local_variable_table.add_new_unnamed(size);
         let address = AddressedTypeRef::new(address, t);
-        local_variable_table.add_existing(name, address.clone());
+        local_variable_table.add_existing(binding, address.clone());
         Ok(address)
     }
 
+    /// Starts recording every name resolved, and every variable defined, from now on
+    pub fn record_name_uses(&mut self) {
+        self.name_uses = Some(Vec::new());
+    }
+
+    /// Takes the names recorded since `record_name_uses` was called
+    pub fn take_name_uses(&mut self) -> Vec<NameUse> {
+        self.name_uses.take().unwrap_or_default()
+    }
+
+    /// Records what a name refers to if recording is enabled
+    pub fn add_name_use(&mut self, name: &SimpleNameToken, target: NameTarget) {
+        if let Some(name_uses) = &mut self.name_uses {
+            name_uses.push(NameUse {
+                location: name.location().clone(),
+                name: name.name().clone(),
+                target,
+            });
+        }
+    }
+
+    /// Records the definition of a named variable if recording is enabled
+    pub fn add_variable_definition(&mut self, binding: &Binding, type_ref: &TypeRef) {
+        if let Some(name_uses) = &mut self.name_uses {
+            name_uses.push(NameUse {
+                location: binding.location().clone(),
+                name: binding.name().clone(),
+                target: NameTarget::Variable {
+                    definition: binding.location().clone(),
+                    type_ref: type_ref.clone(),
+                },
+            });
+        }
+    }
+
     /// Returns whether a main function has been defined
     pub fn has_main(&self) -> bool {
         self.function_signatures.contains_key(&FunctionID(0))
     }
 
+    /// Returns the ids of every type
+    pub fn get_type_ids(&self) -> Vec<TypeID> {
+        self.type_definitions.keys().copied().collect_vec()
+    }
+
+    /// Returns the methods of a type by name, if it has any
+    pub fn get_impl_functions(&self, type_id: TypeID) -> Option<&HashMap<String, FunctionID>> {
+        self.impl_definitions.get(&type_id)
+    }
+
+    /// Returns where a user type is defined
+    pub fn get_type_location(&self, type_id: TypeID) -> Option<&Location> {
+        self.type_locations.get(&type_id)
+    }
+
+    /// Returns where a user function is defined
+    pub fn get_function_location(&self, function_id: FunctionID) -> Option<&Location> {
+        self.function_locations.get(&function_id)
+    }
+
+    /// Returns the builtin functions that aren't methods by name
+    pub fn get_builtin_functions(&self) -> &HashMap<String, FunctionID> {
+        &self.builtin_function_name_table
+    }
+
     /// Returns a `Type` specified by the `TypeID`. Panics if it does not exist
     pub fn get_type(&self, type_id: TypeID) -> &dyn Type {
         (*self.type_definitions.get(&type_id).as_ref().unwrap()).as_ref()
@@ -435,11 +747,32 @@ impl GlobalTable {
 
     /// Converts a `TypeRef` to a user-readable format
     pub fn get_type_name(&self, type_ref: &TypeRef) -> String {
-        format!(
-            "{}{}",
-            unsafe { String::from_utf8_unchecked(vec![b'&'; type_ref.indirection().0]) },
-            self.get_type(*type_ref.type_id()).name()
-        )
+        let mut name = String::new();
+        let mut arrays = 0;
+        // Outermost reference first
+        for i in (1..=type_ref.indirection().0).rev() {
+            if type_ref.is_nullable_at(Indirection(i)) {
+                name += "?";
+            }
+            if type_ref.is_mutable_at(Indirection(i)) {
+                name += "&mut ";
+            } else {
+                name += "&";
+            }
+            if type_ref.is_heap_at(Indirection(i)) {
+                name += "[";
+                arrays += 1;
+            }
+        }
+        name += self.get_type(*type_ref.type_id()).name();
+        name += &"]".repeat(arrays);
+
+        // Show the alias the type was named by
+        if let Some(alias) = type_ref.alias() {
+            format!("{} aka {name}", self.type_aliases[alias.0].name)
+        } else {
+            name
+        }
     }
 
     /// Gets a file by name from the current scope
@@ -463,6 +796,41 @@ impl GlobalTable {
 
     /// Returns what a name resolves to
     pub fn resolve_name(
+        &mut self,
+        name: &SimpleNameToken,
+        from_imported_file: Option<FileID>,
+        containing_class: Option<&SimpleNameToken>,
+        local_variable_table: &LocalVariableTable,
+        global_tracker: &GlobalTracker,
+    ) -> Result<NameResult, WErr> {
+        let result = self.find_name(
+            name,
+            from_imported_file,
+            containing_class,
+            local_variable_table,
+            global_tracker,
+        )?;
+
+        let target = match &result {
+            NameResult::Function(f) => Some(NameTarget::Function(*f)),
+            NameResult::Type(t) => Some(NameTarget::Type(*t)),
+            NameResult::Variable(v) => local_variable_table
+                .binding_at(*v.local_address())
+                .map(|binding| NameTarget::Variable {
+                    definition: binding.location().clone(),
+                    type_ref: v.type_ref().clone(),
+                }),
+            NameResult::File(_) => None,
+        };
+        if let Some(target) = target {
+            self.add_name_use(name, target);
+        }
+
+        Ok(result)
+    }
+
+    /// Finds what a name resolves to without recording its use
+    fn find_name(
         &mut self,
         name: &SimpleNameToken,
         from_imported_file: Option<FileID>,
@@ -470,10 +838,21 @@ impl GlobalTable {
         local_variable_table: &LocalVariableTable,
         global_tracker: &GlobalTracker,
     ) -> Result<NameResult, WErr> {
+        let aliases = &self.type_aliases;
         let process_tree = |tree: &NameTree| -> Option<_> {
             tree.get_entry(name.name()).map(|val| match val {
                 NameTreeEntry::Type(t) => Ok(NameResult::Type(*t)),
                 NameTreeEntry::Function(f) => Ok(NameResult::Function(*f)),
+                // Aliases of a type with no indirection can be used to access its methods
+                NameTreeEntry::Alias(a) => match &aliases[a.0].resolved {
+                    AliasState::Resolved(t) if !t.indirection().has_indirection() => {
+                        Ok(NameResult::Type(*t.type_id()))
+                    }
+                    _ => WErr::ne(
+                        NRErrs::IndirectAliasAccess(aliases[a.0].name.clone()),
+                        name.location().clone(),
+                    ),
+                },
             })
         };
 
@@ -527,6 +906,13 @@ impl GlobalTable {
             }
         }
 
+        // Prelude
+        if let Some(prelude) = self.scope.prelude() {
+            if let Some(r) = process_tree(self.name_table.get_tree_mut(*prelude)) {
+                return r;
+            }
+        }
+
         // Imported files
         if let Some(file) = self.get_imported_file(name, global_tracker) {
             return Ok(NameResult::File(file));
@@ -582,6 +968,11 @@ impl GlobalTable {
         }
     }
 
+    /// Returns whether a function never returns e.g. `exit`
+    pub fn is_diverging(&self, function_id: FunctionID) -> bool {
+        self.diverging_functions.contains(&function_id)
+    }
+
     /// Returns the `FunctionSignature` of a function
     pub fn get_function_signature(&self, function_id: FunctionID) -> &FunctionSignature {
         self.function_signatures.get(&function_id).as_ref().unwrap()
@@ -596,4 +987,10 @@ impl GlobalTable {
         let inline = self.builtin_inline_functions.get(&function);
         (signature, inline)
     }
+
+    /// Returns the interpreter implementation of a builtin function, or `None` if the function is
+    /// defined in code
+    pub fn get_interpreted_function(&self, function: FunctionID) -> Option<InterpretFn> {
+        self.builtin_interpreted_functions.get(&function).copied()
+    }
 }
@@ -11,6 +11,7 @@ use crate::root::parser::parse_function::parse_literal::LiteralToken;
 use crate::root::parser::parse_function::FunctionToken;
 use crate::root::parser::parse_name::SimpleNameToken;
 use crate::root::parser::parse_toplevel::TopLevelTokens;
+use crate::root::parser::path_storage::Scope;
 use crate::root::shared::common::{ByteSize, FunctionID, TypeID};
 use crate::root::shared::common::{LocalAddress, TypeRef};
 use crate::root::shared::types::Type;
@@ -79,26 +80,19 @@ impl Type for UserType {
             literal.location().clone(),
         )
     }
-}
-
-// ! Unoptimised
-/// Converts parsed tokens into a collection of functions to be compiled and a `GlobalDefinitionTable`
-/// with function signatures and type definitions
-pub fn resolve_names(
-    ast: Vec<TopLevelTokens>,
-    global_table: &mut GlobalTable,
-    unprocessed_functions: &mut HashMap<FunctionID, FunctionToken>,
-) -> Result<(), WErr> {
-    let mut ast = ast;
 
-    // ? User types > 1; Builtin Types < -1
-    // let mut type_names: HashMap<TypeName, isize> = HashMap::new();
-    // ? (Name, (type, id)) - Type 0 means global
-    // let mut function_names: HashMap<String, (isize, isize)> = HashMap::new();
-
-    // * Type names
+    fn literal_bytes(&self, literal: &LiteralToken) -> Result<Vec<u8>, WErr> {
+        WErr::ne(
+            EvalErrs::TypeCannotBeInitialisedByLiteral(self.name().clone()),
+            literal.location().clone(),
+        )
+    }
+}
 
-    for symbol in &mut ast {
+/// Registers the names of all types and type aliases in a file so that they can be referenced by
+/// any other file, regardless of import order
+pub fn declare_names(ast: &mut [TopLevelTokens], scope: &Scope, global_table: &mut GlobalTable) {
+    for symbol in ast {
         match symbol {
             TopLevelTokens::Struct(st) => {
                 let id = global_table.add_from_struct_token(st);
@@ -106,21 +100,36 @@ pub fn resolve_names(
             }
             TopLevelTokens::Impl(_) => {}
             TopLevelTokens::Function(_) => {}
+            TopLevelTokens::TypeAlias(tat) => {
+                global_table.add_from_type_alias_token(tat, scope.clone());
+            }
         };
     }
+}
 
-    let mut unsized_final_types: HashMap<TypeID, UnsizedUserType> = new_hashmap();
-
+// ! Unoptimised
+/// Resolves the impls, functions, and structs of a file whose names have already been declared by
+/// `declare_names`. Structs are left as unsized types to be sized by `resolve_all_type_sizes` once
+/// every file has been processed
+pub fn resolve_names(
+    ast: Vec<TopLevelTokens>,
+    global_table: &mut GlobalTable,
+    unprocessed_functions: &mut HashMap<FunctionID, FunctionToken>,
+    unsized_final_types: &mut HashMap<TypeID, UnsizedUserType>,
+) -> Result<(), WErr> {
     // Processes impls, functions, and structs, leaving structs as unsized types
     for symbol in ast {
         match symbol {
             TopLevelTokens::Struct(st) => {
-                let (location, name, attributes, id) = st.dissolve();
+                let (location, name, attributes, id, docs) = st.dissolve();
                 let id = id.unwrap();
+                if let Some(docs) = docs {
+                    global_table.add_type_docs(id, docs);
+                }
 
                 // Process attributes into an unsized type
                 let mut p_attributes: Vec<(SimpleNameToken, TypeRef)> = Vec::new();
-                for (name, type_name) in attributes {
+                for (name, type_name, default, docs) in attributes {
                     let type_ref = global_table.resolve_to_type_ref(&type_name, None)?;
 
                     for (e_name, _) in &p_attributes {
@@ -131,6 +140,12 @@ pub fn resolve_names(
                             );
                         }
                     }
+                    if let Some(default) = default {
+                        global_table.add_attribute_default(id, name.name().clone(), default);
+                    }
+                    if let Some(docs) = docs {
+                        global_table.add_attribute_docs(id, name.name().clone(), docs);
+                    }
                     p_attributes.push((name, type_ref))
                 }
                 unsized_final_types.insert(
@@ -154,7 +169,13 @@ pub fn resolve_names(
                     .type_id();
 
                 for ft in functions {
-                    let function_id = global_table.add_from_function_token(&ft, Some(type_id));
+                    if *ft.is_test() {
+                        return WErr::ne(
+                            NRErrs::TestMethod(ft.name().name().clone()),
+                            ft.location().clone(),
+                        );
+                    }
+                    let function_id = global_table.add_from_function_token(&ft, Some(type_id))?;
                     let signature = resolve_function_signature(&ft, global_table)?;
                     global_table.add_function_signature(function_id, signature);
                     unprocessed_functions.insert(function_id, ft);
@@ -162,13 +183,42 @@ pub fn resolve_names(
             }
             TopLevelTokens::Function(ft) => {
                 // Register a function token
-                let function_id = global_table.add_from_function_token(&ft, None);
+                let function_id = global_table.add_from_function_token(&ft, None)?;
+                if *ft.is_test() {
+                    check_test_function(function_id, &ft)?;
+                }
                 let signature = resolve_function_signature(&ft, global_table)?;
                 global_table.add_function_signature(function_id, signature);
                 unprocessed_functions.insert(function_id, ft);
             }
+            // Resolved by `GlobalTable::resolve_type_aliases`
+            TopLevelTokens::TypeAlias(_) => {}
         };
     }
+    Ok(())
+}
+
+/// Checks that a function marked `#[test]` can be called by the test harness
+fn check_test_function(function_id: FunctionID, ft: &FunctionToken) -> Result<(), WErr> {
+    if function_id.is_main() {
+        return WErr::ne(NRErrs::MainTest, ft.location().clone());
+    }
+    if !ft.parameters().is_empty() || ft.return_type().is_some() {
+        return WErr::ne(
+            NRErrs::InvalidTestSignature(ft.name().name().clone()),
+            ft.location().clone(),
+        );
+    }
+    Ok(())
+}
+
+/// Resolves the size of every user type, erroring only if a type contains itself with no
+/// indirection
+pub fn resolve_all_type_sizes(
+    unsized_final_types: HashMap<TypeID, UnsizedUserType>,
+    global_table: &mut GlobalTable,
+) -> Result<(), WErr> {
+    let mut unsized_final_types = unsized_final_types;
     let mut final_types: HashMap<TypeID, UserType> = new_hashmap();
 
     // Resolve all unsized types
@@ -204,6 +254,5 @@ pub fn resolve_names(
         global_table.add_user_type(id, b!(user_type));
     }
 
-    // (final_types, type_names, unprocessed_functions)
     Ok(())
 }
@@ -41,8 +41,9 @@ fn handle_error_tree(e: &ErrorTree, path_storage: &PathStorage) -> WErr {
         ErrorTree::Stack { base, contexts } => {
             // Base error with context
             // Show base
+            let base = handle_error_tree(base, path_storage);
             let mut sb = "Base Error:\n".to_string();
-            for l in handle_error_tree(base, path_storage)
+            for l in base
                 .with_context(path_storage)
                 .to_string()
                 .lines()
@@ -72,26 +73,37 @@ fn handle_error_tree(e: &ErrorTree, path_storage: &PathStorage) -> WErr {
                 }
             }
 
-            WErr::locationless(sb)
+            WErr::locationless(sb).with_summary(base)
         }
         ErrorTree::Alt(z) => {
             // Show multiple errors
             let mut sb = "Failed multiple parsers -\n".to_string();
+            // The parser that got furthest is most likely to be the one intended
+            let mut furthest: Option<WErr> = None;
 
             for (i, e) in z.iter().enumerate() {
                 sb += &format!("{}:\n", i + 1);
 
-                let werr = handle_error_tree(e, path_storage)
-                    .with_context(path_storage)
-                    .to_string();
-                for line in werr.lines() {
+                let werr = handle_error_tree(e, path_storage);
+                for line in werr.with_context(path_storage).to_string().lines() {
                     sb += "    ";
                     sb += line;
                     sb += "\n";
                 }
+
+                let offset = |e: &WErr| e.summarised().location().as_ref().and_then(|l| l.offset());
+                if furthest
+                    .as_ref()
+                    .is_none_or(|f| offset(&werr) > offset(f))
+                {
+                    furthest = Some(werr);
+                }
             }
 
-            WErr::locationless(sb)
+            match furthest {
+                Some(furthest) => WErr::locationless(sb).with_summary(furthest),
+                None => WErr::locationless(sb),
+            }
         }
     }
 }
@@ -1,6 +1,5 @@
 use std::cmp::min;
 use std::fmt::{Display, Formatter};
-use std::fs;
 use std::marker::PhantomData;
 
 use color_print::cformat;
@@ -34,7 +33,7 @@ impl<'a> ToLocation<'a> {
 }
 
 /// Inner data structure for a location
-#[derive(Debug, Clone, Hash)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
 struct InnerLocation {
     /// File id
     file_id: FileID,
@@ -45,18 +44,18 @@ struct InnerLocation {
 }
 
 /// Error type
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ErrorL;
 
 /// Warning type
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct WarningL;
 
 // Default type is an error
 pub type Location = LocationTyped<ErrorL>;
 
 /// Represents where an error occured
-#[derive(Debug, Clone, Hash)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
 enum ErrorLocation {
     Location(InnerLocation),
     Builtin,
@@ -65,7 +64,7 @@ enum ErrorLocation {
 }
 
 /// Location with its type
-#[derive(Debug, Clone, Hash)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct LocationTyped<ErrorType> {
     error_type: PhantomData<ErrorType>,
     inner_location: ErrorLocation,
@@ -116,6 +115,24 @@ impl<ErrorType> LocationTyped<ErrorType> {
         }
     }
 
+    /// Gets the offset of the location from the start of its file in bytes (none if builtin)
+    pub fn offset(&self) -> Option<usize> {
+        match &self.inner_location {
+            ErrorLocation::Location(l) => Some(l.offset),
+            ErrorLocation::Builtin => None,
+            ErrorLocation::None => None,
+        }
+    }
+
+    /// Gets the line of the location, counted from 1 (none if builtin)
+    pub fn line(&self) -> Option<u32> {
+        match &self.inner_location {
+            ErrorLocation::Location(l) => Some(l.line),
+            ErrorLocation::Builtin => None,
+            ErrorLocation::None => None,
+        }
+    }
+
     /// Creates a `LocationContext`
     pub fn with_context<'a>(
         &'a self,
@@ -150,6 +167,34 @@ impl<ErrorType> LocationTyped<ErrorType> {
         !matches!(self.inner_location, ErrorLocation::None)
     }
 
+    /// Formats a location as `file:line:column` (column counted from 1) for messages emitted at
+    /// runtime e.g. panics
+    pub fn short_position(&self, path_storage: &PathStorage) -> String {
+        let location = match &self.inner_location {
+            ErrorLocation::Builtin => return "<builtin>".to_string(),
+            ErrorLocation::None => return "<unknown>".to_string(),
+            ErrorLocation::Location(l) => l,
+        };
+
+        let file = path_storage.reconstruct_file(location.file_id);
+
+        // The offset is in bytes from the start of the file so the line and column have to be
+        // found from the text before it
+        let position = path_storage.read_file(location.file_id).ok().and_then(|text| {
+            let before = text.get(..location.offset)?;
+            let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+            Some((
+                before.matches('\n').count() + 1,
+                before[line_start..].chars().count() + 1,
+            ))
+        });
+
+        match position {
+            Some((line, column)) => format!("{file}:{line}:{column}"),
+            None => format!("{file}:{}", location.line),
+        }
+    }
+
     /// Formats a location into text
     fn fmt_choice(
         &self,
@@ -179,7 +224,7 @@ impl<ErrorType> LocationTyped<ErrorType> {
             write!(f, "Failed to fetch file reference (has the file changed?)")
         }
 
-        let Ok(file) = fs::read_to_string(path_storage.reconstruct_file(location.file_id)) else {
+        let Ok(file) = path_storage.read_file(location.file_id) else {
             return fail(f);
         };
 
@@ -1,4 +1,4 @@
-mod handle_errors;
+pub mod handle_errors;
 pub mod location;
 pub mod parse;
 pub mod parse_arguments;
@@ -6,12 +6,13 @@ pub mod parse_blocks;
 pub mod parse_comments;
 pub mod parse_function;
 pub mod parse_impl;
-mod parse_imports;
+pub mod parse_imports;
 pub mod parse_name;
 pub mod parse_name_old;
 pub mod parse_parameters;
 pub mod parse_struct;
 pub mod parse_toplevel;
+pub mod parse_type_alias;
 pub mod parse_util;
 pub mod path_storage;
 pub mod soft_alt;
@@ -1,6 +1,4 @@
 use std::collections::HashMap;
-use std::fs;
-use std::path::Path;
 
 use nom::IResult;
 use nom_locate::LocatedSpan;
@@ -22,16 +20,37 @@ pub type ErrorTree<'a> = GenericErrorTree<Span<'a>, &'static str, &'static str,
 
 /// Parses files into tokens
 pub fn parse(path_storage: &mut PathStorage) -> Result<HashMap<FileID, Vec<TopLevelTokens>>, WErr> {
-    // Paths to process
-    let mut path_queue = vec![(FileID::MAIN_FILE, Location::builtin())];
-    let mut output = HashMap::new();
+    parse_files(
+        path_storage,
+        vec![(FileID::MAIN_FILE, Location::builtin())],
+        HashMap::new(),
+        true,
+    )
+}
+
+/// Parses the queued files, the files they import and the prelude into tokens, adding them to
+/// those already in `output` e.g. for a main file that isn't parsed from disk. Paths are printed as
+/// they're parsed if `list_paths` is set
+pub fn parse_files(
+    path_storage: &mut PathStorage,
+    mut path_queue: Vec<(FileID, Location)>,
+    mut output: HashMap<FileID, Vec<TopLevelTokens>>,
+    list_paths: bool,
+) -> Result<HashMap<FileID, Vec<TopLevelTokens>>, WErr> {
+    if let Some(prelude) = path_storage.prelude() {
+        if prelude != FileID::MAIN_FILE {
+            path_queue.push((prelude, Location::builtin()));
+        }
+    }
 
     // Iterate as long as there are files to process
     while let Some((file_id, location)) = path_queue.pop() {
         // Get path
         let reconstructed = path_storage.reconstruct_file(file_id);
-        print!("\n  - {}", &reconstructed);
-        let Ok(text) = fs::read_to_string(Path::new(&reconstructed)) else {
+        if list_paths {
+            print!("\n  - {}", &reconstructed);
+        }
+        let Ok(text) = path_storage.read_file(file_id) else {
             return WErr::ne(
                 ParseError::FailedToOpenFile(reconstructed.to_string()),
                 location,
@@ -51,7 +70,11 @@ pub fn parse(path_storage: &mut PathStorage) -> Result<HashMap<FileID, Vec<TopLe
         debug_assert!(remaining.is_empty());
         output.insert(file_id, new_output);
     }
-    println!();
+    if list_paths {
+        println!();
+    }
+
+    path_storage.inject_prelude();
 
     Ok(output)
 }
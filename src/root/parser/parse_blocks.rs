@@ -5,6 +5,7 @@ use nom::{InputTake, Offset};
 
 use crate::root::errors::parser_errors::create_custom_error;
 use crate::root::parser::parse::{ErrorTree, ParseResult, Span};
+use crate::root::parser::parse_comments::parse_comment;
 use crate::root::parser::parse_util::discard_ignored;
 
 // ! BROKEN
@@ -32,6 +33,13 @@ pub const BRACKET_TERMINATOR: Terminator = Terminator {
     escape_char: None,
 };
 
+pub const SQUARE_BRACKET_TERMINATOR: Terminator = Terminator {
+    opening: '[',
+    closing: ']',
+    code_inner: true,
+    escape_char: None,
+};
+
 pub const STRING_TERMINATOR: Terminator = Terminator {
     opening: '"',
     closing: '"',
@@ -147,6 +155,12 @@ pub fn take_until_or_end_discard_smart<'a>(s: Span<'a>, until: &str) -> ParseRes
             break;
         }
 
+        // Tags in comments don't count
+        if let Ok((ns, _)) = parse_comment(s) {
+            s = ns;
+            continue;
+        }
+
         let c = s.chars().next().unwrap();
 
         for t in &DEFAULT_TERMINATORS {
@@ -1,5 +1,5 @@
 use crate::root::parser::parse::{ParseResult, Span};
-use nom::bytes::complete::{is_not, take_until};
+use nom::bytes::complete::{is_not, take_till, take_until};
 use nom::sequence::{pair, Tuple};
 use nom::Parser;
 use nom_supreme::tag::complete::tag;
@@ -18,6 +18,13 @@ fn pinline_comment(s: Span) -> ParseResult {
         .map(|(s, (_, y, _)): (Span, (Span, Span, Span))| (s, y))
 }
 
+/// Parse a doc comment e.g. `/// Adds two numbers`, returning the text after the `///`
+pub fn parse_doc_comment(s: Span) -> ParseResult {
+    pair(tag("///"), take_till(|c| c == '\n' || c == '\r'))
+        .parse(s)
+        .map(|(s, (_, y)): (Span, (Span, Span))| (s, y))
+}
+
 /// Discard any kind of comment
 pub fn parse_comment(s: Span) -> ParseResult {
     pinline_comment(s).or_else(|_| peol_comment(s))
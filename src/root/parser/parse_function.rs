@@ -18,8 +18,10 @@ use nom_supreme::tag::complete::tag;
 
 pub mod parse_assigner;
 pub mod parse_assignment;
+pub mod parse_block;
 pub mod parse_break;
 pub mod parse_evaluable;
+pub mod parse_heap;
 pub mod parse_if;
 pub mod parse_initialisation;
 pub mod parse_line;
@@ -27,11 +29,11 @@ pub mod parse_literal;
 mod parse_marker;
 pub mod parse_operator;
 pub mod parse_return;
-mod parse_struct_init;
+pub mod parse_struct_init;
 pub mod parse_while;
 
 /// Token representing a function including location
-#[derive(Debug, Getters, Dissolve)]
+#[derive(Debug, Clone, Getters, Dissolve)]
 pub struct FunctionToken {
     location: Location,
     end_location: Location,
@@ -40,11 +42,34 @@ pub struct FunctionToken {
     self_type: SelfType,
     parameters: Parameters,
     lines: Vec<LineTokens>,
+    /// Whether the function is marked `#[test]`
+    is_test: bool,
+    /// Text of the doc comments above the function
+    docs: Option<String>,
+}
+
+impl FunctionToken {
+    /// Sets the doc comments of the function
+    pub fn set_docs(&mut self, docs: Option<String>) {
+        self.docs = docs;
+    }
+}
+
+/// Attribute marking a function as a test
+const TEST_ATTRIBUTE: &str = "#[test]";
+
+/// Parses an optional `#[test]` attribute, returning whether it was found
+fn parse_test_attribute(s: Span) -> ParseResult<Span, bool> {
+    if let Ok((s, _)) = (tag(TEST_ATTRIBUTE), discard_ignored).parse(s) {
+        Ok((s, true))
+    } else {
+        Ok((s, false))
+    }
 }
 
 /// Tests if a line should be parsed as a function
 pub fn test_parse_function(s: Span<'_>) -> ParseResult<Span, ToplevelTestFn<'_>> {
-    match (tag("fn"), require_ignored).parse(s) {
+    match (parse_test_attribute, tag("fn"), require_ignored).parse(s) {
         Ok(_) => Ok((s, |x| {
             parse_function(x, None).map(|(s, x)| (s, TopLevelTokens::Function(x)))
         })),
@@ -57,6 +82,7 @@ pub fn parse_function<'a>(
     s: Span<'a>,
     allow_self: Option<&SimpleNameToken>,
 ) -> ParseResult<'a, Span<'a>, FunctionToken> {
+    let (s, is_test) = parse_test_attribute(s)?;
     let location = Location::from_span(&s);
     let (s, _) = tag("fn").parse(s)?;
     let (s, _) = require_ignored(s)?;
@@ -102,6 +128,8 @@ pub fn parse_function<'a>(
             return_type,
             parameters,
             lines,
+            is_test,
+            docs: None,
         },
     ))
 }
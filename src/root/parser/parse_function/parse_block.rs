@@ -0,0 +1,61 @@
+use b_box::b;
+use derive_getters::Getters;
+
+use crate::root::errors::parser_errors::create_custom_error;
+use crate::root::parser::location::Location;
+use crate::root::parser::parse::{ParseResult, Span};
+use crate::root::parser::parse_function::parse_evaluable::{parse_evaluable, EvaluableToken};
+use crate::root::parser::parse_function::parse_line::{parse_line, LineTokens};
+use crate::root::parser::parse_name::SimpleNameToken;
+use crate::root::parser::parse_util::discard_ignored;
+
+/// Token holding a block used as a value e.g. `{ let a: int = 1; a + 1 }`. The block evaluates to
+/// its trailing expression
+#[derive(Debug, Clone, Getters)]
+pub struct BlockToken {
+    location: Location,
+    lines: Vec<LineTokens>,
+    value: Box<EvaluableToken>,
+}
+
+/// Parses the contents of a block (excluding the braces) ending in a trailing expression
+pub fn parse_block<'a>(
+    contents: Span<'a>,
+    containing_class: Option<&SimpleNameToken>,
+) -> ParseResult<'a, (), BlockToken> {
+    let location = Location::from_span(&contents);
+    let mut lines = Vec::new();
+
+    let mut c = contents;
+    loop {
+        let (cs, _) = discard_ignored(c)?;
+        if cs.is_empty() {
+            return Err(create_custom_error(
+                "Expected a value at the end of the block".to_string(),
+                cs,
+            ));
+        }
+
+        match parse_line(cs, containing_class) {
+            Ok((cs, line)) => {
+                lines.push(line);
+                c = cs;
+            }
+            Err(line_error) => {
+                // Not a line - attempt to parse the rest of the block as the trailing expression
+                let Ok((_, value)) = parse_evaluable(cs, containing_class, false) else {
+                    return Err(line_error);
+                };
+
+                return Ok((
+                    (),
+                    BlockToken {
+                        location,
+                        lines,
+                        value: b!(value),
+                    },
+                ));
+            }
+        }
+    }
+}
@@ -7,7 +7,7 @@ use nom::character::complete::char;
 use nom_supreme::tag::complete::tag;
 
 /// Token representing a break
-#[derive(Debug, Getters)]
+#[derive(Debug, Clone, Getters)]
 pub struct BreakToken {
     location: Location,
 }
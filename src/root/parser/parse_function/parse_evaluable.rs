@@ -3,26 +3,44 @@ use derive_getters::Getters;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::character::complete::char;
+use nom::combinator::{map, peek};
+use nom::sequence::Tuple;
 
 use crate::root::errors::parser_errors::create_custom_error;
 use crate::root::parser::location::Location;
 use crate::root::parser::parse::{ErrorTree, ParseResult, Span};
 use crate::root::parser::parse_arguments::parse_arguments;
-use crate::root::parser::parse_blocks::{parse_default_terminator_content, BRACKET_TERMINATOR};
+use crate::root::parser::parse_blocks::{
+    parse_default_terminator_content, parse_terminator, BRACE_TERMINATOR, BRACKET_TERMINATOR,
+    DEFAULT_TERMINATORS, SQUARE_BRACKET_TERMINATOR,
+};
+use crate::root::parser::parse_function::parse_block::{parse_block, BlockToken};
+use crate::root::parser::parse_function::parse_heap::{
+    parse_delete, parse_heap_array, test_parse_delete, test_parse_heap_array, HeapArrayToken,
+};
+use crate::root::parser::parse_function::parse_if::{
+    parse_if_expression, test_parse_if_expression, IfExpressionToken,
+};
 use crate::root::parser::parse_function::parse_literal::{parse_literal, LiteralToken};
 use crate::root::parser::parse_function::parse_operator::{parse_operator, OperatorToken};
 use crate::root::parser::parse_function::parse_struct_init::{parse_struct_init, StructInitToken};
 use crate::root::parser::parse_name::{parse_simple_name, SimpleNameToken};
-use crate::root::parser::parse_util::discard_ignored;
+use crate::root::parser::parse_util::{discard_ignored, require_ignored};
 use crate::root::shared::common::Indirection;
 
 /// A token that can be evaluated to a value with location info
-#[derive(Debug, Getters)]
+#[derive(Debug, Clone, Getters)]
 pub struct EvaluableToken {
     location: Location,
     token: EvaluableTokens,
 }
 
+impl EvaluableToken {
+    pub fn new(location: Location, token: EvaluableTokens) -> EvaluableToken {
+        EvaluableToken { location, token }
+    }
+}
+
 /// Create a temp token from a token (and location)
 #[allow(private_interfaces)]
 pub fn temp_from_token(s: Span, token: EvaluableTokens) -> TempEvaluableTokensOne {
@@ -33,7 +51,7 @@ pub fn temp_from_token(s: Span, token: EvaluableTokens) -> TempEvaluableTokensOn
 }
 
 /// A token that can be evaluated to a value
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum EvaluableTokens {
     Name(SimpleNameToken, Option<SimpleNameToken>),
     StaticAccess {
@@ -52,13 +70,30 @@ pub enum EvaluableTokens {
     StructInitialiser(StructInitToken),
     InfixOperator(Box<EvaluableToken>, OperatorToken, Box<EvaluableToken>),
     PrefixOperator(OperatorToken, Box<EvaluableToken>),
+    Block(BlockToken),
+    If(IfExpressionToken),
+    HeapArray(HeapArrayToken),
+    /// A reference to the value at an index of the heap allocation a reference points into e.g.
+    /// `a[i]`
+    Index {
+        parent: Box<EvaluableToken>,
+        index: Box<EvaluableToken>,
+    },
+    /// Frees a heap reference
+    Delete(Box<EvaluableToken>),
     None,
 }
 
 /// A `TypeRef` that hasn't been resolved
-#[derive(Debug, Getters)]
+#[derive(Debug, Clone, Getters)]
 pub struct UnresolvedTypeRefToken {
     indirection: Indirection,
+    /// Which references are mutable (see `TypeRef::mutability`)
+    mutability: u64,
+    /// Which references are nullable (see `TypeRef::nullability`)
+    nullability: u64,
+    /// Which references are to heap arrays (see `TypeRef::heap`)
+    heap: u64,
     inner: FullNameToken,
 }
 
@@ -71,6 +106,9 @@ impl UnresolvedTypeRefToken {
     ) -> UnresolvedTypeRefToken {
         UnresolvedTypeRefToken {
             indirection: Indirection(0),
+            mutability: 0,
+            nullability: 0,
+            heap: 0,
             inner: FullNameToken {
                 location,
                 token: FullNameTokens::Name {
@@ -89,6 +127,9 @@ impl UnresolvedTypeRefToken {
     ) -> UnresolvedTypeRefToken {
         UnresolvedTypeRefToken {
             indirection,
+            mutability: 0,
+            nullability: 0,
+            heap: 0,
             inner: FullNameToken {
                 location,
                 token: FullNameTokens::Name {
@@ -99,6 +140,15 @@ impl UnresolvedTypeRefToken {
         }
     }
 
+    /// Returns the same type with the outermost reference mutable
+    pub fn with_outer_mutable(self) -> UnresolvedTypeRefToken {
+        let outer = 1 << (self.indirection.0 - 1);
+        UnresolvedTypeRefToken {
+            mutability: self.mutability | outer,
+            ..self
+        }
+    }
+
     #[allow(dead_code)]
     pub fn into_inner(self) -> FullNameToken {
         self.inner
@@ -106,7 +156,7 @@ impl UnresolvedTypeRefToken {
 }
 
 /// A token representing a name (e.g `a`, `a.b`, `a::b`) with a location
-#[derive(Debug, Getters)]
+#[derive(Debug, Clone, Getters)]
 pub struct FullNameToken {
     location: Location,
     token: FullNameTokens,
@@ -120,6 +170,9 @@ impl FullNameToken {
     pub fn with_no_indirection(self) -> UnresolvedTypeRefToken {
         UnresolvedTypeRefToken {
             indirection: Indirection(0),
+            mutability: 0,
+            nullability: 0,
+            heap: 0,
             inner: self,
         }
     }
@@ -132,7 +185,7 @@ impl FullNameToken {
 }
 
 /// A token representing a name (e.g `a`, `a.b`, `a::b`)
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum FullNameTokens {
     Name { name: SimpleNameToken, containing_class: Option<SimpleNameToken> },
     StaticAccess { inner: Box<FullNameToken>, name: SimpleNameToken },
@@ -172,6 +225,7 @@ enum TempEvaluableTokensOne {
     ),
     StaticFunctionCall(SimpleNameToken, Vec<EvaluableToken>),
     DynamicFunctionCall(SimpleNameToken, Vec<EvaluableToken>),
+    Index(Location, EvaluableToken),
 }
 
 /// An `EvaluableToken` or `OperatorToken`
@@ -186,20 +240,54 @@ pub fn parse_full_name<'a>(
     s: Span<'a>,
     containing_class: Option<&SimpleNameToken>,
 ) -> ParseResult<'a, Span<'a>, UnresolvedTypeRefToken> {
-    let mut indirection: usize = 0;
+    // Whether each reference is mutable, nullable and to a heap array, outermost first
+    let mut references = Vec::new();
     let mut s = s;
     // Add indirection based on number of &s
     loop {
         let (ns, _) = discard_ignored(s)?;
 
-        if let Ok((ns, _)) = char::<Span, ErrorTree>('&')(ns) {
-            indirection += 1;
-            s = ns;
-        } else {
-            s = ns;
-            break;
+        // `?&` is a nullable reference
+        let (ns, nullable) = match char::<Span, ErrorTree>('?')(ns) {
+            Ok((ns, _)) => (ns, true),
+            Err(_) => (ns, false),
+        };
+
+        match char::<Span, ErrorTree>('&')(ns) {
+            Ok((ns, _)) => {
+                // `&mut` is a mutable reference, which may be followed directly by `[`
+                let mut_end = alt((require_ignored, map(peek(char('[')), |_| ())));
+                let (ns, mutable) = match (tag::<&str, Span, ErrorTree>("mut"), mut_end).parse(ns) {
+                    Ok((ns, _)) => (ns, true),
+                    Err(_) => (ns, false),
+                };
+                // `&[T]` is a reference to a heap array, closed after the name
+                let (ns, _) = discard_ignored(ns)?;
+                let (ns, heap) = match char::<Span, ErrorTree>('[')(ns) {
+                    Ok((ns, _)) => (ns, true),
+                    Err(_) => (ns, false),
+                };
+                references.push((mutable, nullable, heap));
+                s = ns;
+            }
+            Err(e) if nullable => return Err(e),
+            Err(_) => {
+                s = ns;
+                break;
+            }
         }
     }
+    let indirection = references.len();
+    let (mutability, nullability, heap) = references.iter().rev().enumerate().fold(
+        (0, 0, 0),
+        |(mutability, nullability, heap), (i, (mutable, nullable, array))| {
+            (
+                mutability | ((*mutable as u64) << i),
+                nullability | ((*nullable as u64) << i),
+                heap | ((*array as u64) << i),
+            )
+        },
+    );
 
     let (s, _) = discard_ignored(s)?;
     let (s, section) = parse_simple_name(s)?;
@@ -241,10 +329,20 @@ pub fn parse_full_name<'a>(
         s = ns;
     }
 
+    // Close heap array references
+    for _ in 0..heap.count_ones() {
+        let (ns, _) = discard_ignored(s)?;
+        let (ns, _) = char(']')(ns)?;
+        s = ns;
+    }
+
     Ok((
         s,
         UnresolvedTypeRefToken {
             indirection: Indirection(indirection),
+            mutability,
+            nullability,
+            heap,
             inner: current,
         },
     ))
@@ -314,8 +412,67 @@ pub fn parse_evaluable<'a, 'b>(
             break;
         }
 
+        // `delete` takes the rest of the evaluable
+        if evaluables.is_empty() && test_parse_delete(ns) {
+            return parse_delete(ns, containing_class, semicolon_terminated);
+        }
+
+        // Parse heap arrays
+        let ns = if test_parse_heap_array(ns) {
+            let t_span = ns;
+            let (ns, heap_array) = parse_heap_array(ns, containing_class, semicolon_terminated)?;
+            let takes_rest = heap_array.from().is_some();
+            evaluables.push((
+                TempEvaluableTokensOne::EvaluableToken(EvaluableToken {
+                    location: heap_array.location().clone(),
+                    token: EvaluableTokens::HeapArray(heap_array),
+                }),
+                t_span,
+            ));
+            // `from` takes the rest of the evaluable
+            if takes_rest {
+                s = ns;
+                break;
+            }
+            ns
+        }
+        // Parse if expressions
+        else if test_parse_if_expression(ns) {
+            let t_span = ns;
+            let (ns, if_expression) = parse_if_expression(ns, containing_class)?;
+            evaluables.push((
+                TempEvaluableTokensOne::EvaluableToken(EvaluableToken {
+                    location: if_expression.location().clone(),
+                    token: EvaluableTokens::If(if_expression),
+                }),
+                t_span,
+            ));
+            ns
+        }
+        // Parse blocks used as values
+        else if char::<Span, ErrorTree>('{')(ns).is_ok() {
+            let (ns, inner) = parse_default_terminator_content(ns, &BRACE_TERMINATOR)?;
+            let (_, block) = parse_block(inner, containing_class)?;
+            evaluables.push((
+                TempEvaluableTokensOne::EvaluableToken(EvaluableToken {
+                    location: block.location().clone(),
+                    token: EvaluableTokens::Block(block),
+                }),
+                inner,
+            ));
+            ns
+        }
+        // Parse indexing e.g. `a[i]`
+        else if !evaluables.is_empty() && char::<Span, ErrorTree>('[')(ns).is_ok() {
+            let location = Location::from_span(&ns);
+            let (ns, inner) =
+                parse_terminator(ns, &SQUARE_BRACKET_TERMINATOR, &DEFAULT_TERMINATORS)?;
+            let (_, index) = parse_evaluable(inner, containing_class, false)?;
+            evaluables.push((TempEvaluableTokensOne::Index(location, index), inner));
+            ns
+        }
         // Recursively parse bracketed sections
-        let ns = if let Ok((ns, inner)) = parse_default_terminator_content(s, &BRACKET_TERMINATOR) {
+        else if let Ok((ns, inner)) = parse_default_terminator_content(ns, &BRACKET_TERMINATOR) {
             let (_, evaluable) = parse_evaluable(inner, containing_class, false)?;
             evaluables.push((TempEvaluableTokensOne::EvaluableToken(evaluable), inner));
             ns
@@ -453,6 +610,33 @@ pub fn parse_evaluable<'a, 'b>(
                     )),
                 }
             }
+            TempEvaluableTokensOne::Index(location, index) => {
+                let Some((token, t2_span)) = new_evaluables.pop() else {
+                    return Err(create_custom_error(
+                        "Must have something to index".to_string(),
+                        t1_span,
+                    ));
+                };
+
+                match token {
+                    TempEvaluableTokensTwo::Operator(_) => {
+                        return Err(create_custom_error(
+                            "Cannot index an operator".to_string(),
+                            t2_span,
+                        ))
+                    }
+                    TempEvaluableTokensTwo::EvaluableToken(e) => new_evaluables.push((
+                        TempEvaluableTokensTwo::EvaluableToken(EvaluableToken {
+                            location,
+                            token: EvaluableTokens::Index {
+                                parent: b!(e),
+                                index: b!(index),
+                            },
+                        }),
+                        t1_span,
+                    )),
+                }
+            }
             TempEvaluableTokensOne::FunctionCall(n, c, a) => new_evaluables.push((
                 TempEvaluableTokensTwo::EvaluableToken(EvaluableToken {
                     location: n.location().clone(),
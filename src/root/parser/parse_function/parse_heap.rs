@@ -0,0 +1,92 @@
+use b_box::b;
+use derive_getters::{Dissolve, Getters};
+use nom::character::complete::char;
+use nom::combinator::peek;
+use nom::sequence::Tuple;
+use nom_supreme::tag::complete::tag;
+
+use crate::root::parser::location::Location;
+use crate::root::parser::parse::{ParseResult, Span};
+use crate::root::parser::parse_blocks::{
+    parse_terminator, DEFAULT_TERMINATORS, SQUARE_BRACKET_TERMINATOR,
+};
+use crate::root::parser::parse_function::parse_evaluable::{
+    parse_evaluable, parse_full_name, EvaluableToken, EvaluableTokens, UnresolvedTypeRefToken,
+};
+use crate::root::parser::parse_name::SimpleNameToken;
+use crate::root::parser::parse_util::{discard_ignored, require_ignored};
+
+/// Token representing a heap allocated array e.g. `new [int; n]`, or the reallocation of an
+/// existing one e.g. `new [int; n] from p`
+#[derive(Debug, Clone, Dissolve, Getters)]
+pub struct HeapArrayToken {
+    location: Location,
+    element: UnresolvedTypeRefToken,
+    length: Box<EvaluableToken>,
+    /// Existing allocation to grow or shrink
+    from: Option<Box<EvaluableToken>>,
+}
+
+/// Tests whether the text should be parsed as a heap array
+pub fn test_parse_heap_array(s: Span) -> bool {
+    (tag("new"), discard_ignored, peek(char('[')))
+        .parse(s)
+        .is_ok()
+}
+
+/// Parses a heap array e.g. `new [int; n]`. As `from` takes the rest of the evaluable, if it is
+/// present the rest of `s` will also be consumed
+pub fn parse_heap_array<'a>(
+    s: Span<'a>,
+    containing_class: Option<&SimpleNameToken>,
+    semicolon_terminated: bool,
+) -> ParseResult<'a, Span<'a>, HeapArrayToken> {
+    let (s, l) = tag("new")(s)?;
+    let (s, _) = discard_ignored(s)?;
+    let (s, contents) = parse_terminator(s, &SQUARE_BRACKET_TERMINATOR, &DEFAULT_TERMINATORS)?;
+
+    // Element type and length
+    let (contents, element) = parse_full_name(contents, containing_class)?;
+    let (contents, _) = discard_ignored(contents)?;
+    let (contents, _) = char(';')(contents)?;
+    let (_, length) = parse_evaluable(contents, containing_class, false)?;
+
+    let (ns, _) = discard_ignored(s)?;
+    let (s, from) = if let Ok((ns, _)) = (tag("from"), require_ignored).parse(ns) {
+        let (ns, from) = parse_evaluable(ns, containing_class, semicolon_terminated)?;
+        (ns, Some(b!(from)))
+    } else {
+        (s, None)
+    };
+
+    Ok((
+        s,
+        HeapArrayToken {
+            location: Location::from_span(&l),
+            element,
+            length: b!(length),
+            from,
+        },
+    ))
+}
+
+/// Tests whether the text should be parsed as a delete
+pub fn test_parse_delete(s: Span) -> bool {
+    (tag("delete"), require_ignored).parse(s).is_ok()
+}
+
+/// Parses a delete e.g. `delete p`, consuming the rest of the evaluable
+pub fn parse_delete<'a>(
+    s: Span<'a>,
+    containing_class: Option<&SimpleNameToken>,
+    semicolon_terminated: bool,
+) -> ParseResult<'a, Span<'a>, EvaluableToken> {
+    let (s, l) = tag("delete")(s)?;
+    let (s, _) = require_ignored(s)?;
+    let (s, pointer) = parse_evaluable(s, containing_class, semicolon_terminated)?;
+
+    Ok((
+        s,
+        EvaluableToken::new(Location::from_span(&l), EvaluableTokens::Delete(b!(pointer))),
+    ))
+}
@@ -1,18 +1,24 @@
+use b_box::b;
+
+use crate::root::errors::parser_errors::create_custom_error;
 use crate::root::parser::location::Location;
 use crate::root::parser::parse::{ErrorTree, ParseResult, Span};
 use crate::root::parser::parse_blocks::{
     parse_default_terminator_content, BRACE_TERMINATOR, BRACKET_TERMINATOR,
 };
+use crate::root::parser::parse_function::parse_block::{parse_block, BlockToken};
 use crate::root::parser::parse_function::parse_evaluable::{parse_evaluable, EvaluableToken};
 use crate::root::parser::parse_function::parse_line::{parse_lines, LineTestFn, LineTokens};
 use crate::root::parser::parse_name::SimpleNameToken;
 use crate::root::parser::parse_util::{discard_ignored, require_ignored};
 use derive_getters::Getters;
+use nom::character::complete::char;
+use nom::combinator::peek;
 use nom::sequence::Tuple;
 use nom_supreme::tag::complete::tag;
 
 /// Token holding an if statement
-#[derive(Debug, Getters)]
+#[derive(Debug, Clone, Getters)]
 pub struct IfToken {
     #[allow(dead_code)]
     location: Location,
@@ -22,6 +28,17 @@ pub struct IfToken {
     else_contents: Option<Vec<LineTokens>>,
 }
 
+/// Token holding an if used as a value. Every branch is a block ending in a value so an else is
+/// required
+#[derive(Debug, Clone, Getters)]
+pub struct IfExpressionToken {
+    location: Location,
+    if_condition: Box<EvaluableToken>,
+    if_block: BlockToken,
+    elif_condition_blocks: Vec<(EvaluableToken, BlockToken)>,
+    else_block: BlockToken,
+}
+
 /// Checks if the line should be parsed as an if statement
 pub fn test_parse_if<'b>(s: Span<'_>) -> ParseResult<Span, LineTestFn<'_, 'b>> {
     match tag("if")(s) {
@@ -113,3 +130,77 @@ pub fn parse_if<'a>(
         },
     ))
 }
+
+/// Checks if an evaluable section should be parsed as an if expression
+pub fn test_parse_if_expression(s: Span) -> bool {
+    (tag("if"), discard_ignored, peek(char('(')))
+        .parse(s)
+        .is_ok()
+}
+
+/// Parses an if expression e.g. `if (a) { 1 } else { 2 }`
+pub fn parse_if_expression<'a>(
+    s: Span<'a>,
+    containing_class: Option<&SimpleNameToken>,
+) -> ParseResult<'a, Span<'a>, IfExpressionToken> {
+    let (s, l) = tag("if")(s)?; // If
+    let (s, _) = discard_ignored(s)?;
+
+    // Parse condition
+    let (s, content) = parse_default_terminator_content(s, &BRACKET_TERMINATOR)?;
+    let (_, if_condition) = parse_evaluable(content, containing_class, false)?;
+    let (s, _) = discard_ignored(s)?;
+
+    // Parse block
+    let (s, contents) = parse_default_terminator_content(s, &BRACE_TERMINATOR)?;
+    let (_, if_block) = parse_block(contents, containing_class)?;
+
+    // Parse elifs
+    let mut elifs = Vec::new();
+    let mut s = s;
+
+    loop {
+        let (ns, _) = discard_ignored(s)?;
+
+        let Ok((ns, _)) = tag::<_, _, ErrorTree>("else")(ns) else {
+            return Err(create_custom_error(
+                "Expected an else branch as an if used as a value must always produce a value"
+                    .to_string(),
+                l,
+            ));
+        };
+
+        let (ns, condition) = if let Ok((ns, _)) = (require_ignored, tag("if")).parse(ns) {
+            let (ns, _) = discard_ignored(ns)?;
+            let (ns, content) = parse_default_terminator_content(ns, &BRACKET_TERMINATOR)?;
+            let (_, condition) = parse_evaluable(content, containing_class, false)?;
+            (ns, Some(condition))
+        } else {
+            (ns, None)
+        };
+
+        let (ns, _) = discard_ignored(ns)?;
+
+        let (ns, contents) = parse_default_terminator_content(ns, &BRACE_TERMINATOR)?;
+        let (_, block) = parse_block(contents, containing_class)?;
+
+        // ? Handle else if
+        if let Some(condition) = condition {
+            elifs.push((condition, block));
+        } else {
+            // ? Handle else
+            return Ok((
+                ns,
+                IfExpressionToken {
+                    location: Location::from_span(&l),
+                    if_condition: b!(if_condition),
+                    if_block,
+                    elif_condition_blocks: elifs,
+                    else_block: block,
+                },
+            ));
+        }
+
+        s = ns;
+    }
+}
@@ -1,5 +1,5 @@
 use crate::root::parser::location::Location;
-use crate::root::parser::parse::{ParseResult, Span};
+use crate::root::parser::parse::{ErrorTree, ParseResult, Span};
 use crate::root::parser::parse_function::parse_evaluable::{
     parse_evaluable, parse_full_name, EvaluableToken, UnresolvedTypeRefToken,
 };
@@ -11,14 +11,16 @@ use nom::character::complete::char;
 use nom::sequence::Tuple;
 use nom_supreme::tag::complete::tag;
 
-// Token holding an initialiser
-#[derive(Debug, Getters)]
+// Token holding an initialiser. The value may be omitted e.g. `let a: int;`. Variables are
+// immutable unless declared with `let mut`
+#[derive(Debug, Clone, Getters)]
 pub struct InitialisationToken {
     #[allow(dead_code)]
     location: Location,
+    mutable: bool,
     name: SimpleNameToken,
     type_name: UnresolvedTypeRefToken,
-    value: EvaluableToken,
+    value: Option<EvaluableToken>,
 }
 
 /// Test if line should be parsed as initialiser
@@ -39,6 +41,12 @@ pub fn parse_initialisation<'a>(
     let (s, l) = tag("let")(s)?;
     let (s, _) = require_ignored(s)?;
 
+    // Parse mutability
+    let (s, mutable) = match (tag("mut"), require_ignored).parse(s) {
+        Ok((s, _)) => (s, true),
+        Err(_) => (s, false),
+    };
+
     // Parse variable name
     let (s, name) = parse_simple_name(s)?;
     let (s, _) = discard_ignored(s)?;
@@ -48,16 +56,24 @@ pub fn parse_initialisation<'a>(
     // Parse type
     let (s, type_name) = parse_full_name(s, containing_class)?;
     let (s, _) = discard_ignored(s)?;
-    let (s, _) = char('=')(s)?;
-    let (s, _) = discard_ignored(s)?;
 
-    // Parse value
-    let (s, value) = parse_evaluable(s, containing_class, true)?;
+    // Declared without a value
+    let (s, value) = if let Ok((s, _)) = char::<_, ErrorTree>(';')(s) {
+        (s, None)
+    } else {
+        let (s, _) = char('=')(s)?;
+        let (s, _) = discard_ignored(s)?;
+
+        // Parse value
+        let (s, value) = parse_evaluable(s, containing_class, true)?;
+        (s, Some(value))
+    };
 
     Ok((
         s,
         InitialisationToken {
             location: Location::from_span(&l),
+            mutable,
             name,
             type_name,
             value,
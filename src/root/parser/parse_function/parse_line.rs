@@ -17,7 +17,7 @@ use crate::root::parser::parse_name::SimpleNameToken;
 use crate::root::parser::parse_util::discard_ignored;
 
 /// A token holding all types of lines
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum LineTokens {
     Initialisation(InitialisationToken),
     If(IfToken),
@@ -7,24 +7,31 @@ use crate::root::shared::common::TypeID;
 use derive_getters::{Dissolve, Getters};
 use nom::branch::alt;
 use nom::bytes::complete::tag;
+use nom::character::complete::satisfy;
+use nom::combinator::not;
+use nom::sequence::terminated;
 
-#[derive(Debug, Dissolve, Getters)]
+#[derive(Debug, Clone, Dissolve, Getters)]
 pub struct LiteralToken {
     location: Location,
     literal: LiteralTokens,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum LiteralTokens {
     Bool(bool),
     Int(i128),
+    /// A null reference. Has no type of its own - coerces to any reference type
+    Null,
 }
 
 impl LiteralTokens {
-    pub fn default_type(&self) -> TypeID {
+    /// Returns the type of the literal when no type is expected. `None` for `null`
+    pub fn default_type(&self) -> Option<TypeID> {
         match self {
-            LiteralTokens::Bool(_) => BoolType::id(),
-            LiteralTokens::Int(_) => IntType::id(),
+            LiteralTokens::Bool(_) => Some(BoolType::id()),
+            LiteralTokens::Int(_) => Some(IntType::id()),
+            LiteralTokens::Null => None,
         }
     }
 }
@@ -36,6 +43,14 @@ pub fn parse_literal(s: Span) -> ParseResult<Span, LiteralToken> {
     let (ns, l) = alt((
         |x| tag("true")(x).map(|(s, _)| (s, LiteralTokens::Bool(true))),
         |x| tag("false")(x).map(|(s, _)| (s, LiteralTokens::Bool(false))),
+        // Don't match names starting with `null`
+        |x| {
+            terminated(
+                tag("null"),
+                not(satisfy(|c| c.is_alphanumeric() || c == '_')),
+            )(x)
+            .map(|(s, _)| (s, LiteralTokens::Null))
+        },
         |x| nom::character::complete::i128(x).map(|(s, i)| (s, LiteralTokens::Int(i))),
     ))(s)?;
 
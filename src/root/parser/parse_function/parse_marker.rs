@@ -16,7 +16,7 @@ use crate::root::parser::parse_util::{discard_ignored, require_ignored};
 
 /// Debug marker token
 #[cfg(debug_assertions)]
-#[derive(Debug, Getters)]
+#[derive(Debug, Clone, Getters)]
 pub struct MarkerToken {
     value: String,
 }
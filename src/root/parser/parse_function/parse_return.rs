@@ -10,7 +10,7 @@ use nom::sequence::Tuple;
 use nom_supreme::tag::complete::tag;
 
 /// Token representing a return statement with location
-#[derive(Debug, Getters)]
+#[derive(Debug, Clone, Getters)]
 pub struct ReturnToken {
     location: Location,
     return_value: Option<EvaluableToken>,
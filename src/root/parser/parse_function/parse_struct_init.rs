@@ -4,7 +4,7 @@ use crate::root::parser::parse_blocks::{
     parse_default_terminator_content, take_until_or_end_discard_smart, BRACE_TERMINATOR,
 };
 use crate::root::parser::parse_function::parse_evaluable::{
-    parse_evaluable, parse_full_name, EvaluableToken, UnresolvedTypeRefToken,
+    parse_evaluable, parse_full_name, EvaluableToken, EvaluableTokens, UnresolvedTypeRefToken,
 };
 use crate::root::parser::parse_name::{parse_simple_name, SimpleNameToken};
 use crate::root::parser::parse_util::discard_ignored;
@@ -14,7 +14,9 @@ use nom::bytes::complete::tag;
 use nom::character::streaming::char;
 
 /// Struct initialisation token with location
-#[derive(Debug, Dissolve, Getters)]
+///
+/// Attributes can be given in any order and attributes with defaults can be omitted
+#[derive(Debug, Clone, Dissolve, Getters)]
 pub struct StructInitToken {
     location: Location,
     name: UnresolvedTypeRefToken,
@@ -51,6 +53,19 @@ pub fn parse_struct_init<'a>(
         let (ns, _) = discard_ignored(ns)?;
         let (ns, name) = parse_simple_name(ns)?;
         let (ns, _) = discard_ignored(ns)?;
+
+        // Shorthand e.g. `Node { val }` is equivalent to `Node { val: val }`
+        if ns.is_empty() || char::<Span, ErrorTree>(',')(ns).is_ok() {
+            let (ns, _) = take_until_or_end_discard_smart(ns, ",")?;
+            let eval = EvaluableToken::new(
+                name.location().clone(),
+                EvaluableTokens::Name(name.clone(), containing_class.cloned()),
+            );
+            contents.push((name, eval));
+            s = ns;
+            continue;
+        }
+
         let (ns, _) = char(':')(ns)?;
         let (ns, _) = discard_ignored(ns)?;
 
@@ -12,7 +12,7 @@ use nom::sequence::Tuple;
 use nom_supreme::tag::complete::tag;
 
 /// Token representing a while statement with location
-#[derive(Debug, Getters)]
+#[derive(Debug, Clone, Getters)]
 pub struct WhileToken {
     #[allow(dead_code)]
     location: Location,
@@ -4,7 +4,7 @@ use crate::root::parser::parse_blocks::{parse_default_terminator_content, BRACE_
 use crate::root::parser::parse_function::{parse_function, FunctionToken};
 use crate::root::parser::parse_name::{parse_simple_name, SimpleNameToken};
 use crate::root::parser::parse_toplevel::{TopLevelTokens, ToplevelTestFn};
-use crate::root::parser::parse_util::{discard_ignored, require_ignored};
+use crate::root::parser::parse_util::{discard_ignored, discard_ignored_docs, require_ignored};
 use derive_getters::{Dissolve, Getters};
 use nom::sequence::Tuple;
 use nom::Parser;
@@ -45,16 +45,17 @@ pub fn parse_impl(s: Span) -> ParseResult<Span, ImplToken> {
     let mut functions = Vec::new();
     let mut c = contents;
     loop {
-        let (cs, _) = discard_ignored(c)?;
+        let (cs, docs) = discard_ignored_docs(c)?;
         if cs.is_empty() {
             break;
         }
 
-        let (cs, function) = parse_function(
+        let (cs, mut function) = parse_function(
             cs,
             // ? Pass class name (type) to function in case needed for self
             Some(&name),
         )?;
+        function.set_docs(docs);
 
         functions.push(function);
         c = cs;
@@ -24,7 +24,8 @@ pub fn parse_imports<'a>(
             is_use = false;
             tag::<_, _, ErrorTree>("import")(ns)
         }) else {
-            return Ok((ns, found_paths)); // No more imports
+            // No more imports - leave ignored text as it may contain doc comments
+            return Ok((s, found_paths));
         };
 
         // Get path
@@ -1,21 +1,24 @@
 use nom::character::complete::char;
+use nom::sequence::Tuple;
+use nom_supreme::tag::complete::tag;
 
 use crate::root::parser::parse::{ErrorTree, ParseResult, Span};
 use crate::root::parser::parse_function::parse_evaluable::{
     parse_full_name, UnresolvedTypeRefToken,
 };
 use crate::root::parser::parse_name::{parse_simple_name, SimpleNameToken};
-use crate::root::parser::parse_util::discard_ignored;
+use crate::root::parser::parse_util::{discard_ignored, require_ignored};
 use crate::root::shared::common::Indirection;
 
 pub type Parameters = Vec<(SimpleNameToken, UnresolvedTypeRefToken)>;
 
-/// The type of self i.e. whether self is passed by value, by reference, or isn't used
+/// The type of self i.e. whether self is passed by value, by (mutable) reference, or isn't used
 #[derive(Debug, Copy, Clone)]
 pub enum SelfType {
     None,
     CopySelf,
     RefSelf,
+    MutRefSelf,
 }
 
 impl SelfType {
@@ -23,6 +26,11 @@ impl SelfType {
     pub fn uses_self(&self) -> bool {
         !matches!(&self, SelfType::None)
     }
+
+    /// Returns `true` if self is passed by reference
+    pub fn is_reference(&self) -> bool {
+        matches!(&self, SelfType::RefSelf | SelfType::MutRefSelf)
+    }
 }
 
 /// Parses a parameter list
@@ -36,13 +44,22 @@ pub fn parse_parameters<'a>(
 
     let mut has_self = SelfType::None;
     let mut has_ref = false;
+    let mut has_mut = false;
 
     while !s.is_empty() {
         // Handle reference (for self) if no previous parameters
         let ns = if parameters.is_empty() {
             if let Ok((ns, _)) = char::<Span, ErrorTree>('&')(s) {
                 has_ref = true;
-                ns
+                // `&mut self`
+                if let Ok((ns, _)) =
+                    (tag::<&str, Span, ErrorTree>("mut"), require_ignored).parse(ns)
+                {
+                    has_mut = true;
+                    ns
+                } else {
+                    ns
+                }
             } else {
                 s
             }
@@ -56,7 +73,9 @@ pub fn parse_parameters<'a>(
         let (ns, p_type) =
             // If self, get self type rather than specified type
             if allow_self.is_some() && parameters.is_empty() && *name.name() == "self" {
-                has_self = if has_ref {
+                has_self = if has_mut {
+                    SelfType::MutRefSelf
+                } else if has_ref {
                     SelfType::RefSelf
                 } else {
                     SelfType::CopySelf
@@ -74,6 +93,11 @@ pub fn parse_parameters<'a>(
                     name.location().clone(),
                     i,
                 );
+                let p_type = if has_mut {
+                    p_type.with_outer_mutable()
+                } else {
+                    p_type
+                };
                 (ns, p_type)
             } else {
                 let (ns, _) = discard_ignored(ns)?;
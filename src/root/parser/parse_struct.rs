@@ -1,23 +1,39 @@
 use crate::root::parser::location::Location;
-use crate::root::parser::parse::{ParseResult, Span};
-use crate::root::parser::parse_blocks::{parse_default_terminator_content, BRACE_TERMINATOR};
+use crate::root::errors::parser_errors::create_custom_error;
+use crate::root::parser::parse::{ErrorTree, ParseResult, Span};
+use crate::root::parser::parse_blocks::{
+    parse_default_terminator_content, take_until_or_end_discard_smart, BRACE_TERMINATOR,
+};
+use crate::root::parser::parse_function::parse_evaluable::{
+    parse_evaluable, parse_full_name, EvaluableToken, UnresolvedTypeRefToken,
+};
 use crate::root::parser::parse_name::{parse_simple_name, SimpleNameToken};
-use crate::root::parser::parse_parameters::{parse_parameters, Parameters};
 use crate::root::parser::parse_toplevel::{TopLevelTokens, ToplevelTestFn};
-use crate::root::parser::parse_util::{discard_ignored, require_ignored};
+use crate::root::parser::parse_util::{discard_ignored, discard_ignored_docs, require_ignored};
 use crate::root::shared::common::TypeID;
 use derive_getters::{Dissolve, Getters};
 use nom::sequence::Tuple;
+use nom::character::complete::char;
 use nom::Parser;
 use nom_supreme::tag::complete::tag;
 
+/// Struct attributes with their names, types, default values and doc comments
+pub type StructAttributes = Vec<(
+    SimpleNameToken,
+    UnresolvedTypeRefToken,
+    Option<EvaluableToken>,
+    Option<String>,
+)>;
+
 /// Token representing a struct with location
 #[derive(Debug, Getters, Dissolve)]
 pub struct StructToken {
     location: Location,
     name: SimpleNameToken,
-    attributes: Parameters,
+    attributes: StructAttributes,
     id: Option<TypeID>,
+    /// Text of the doc comments above the struct
+    docs: Option<String>,
 }
 
 impl StructToken {
@@ -25,6 +41,11 @@ impl StructToken {
     pub fn set_id(&mut self, id: TypeID) {
         self.id = Some(id);
     }
+
+    /// Sets the doc comments of the struct
+    pub fn set_docs(&mut self, docs: Option<String>) {
+        self.docs = docs;
+    }
 }
 
 /// Tests if line should be parsed as a struct
@@ -45,8 +66,7 @@ pub fn parse_struct(s: Span) -> ParseResult<Span, StructToken> {
     let (s, name) = parse_simple_name(s)?;
     let (s, _) = discard_ignored(s)?;
     let (s, contents) = parse_default_terminator_content(s, &BRACE_TERMINATOR)?;
-    // Parse attributes as parameters
-    let (_, (attributes, _)) = parse_parameters(contents, None)?;
+    let (_, attributes) = parse_struct_attributes(contents)?;
 
     Ok((
         s,
@@ -55,6 +75,44 @@ pub fn parse_struct(s: Span) -> ParseResult<Span, StructToken> {
             name,
             attributes,
             id: None,
+            docs: None,
         },
     ))
 }
+
+/// Parses struct attributes e.g. `val: int, count: int = 0`
+fn parse_struct_attributes(s: Span) -> ParseResult<Span, StructAttributes> {
+    let mut s = s;
+    let mut attributes = Vec::new();
+
+    // Ignored text is left at the start of each attribute as it may contain doc comments
+    while !discard_ignored(s)?.0.is_empty() {
+        let (ns, attribute) = take_until_or_end_discard_smart(s, ",")?;
+
+        let (a, docs) = discard_ignored_docs(attribute)?;
+        let (a, name) = parse_simple_name(a)?;
+        let (a, _) = discard_ignored(a)?;
+        let (a, _) = char(':')(a)?;
+        let (a, type_name) = parse_full_name(a, None)?;
+        let (a, _) = discard_ignored(a)?;
+
+        // Default value
+        let default = if let Ok((a, _)) = char::<Span, ErrorTree>('=')(a) {
+            let (_, default) = parse_evaluable(a, None, false)?;
+            Some(default)
+        } else if !a.is_empty() {
+            return Err(create_custom_error(
+                "Expected ',' or '=' after attribute type".to_string(),
+                a,
+            ));
+        } else {
+            None
+        };
+
+        attributes.push((name, type_name, default, docs));
+
+        s = ns;
+    }
+
+    Ok((s, attributes))
+}
@@ -2,15 +2,18 @@ use crate::root::parser::parse::{ParseResult, Span};
 use crate::root::parser::parse_function::{test_parse_function, FunctionToken};
 use crate::root::parser::parse_impl::{test_parse_impl, ImplToken};
 use crate::root::parser::parse_struct::{test_parse_struct, StructToken};
-use crate::root::parser::parse_util::discard_ignored;
+use crate::root::parser::parse_type_alias::{test_parse_type_alias, TypeAliasToken};
+use crate::root::parser::parse_util::discard_ignored_docs;
 use nom::branch::alt;
 use nom::Parser;
 
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum TopLevelTokens {
     Struct(StructToken),
     Impl(ImplToken),
     Function(FunctionToken),
+    TypeAlias(TypeAliasToken),
 }
 
 pub type ToplevelTestFn<'a> = fn(Span<'a>) -> ParseResult<Span<'a>, TopLevelTokens>;
@@ -23,17 +26,29 @@ pub fn parse_toplevel(s: Span) -> ParseResult<Span, Vec<TopLevelTokens>> {
     loop {
         let ns = s;
 
-        let (ns, _) = discard_ignored(ns)?;
+        let (ns, docs) = discard_ignored_docs(ns)?;
 
         if ns.is_empty() {
             return Ok((ns, tokens));
         }
 
-        // Parse either a struct, impl, or function
-        let (_, parse_fn) =
-            alt((test_parse_struct, test_parse_impl, test_parse_function)).parse(ns)?;
-
-        let (ns, token) = parse_fn(ns)?;
+        // Parse either a struct, impl, function, or type alias
+        let (_, parse_fn) = alt((
+            test_parse_struct,
+            test_parse_impl,
+            test_parse_function,
+            test_parse_type_alias,
+        ))
+        .parse(ns)?;
+
+        let (ns, mut token) = parse_fn(ns)?;
+
+        // Attach doc comments to the items that can have them
+        match &mut token {
+            TopLevelTokens::Struct(st) => st.set_docs(docs),
+            TopLevelTokens::Function(ft) => ft.set_docs(docs),
+            TopLevelTokens::Impl(_) | TopLevelTokens::TypeAlias(_) => {}
+        }
 
         tokens.push(token);
 
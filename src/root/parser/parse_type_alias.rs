@@ -0,0 +1,54 @@
+use crate::root::parser::location::Location;
+use crate::root::parser::parse::{ParseResult, Span};
+use crate::root::parser::parse_function::parse_evaluable::{
+    parse_full_name, UnresolvedTypeRefToken,
+};
+use crate::root::parser::parse_name::{parse_simple_name, SimpleNameToken};
+use crate::root::parser::parse_toplevel::{TopLevelTokens, ToplevelTestFn};
+use crate::root::parser::parse_util::{discard_ignored, require_ignored};
+use derive_getters::{Dissolve, Getters};
+use nom::character::complete::char;
+use nom::sequence::Tuple;
+use nom::Parser;
+use nom_supreme::tag::complete::tag;
+
+/// Token representing a type alias e.g. `type NodePtr = &Node;`
+#[derive(Debug, Getters, Dissolve)]
+pub struct TypeAliasToken {
+    location: Location,
+    name: SimpleNameToken,
+    target: UnresolvedTypeRefToken,
+}
+
+/// Tests if line should be parsed as a type alias
+pub fn test_parse_type_alias(s: Span<'_>) -> ParseResult<'_, Span<'_>, ToplevelTestFn<'_>> {
+    match (tag("type"), require_ignored).parse(s) {
+        Ok(_) => Ok((s, |x| {
+            parse_type_alias(x).map(|(s, x)| (s, TopLevelTokens::TypeAlias(x)))
+        })),
+        Err(e) => Err(e),
+    }
+}
+
+/// Parses line as a type alias
+pub fn parse_type_alias(s: Span) -> ParseResult<Span, TypeAliasToken> {
+    let location = Location::from_span(&s);
+    let (s, _) = tag("type").parse(s)?;
+    let (s, _) = require_ignored(s)?;
+    let (s, name) = parse_simple_name(s)?;
+    let (s, _) = discard_ignored(s)?;
+    let (s, _) = char('=')(s)?;
+    let (s, _) = discard_ignored(s)?;
+    let (s, target) = parse_full_name(s, None)?;
+    let (s, _) = discard_ignored(s)?;
+    let (s, _) = char(';')(s)?;
+
+    Ok((
+        s,
+        TypeAliasToken {
+            location,
+            name,
+            target,
+        },
+    ))
+}
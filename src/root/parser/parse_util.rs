@@ -37,6 +37,41 @@ pub fn discard_ignored(s: Span) -> ParseResult<Span, ()> {
     Ok((s, ()))
 }
 
+/// Discards whitespace and comments, returning the text of any doc comments found with one line per
+/// comment
+pub fn discard_ignored_docs(s: Span) -> ParseResult<Span, Option<String>> {
+    let mut s = s;
+    let mut docs: Option<String> = None;
+    // Ensures no infinite loop
+    let mut found = true;
+    while found {
+        found = false;
+        // Doc comments
+        if let Ok((ns, doc)) = parse_comments::parse_doc_comment(s) {
+            let docs = docs.get_or_insert_with(String::new);
+            // Text is usually separated from the `///` by a space
+            *docs += doc.strip_prefix(' ').unwrap_or(&doc);
+            docs.push('\n');
+            s = ns;
+            found = true;
+        }
+
+        // Comments
+        if let Ok((ns, _)) = parse_comments::parse_comment(s) {
+            s = ns;
+            found = true;
+        }
+
+        // Whitespace
+        if let Ok((ns, _)) = multispace1::<_, ErrorTree>(s) {
+            s = ns;
+            found = true;
+        }
+    }
+
+    Ok((s, docs))
+}
+
 /// Discards whitespace and comments requiring that whitespace/comments exist
 /// e.g. `let a = 1` requires ignored between `let` and `a`
 pub fn require_ignored(s: Span) -> ParseResult<Span, ()> {
@@ -6,12 +6,15 @@ use crate::root::utils::identify_first_last::IdentifyLast;
 use derive_getters::Getters;
 use derive_new::new;
 use nom::character::complete::anychar;
+use itertools::Itertools;
 use nom::InputTake;
 use std::collections::HashMap;
 use std::fs;
+use std::io;
+use std::path::Path;
 
 /// ID corresponding to a file
-#[derive(Hash, Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Hash, Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub struct FileID(usize);
 
 impl FileID {
@@ -53,6 +56,9 @@ pub struct Scope {
     files_used: Vec<(FileID, Location)>,
     files_imported: Vec<(FileID, Location)>,
     folders_imported: Vec<(FolderID, Location)>,
+    /// File implicitly used after all other files
+    #[new(default)]
+    prelude: Option<FileID>,
 }
 
 /// Represents a file of code with a scope
@@ -67,10 +73,17 @@ pub struct CodeFile {
 pub struct PathStorage {
     folders: Vec<CodeFolder>,
     files: Vec<CodeFile>,
+    /// Library folders searched, in order, when a path can't be found relative to its usual base
+    search_paths: Vec<FolderID>,
+    /// File whose contents are implicitly used by every other file
+    prelude: Option<FileID>,
+    /// Contents of files that are used instead of reading them from disk e.g. unsaved files open in
+    /// an editor
+    contents: HashMap<String, String>,
 }
 
 impl PathStorage {
-    pub fn new(main: &str) -> Result<PathStorage, WErr> {
+    pub fn new(main: &str, search_paths: &[String]) -> Result<PathStorage, WErr> {
         // TODO: Only allow certain characters in base
         if !main.ends_with(".why") {
             todo!()
@@ -88,6 +101,9 @@ impl PathStorage {
                     current: section.to_string(),
                     scope: Scope::default(),
                 });
+                folders[current.0]
+                    .child_files
+                    .insert(section.to_string(), FileID(files.len() - 1));
             } else {
                 folders.push(CodeFolder {
                     parent: current,
@@ -95,11 +111,31 @@ impl PathStorage {
                     child_files: Default::default(),
                     current: section.to_string(),
                 });
-                current = FolderID(folders.len() - 1);
+                let id = FolderID(folders.len() - 1);
+                folders[current.0]
+                    .child_folders
+                    .insert(section.to_string(), id);
+                current = id;
+            }
+        }
+
+        let mut path_storage = PathStorage {
+            folders,
+            files,
+            search_paths: Vec::new(),
+            prelude: None,
+            contents: HashMap::new(),
+        };
+
+        for search_path in search_paths {
+            let mut current = FolderID::ROOT_FOLDER;
+            for section in search_path.split_terminator('/') {
+                current = path_storage.add_folder(section, current);
             }
+            path_storage.search_paths.push(current);
         }
 
-        Ok(PathStorage { folders, files })
+        Ok(path_storage)
     }
 
     /// Returns a `CodeFile` ref by its ID
@@ -138,6 +174,28 @@ impl PathStorage {
         sb
     }
 
+    /// Reconstructs the paths of every file known, including those that failed to parse
+    pub fn file_paths(&self) -> Vec<String> {
+        (0..self.files.len())
+            .map(|id| self.reconstruct_file(FileID(id)))
+            .collect()
+    }
+
+    /// Uses `text` as the contents of the file at `path` instead of reading it from disk. `path` is
+    /// in the form returned by `reconstruct_file`
+    pub fn set_contents(&mut self, path: String, text: String) {
+        self.contents.insert(path, text);
+    }
+
+    /// Reads a file by its ID, using contents set by `set_contents` if there are any
+    pub fn read_file(&self, id: FileID) -> io::Result<String> {
+        let path = self.reconstruct_file(id);
+        match self.contents.get(&path) {
+            Some(text) => Ok(text.clone()),
+            None => fs::read_to_string(Path::new(&path)),
+        }
+    }
+
     /// Reconstructs a folder path from its ID
     pub fn reconstruct_folder(&self, id: FolderID) -> String {
         let mut sb = self.get_folder(id).current.to_string();
@@ -149,6 +207,91 @@ impl PathStorage {
         sb
     }
 
+    /// Reconstructs the path of a folder from its ID, including a trailing separator. The root folder
+    /// is represented by an empty string
+    fn reconstruct_folder_prefix(&self, id: FolderID) -> String {
+        if id == FolderID::ROOT_FOLDER {
+            String::new()
+        } else {
+            self.reconstruct_folder(id) + std::path::MAIN_SEPARATOR_STR
+        }
+    }
+
+    /// Finds the first folder, starting with `default_base` and then trying each search path in
+    /// order, that contains the file (or folder) at `sections`
+    /// Returns the folder found, if any, and every path tried
+    fn find_base(
+        &self,
+        default_base: FolderID,
+        sections: &[&str],
+        is_folder: bool,
+    ) -> (Option<FolderID>, Vec<String>) {
+        let mut tried = Vec::new();
+        for base in [default_base]
+            .into_iter()
+            .chain(self.search_paths.iter().copied())
+        {
+            let mut candidate = self.reconstruct_folder_prefix(base)
+                + &sections.join(std::path::MAIN_SEPARATOR_STR);
+            if !is_folder {
+                candidate += ".why";
+            }
+
+            let exists = if is_folder {
+                Path::new(&candidate).is_dir()
+            } else {
+                Path::new(&candidate).is_file()
+            };
+            tried.push(candidate);
+
+            if exists {
+                return (Some(base), tried);
+            }
+        }
+        (None, tried)
+    }
+
+    /// Sets the file whose contents are implicitly available to every other file. The path is
+    /// searched for in the same way as an absolute import
+    /// Returns every path tried if the file can't be found
+    pub fn set_prelude(&mut self, path: &str) -> Result<FileID, Vec<String>> {
+        let path = path.strip_suffix(".why").unwrap_or(path);
+        let sections = path.split_terminator('/').collect_vec();
+        let (found, tried) = self.find_base(FolderID::ROOT_FOLDER, &sections, false);
+        let Some(mut current) = found else {
+            return Err(tried);
+        };
+
+        for (is_last, section) in sections.into_iter().identify_last() {
+            if is_last {
+                let (file, _) = self.add_file(section, current);
+                self.prelude = Some(file);
+                return Ok(file);
+            } else {
+                current = self.add_folder(section, current);
+            }
+        }
+
+        Err(tried)
+    }
+
+    /// Adds the prelude to the scope of every file other than the prelude itself
+    pub fn inject_prelude(&mut self) {
+        let Some(prelude) = self.prelude else {
+            return;
+        };
+        for (id, file) in self.files.iter_mut().enumerate() {
+            if FileID(id) != prelude {
+                file.scope.prelude = Some(prelude);
+            }
+        }
+    }
+
+    /// Returns the prelude file, if there is one
+    pub fn prelude(&self) -> Option<FileID> {
+        self.prelude
+    }
+
     /// Adds an import and adds it to a file, returning new files
     /// required to compile the current file
     pub fn get_id_and_add_to_file<'a>(
@@ -223,12 +366,30 @@ impl PathStorage {
             ));
         }
 
-        let mut current: FolderID = if is_absolute {
+        let default_base: FolderID = if is_absolute {
             FolderID::ROOT_FOLDER
         } else {
             self.get_file(current_file).parent
         };
 
+        let (found, tried) = self.find_base(
+            default_base,
+            &path_span.split_terminator('/').collect_vec(),
+            is_folder,
+        );
+
+        let Some(mut current) = found else {
+            return Err(create_custom_error(
+                format!(
+                    "Could not find {} '{}' - tried: {}",
+                    if is_folder { "folder" } else { "file" },
+                    path_span.fragment(),
+                    tried.join(", ")
+                ),
+                path_span,
+            ));
+        };
+
         for (is_last, section) in path_span.split_terminator('/').identify_last() {
             if is_last {
                 if is_folder {
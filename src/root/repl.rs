@@ -0,0 +1,460 @@
+use std::collections::HashMap;
+use std::io;
+use std::io::{BufRead, Write};
+use std::mem;
+use std::ops::Range;
+
+use itertools::Itertools;
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::sequence::tuple;
+use nom::{InputTake, Parser};
+
+use crate::root::builtin::types::int::IntType;
+use crate::root::compiler::analysis::escape::check_escapes;
+use crate::root::compiler::analysis::initialisation::check_initialisation;
+use crate::root::compiler::analysis::nullability::check_nullability;
+use crate::root::compiler::compile_function::{compile_function, compile_lines_in_scope};
+use crate::root::compiler::global_tracker::{GlobalTracker, RuntimeChecks};
+use crate::root::compiler::local_variable_table::{Binding, LocalVariableTable};
+use crate::root::errors::parser_errors::ParseError;
+use crate::root::errors::WErr;
+use crate::root::interpreter::memory::Memory;
+use crate::root::interpreter::{Halt, Interpreter};
+use crate::root::name_resolver::name_resolvers::GlobalTable;
+use crate::root::name_resolver::resolve::resolve;
+use crate::root::parser::handle_errors::handle_error;
+use crate::root::parser::parse::{parse_files, ErrorTree, Span};
+use crate::root::parser::parse_function::parse_evaluable::UnresolvedTypeRefToken;
+use crate::root::parser::parse_function::parse_line::{parse_lines, LineTokens};
+use crate::root::parser::parse_function::{test_parse_function, FunctionToken};
+use crate::root::parser::parse_impl::test_parse_impl;
+use crate::root::parser::parse_imports::parse_imports;
+use crate::root::parser::parse_struct::test_parse_struct;
+use crate::root::parser::parse_toplevel::{parse_toplevel, TopLevelTokens};
+use crate::root::parser::parse_type_alias::test_parse_type_alias;
+use crate::root::parser::parse_util::{discard_ignored_docs, require_ignored};
+use crate::root::parser::path_storage::{FileID, PathStorage};
+use crate::root::shared::common::{AddressedTypeRef, FunctionID, LocalAddress, TypeRef};
+use crate::root::{Args, DEFAULT_PRELUDE};
+
+/// Path of the main file the session's input is stored as. Imports are relative to the working
+/// directory, as they are for files in it
+const REPL_FILE: &str = "repl.why";
+
+/// The definitions of a session resolved along with the files they import
+struct Loaded {
+    global_table: GlobalTable,
+    functions: HashMap<FunctionID, FunctionToken>,
+    path_storage: PathStorage,
+}
+
+/// Everything typed into a REPL and the state of the statements run
+struct Session<'a> {
+    args: &'a Args,
+    runtime_checks: RuntimeChecks,
+    /// Every input accepted so far, in order, so that locations in it can be shown like those in a
+    /// file
+    source: String,
+    /// Ranges of `source` containing definitions and imports. The rest are statements
+    definitions: Vec<Range<usize>>,
+    loaded: Loaded,
+    /// Variables defined by statements, stored in a frame at the top of the stack
+    local_variables: LocalVariableTable,
+    /// How each variable in `local_variables` was defined so it can be recreated when definitions
+    /// are reloaded
+    variables: Vec<(Binding, UnresolvedTypeRefToken, LocalAddress)>,
+    /// Every statement that has run without halting. The variables they define stay in scope so
+    /// the statements entered after them are analysed following them, as in a function
+    statements: Vec<LineTokens>,
+    memory: Memory,
+}
+
+/// Reads struct, impl and function definitions, imports and statements from stdin, running
+/// statements with the interpreter as they're entered. Returns the exit code given by `return` or
+/// `exit`, or 0 at the end of input
+pub fn run_repl(args: &Args) -> Result<i32, String> {
+    let mut session = Session::new(args)?;
+    println!("Enter definitions or statements to run them. Press Ctrl+D to exit");
+
+    let mut lines = io::stdin().lock().lines();
+    while let Some(input) = read_input(&mut lines) {
+        if input.trim().is_empty() {
+            continue;
+        }
+        if let Some(code) = session.enter(&input) {
+            return Ok(code);
+        }
+    }
+    Ok(session.end())
+}
+
+/// Prompts for and reads a line, continuing to read lines until every `{` has been closed.
+/// Returns `None` at the end of input, discarding unfinished input
+fn read_input(lines: &mut impl Iterator<Item = io::Result<String>>) -> Option<String> {
+    let mut input = String::new();
+    loop {
+        print!("{}", if input.is_empty() { ">>> " } else { "... " });
+        io::stdout().flush().ok();
+        let Some(Ok(line)) = lines.next() else {
+            println!();
+            return None;
+        };
+        input += &line;
+        input.push('\n');
+
+        let opened = input.chars().filter(|c| *c == '{').count();
+        let closed = input.chars().filter(|c| *c == '}').count();
+        if closed >= opened {
+            return Some(input);
+        }
+    }
+}
+
+/// Returns the text of `source` in `range`, located as it is in the whole of `source`
+fn span_of<'a>(source: &'a str, range: &Range<usize>) -> Span<'a> {
+    let (span, _) =
+        Span::new_extra(&source[..range.end], FileID::MAIN_FILE).take_split(range.start);
+    span
+}
+
+/// Returns whether input starts with an import or a definition, rather than a statement
+fn is_definition(s: Span) -> bool {
+    let Ok((s, _)) = discard_ignored_docs(s) else {
+        return false;
+    };
+    tuple((
+        alt((tag::<_, _, ErrorTree>("use"), tag("import"))),
+        require_ignored,
+    ))(s)
+    .is_ok()
+        || alt((
+            test_parse_struct,
+            test_parse_impl,
+            test_parse_function,
+            test_parse_type_alias,
+        ))
+        .parse(s)
+        .is_ok()
+}
+
+/// Parses and resolves the definitions in `source`, and the files they import, then compiles each
+/// function defined to check it. Returns the formatted error if any step fails
+fn load(
+    args: &Args,
+    runtime_checks: RuntimeChecks,
+    source: &str,
+    definitions: &[Range<usize>],
+) -> Result<Loaded, String> {
+    // The path is a valid file path
+    let mut path_storage = PathStorage::new(REPL_FILE, &args.search_paths()).unwrap();
+    path_storage.set_contents(REPL_FILE.to_string(), source.to_string());
+    if !args.no_prelude {
+        let prelude = args.prelude.as_deref().unwrap_or(DEFAULT_PRELUDE);
+        if let Err(tried) = path_storage.set_prelude(prelude) {
+            // Only error if the prelude was explicitly specified
+            if args.prelude.is_some() {
+                return Err(
+                    WErr::locationless(ParseError::FailedToOpenFile(tried.join(", ")))
+                        .with_context(&path_storage)
+                        .to_string(),
+                );
+            }
+        }
+    }
+
+    let toplevel_tokens = parse_definitions(&mut path_storage, source, definitions)
+        .map_err(|e| e.with_context(&path_storage).to_string())?;
+    let (mut global_table, functions) = resolve(toplevel_tokens, &path_storage, false)
+        .map_err(|e| e.with_context(&path_storage).to_string())?;
+
+    let mut global_tracker = GlobalTracker::new(&path_storage, runtime_checks);
+    for (id, function) in functions
+        .iter()
+        .filter(|(_, ft)| ft.location().file_id() == Some(FileID::MAIN_FILE))
+        .sorted_by_key(|(id, _)| id.0)
+    {
+        global_tracker.reset_functions();
+        global_table.scope_namespace(
+            FileID::MAIN_FILE,
+            path_storage.get_file(FileID::MAIN_FILE).scope().clone(),
+        );
+        compile_function(
+            *id,
+            function.clone(),
+            &mut global_table,
+            &mut global_tracker,
+        )
+        .map_err(|e| e.with_context(&path_storage).to_string())?;
+    }
+    drop(global_tracker);
+
+    Ok(Loaded {
+        global_table,
+        functions,
+        path_storage,
+    })
+}
+
+/// Parses the definitions in `source` as the main file, then the files they import
+fn parse_definitions(
+    path_storage: &mut PathStorage,
+    source: &str,
+    definitions: &[Range<usize>],
+) -> Result<HashMap<FileID, Vec<TopLevelTokens>>, WErr> {
+    let mut path_queue = Vec::new();
+    let mut tokens = Vec::new();
+    for range in definitions {
+        let (s, new_files) = handle_error(
+            parse_imports(span_of(source, range), path_storage, FileID::MAIN_FILE),
+            path_storage,
+        )?;
+        path_queue.extend(new_files);
+        let (_, new_tokens) = handle_error(parse_toplevel(s), path_storage)?;
+        tokens.extend(new_tokens);
+    }
+
+    parse_files(
+        path_storage,
+        path_queue,
+        HashMap::from([(FileID::MAIN_FILE, tokens)]),
+        false,
+    )
+}
+
+/// Runs the analyses `compile_function` runs on the lines of a function over statements, setting
+/// their results in `global_tracker`
+fn analyse_statements(
+    statements: &[LineTokens],
+    return_type: &TypeRef,
+    global_table: &mut GlobalTable,
+    global_tracker: &mut GlobalTracker,
+) -> Result<(), WErr> {
+    let initialising_assignments = check_initialisation(statements, global_table)?;
+    global_tracker.set_initialising_assignments(initialising_assignments);
+    check_escapes(&Vec::new(), Some(return_type), statements, global_table)?;
+    let non_null_values = check_nullability(&Vec::new(), statements, global_table)?;
+    global_tracker.set_non_null_values(non_null_values);
+    Ok(())
+}
+
+/// Runs statements in the session frame, printing the value of each bare expression with its
+/// type. `variables` is extended with the variables defined
+fn run_lines(
+    interpreter: &mut Interpreter,
+    lines: &[LineTokens],
+    local_variables: &mut LocalVariableTable,
+    variables: &mut Vec<(Binding, UnresolvedTypeRefToken, LocalAddress)>,
+) -> Result<(), Halt> {
+    for line in lines {
+        if let Some(value) =
+            interpreter.execute_statement(line, FileID::MAIN_FILE, local_variables)?
+        {
+            let text = interpreter.display(&value)?;
+            println!("{text}: {}", interpreter.type_name(value.type_ref()));
+        }
+
+        if let LineTokens::Initialisation(it) = line {
+            let name = it.name();
+            let address = local_variables.get(name.name()).unwrap();
+            variables.push((
+                Binding::new(name.name().clone(), *it.mutable(), name.location().clone()),
+                it.type_name().clone(),
+                *address.local_address(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+impl<'a> Session<'a> {
+    fn new(args: &'a Args) -> Result<Session<'a>, String> {
+        let runtime_checks = args.runtime_checks();
+        Ok(Session {
+            args,
+            runtime_checks,
+            source: String::new(),
+            definitions: Vec::new(),
+            loaded: load(args, runtime_checks, "", &[])?,
+            local_variables: LocalVariableTable::new(),
+            variables: Vec::new(),
+            statements: Vec::new(),
+            memory: Memory::new(),
+        })
+    }
+
+    /// Handles a piece of input, returning the code to exit with if it ended the session
+    fn enter(&mut self, input: &str) -> Option<i32> {
+        let start = self.source.len();
+        self.source += input;
+        if is_definition(span_of(&self.source, &(start..self.source.len()))) {
+            self.define(start);
+            return None;
+        }
+
+        // Statements end with `;` as in a function, so it's added if it's been left off
+        let end = self.source.trim_end().len();
+        if !self.source[..end].ends_with([';', '}']) {
+            self.source.insert(end, ';');
+        }
+        self.run(start)
+    }
+
+    /// Adds the definitions in `source` from `start`, discarding them if they can't be loaded
+    fn define(&mut self, start: usize) {
+        let mut definitions = self.definitions.clone();
+        definitions.push(start..self.source.len());
+
+        let mut loaded = match load(self.args, self.runtime_checks, &self.source, &definitions) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                println!("{e}");
+                self.source.truncate(start);
+                return;
+            }
+        };
+        match self.rebind(&mut loaded.global_table, &loaded.path_storage) {
+            Ok(local_variables) => {
+                self.local_variables = local_variables;
+                self.definitions = definitions;
+                self.loaded = loaded;
+            }
+            Err(e) => {
+                println!("{}", e.with_context(&loaded.path_storage));
+                self.source.truncate(start);
+            }
+        }
+    }
+
+    /// Recreates the variables defined so far using types resolved by a new global table, as the
+    /// IDs of types can change when definitions are reloaded
+    fn rebind(
+        &self,
+        global_table: &mut GlobalTable,
+        path_storage: &PathStorage,
+    ) -> Result<LocalVariableTable, WErr> {
+        global_table.scope_namespace(
+            FileID::MAIN_FILE,
+            path_storage.get_file(FileID::MAIN_FILE).scope().clone(),
+        );
+        let mut local_variables = LocalVariableTable::new();
+        local_variables.add_new_unnamed(self.local_variables.stack_size());
+        for (binding, t, address) in &self.variables {
+            let t = global_table.resolve_to_type_ref(t, None)?;
+            local_variables.add_existing(binding.clone(), AddressedTypeRef::new(*address, t));
+        }
+        Ok(local_variables)
+    }
+
+    /// Checks and runs the statements in `source` from `start`, discarding them if they can't be
+    /// parsed or don't compile. Variables are only kept if every statement runs. Returns the code to
+    /// exit with if they ended the session
+    fn run(&mut self, start: usize) -> Option<i32> {
+        let Loaded {
+            global_table,
+            functions,
+            path_storage,
+        } = &mut self.loaded;
+        path_storage.set_contents(REPL_FILE.to_string(), self.source.clone());
+
+        let range = start..self.source.len();
+        let lines = match handle_error(
+            parse_lines(span_of(&self.source, &range), None),
+            path_storage,
+        ) {
+            Ok((_, lines)) => lines,
+            Err(e) => {
+                println!("{}", e.with_context(path_storage));
+                self.source.truncate(start);
+                return None;
+            }
+        };
+
+        // Statements are checked as if they were in `main`, which returns an exit code
+        global_table.scope_namespace(
+            FileID::MAIN_FILE,
+            path_storage.get_file(FileID::MAIN_FILE).scope().clone(),
+        );
+        let mut local_variables = self.local_variables.clone();
+        let return_variable = global_table
+            .add_local_variable_unnamed(IntType::id().immediate_single(), &mut local_variables);
+        let statements = self.statements.iter().chain(&lines).cloned().collect_vec();
+        let mut global_tracker = GlobalTracker::new(path_storage, self.runtime_checks);
+        let checked = analyse_statements(
+            &statements,
+            return_variable.type_ref(),
+            global_table,
+            &mut global_tracker,
+        )
+        .and_then(|()| {
+            compile_lines_in_scope(
+                FunctionID::MAIN_FUNCTION,
+                &lines,
+                &Some(return_variable),
+                &None,
+                &mut local_variables,
+                global_table,
+                &mut global_tracker,
+            )
+        });
+        if let Err(e) = checked {
+            println!("{}", e.with_context(path_storage));
+            self.source.truncate(start);
+            return None;
+        }
+
+        let mut local_variables = self.local_variables.clone();
+        let mut variables = Vec::new();
+        let mut interpreter = Interpreter::with_memory(
+            global_table,
+            functions,
+            path_storage,
+            self.runtime_checks,
+            mem::take(&mut self.memory),
+        );
+        let code = match run_lines(
+            &mut interpreter,
+            &lines,
+            &mut local_variables,
+            &mut variables,
+        ) {
+            Ok(()) => {
+                self.local_variables = local_variables;
+                self.variables.extend(variables);
+                self.statements = statements;
+                None
+            }
+            Err(Halt::Error(e)) => {
+                println!("{}", e.with_context(path_storage));
+                None
+            }
+            // The session can continue after a panic
+            Err(halt @ Halt::Panic(_)) => {
+                interpreter.finish(Err(halt)).ok();
+                None
+            }
+            Err(halt @ Halt::Exit(_)) => interpreter.finish(Err(halt)).ok(),
+        };
+        self.memory = interpreter.into_memory();
+        code
+    }
+
+    /// Ends the session at the end of input, reporting leaked memory as when `main` returns.
+    /// Returns the code to exit with
+    fn end(mut self) -> i32 {
+        let Loaded {
+            global_table,
+            functions,
+            path_storage,
+        } = &mut self.loaded;
+        Interpreter::with_memory(
+            global_table,
+            functions,
+            path_storage,
+            self.runtime_checks,
+            self.memory,
+        )
+        .finish(Ok(0))
+        .unwrap_or(1)
+    }
+}
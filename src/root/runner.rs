@@ -1,5 +1,6 @@
 use std::fs;
 use std::process::Command;
+use std::time::Duration;
 
 use crate::ret_time;
 use color_print::cprintln;
@@ -43,8 +44,29 @@ pub fn link_gcc(output: &str) -> Result<(), ()> {
     Ok(())
 }
 
-/// Runs the built program
-pub fn run(output: &str) {
+/// Output of a program kept instead of being printed
+#[derive(Default)]
+pub struct Captured {
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Runs the built program without printing anything, returning its exit code and what it wrote to
+/// stdout and stderr
+pub fn run_captured(output: &str) -> Result<(Option<i32>, Captured), String> {
+    let full = fs::canonicalize(format!("{output}.out")).map_err(|e| e.to_string())?;
+    let result = Command::new(full).output().map_err(|e| e.to_string())?;
+    Ok((
+        result.status.code(),
+        Captured {
+            stdout: String::from_utf8_lossy(&result.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&result.stderr).to_string(),
+        },
+    ))
+}
+
+/// Runs the built program, returning its exit code if it has one
+pub fn run(output: &str) -> Option<i32> {
     let time;
     ret_time!(time,
         let full = fs::canonicalize(format!("{output}.out")).unwrap();
@@ -55,17 +77,23 @@ pub fn run(output: &str) {
                     None => {
                         cprintln!("<r,bold>\nProcess did not return an exit code. \
                         This could be due to a forceful termination</>");
-                        return;
+                        return None;
                     }
                 }
             }
             Err(e) => {
                 cprintln!("<r,bold>Starting process failed with error:\n{}</>", e);
-                return;
+                return None;
             }
         };
     );
 
+    print_exited(code, time);
+    Some(code)
+}
+
+/// Prints the banner shown after a program has run, with its exit code and how long it took
+pub fn print_exited(code: i32, time: Duration) {
     let termsize::Size { rows: _, cols } = termsize::get().unwrap();
     const EXITED: &str = "Exited";
     if cols > EXITED.len() as u16 && cols < 300 {
@@ -7,6 +7,10 @@ use derive_more::{Add, AddAssign, Sub, SubAssign};
 /// A unique type ID. Negative is builtin, positive is user-defined
 pub struct TypeID(pub isize);
 
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+/// The index of a type alias declared with `type Name = ...;`
+pub struct TypeAliasID(pub usize);
+
 #[allow(dead_code)]
 impl TypeID {
     /// Creates a `TypeRef`
@@ -19,6 +23,11 @@ impl TypeID {
         TypeRef::new(self, 1, Indirection(indirection))
     }
 
+    /// Creates a `TypeRef` with 1 element that is a mutable reference to the type
+    pub fn mutable_reference_single(self) -> TypeRef {
+        self.immediate_single().plus_one_indirect_mutable(true)
+    }
+
     /// Creates a `TypeRef` with no indirection
     pub fn immediate(self, elements: usize) -> TypeRef {
         TypeRef::new(self, elements, Indirection(0))
@@ -100,12 +109,45 @@ impl Display for LocalAddress {
     }
 }
 
-#[derive(Getters, Clone, PartialEq, Debug)]
+#[derive(Getters, Clone, Debug)]
 /// A `TypeID` with `Indirection`
 pub struct TypeRef {
     type_id: TypeID,
     elements: usize,
     indirection: Indirection,
+    /// Which levels of indirection are mutable (`&mut`) references. Bit `n` is set if the
+    /// reference at indirection `n + 1` (counting out from the value) is mutable
+    mutability: u64,
+    /// Which levels of indirection are nullable (`?&`) references, using the same bits as
+    /// `mutability`
+    nullability: u64,
+    /// Which levels of indirection are references to the first value of a heap array (`&[T]`),
+    /// using the same bits as `mutability`. Only these can be indexed, freed or reallocated
+    heap: u64,
+    /// The alias the type was named by, if any. Only used to name the type in error messages
+    alias: Option<TypeAliasID>,
+}
+
+/// Mutability, nullability and aliases don't affect the layout of a type so are ignored when
+/// comparing types. Use `TypeRef::can_coerce_mutability_to` and
+/// `TypeRef::can_coerce_nullability_to` where they matter. A heap array reference is a different
+/// type to a plain reference so that any reference can't be indexed or freed
+impl PartialEq for TypeRef {
+    fn eq(&self, other: &Self) -> bool {
+        self.type_id == other.type_id
+            && self.elements == other.elements
+            && self.indirection == other.indirection
+            && self.heap == other.heap
+    }
+}
+
+/// Returns a mask of the per-reference bits (see `TypeRef::mutability`) valid for an indirection
+fn reference_mask(indirection: Indirection) -> u64 {
+    if indirection.0 >= 64 {
+        u64::MAX
+    } else {
+        (1 << indirection.0) - 1
+    }
 }
 
 impl TypeRef {
@@ -114,6 +156,10 @@ impl TypeRef {
             type_id,
             elements,
             indirection,
+            mutability: 0,
+            nullability: 0,
+            heap: 0,
+            alias: None,
         }
     }
 
@@ -123,40 +169,149 @@ impl TypeRef {
         self.elements == 1
     }
 
-    /// Returns a `TypeRef` with a different indirection
+    /// Returns a `TypeRef` with different reference mutability (see `TypeRef::mutability`)
+    pub fn with_mutability(&self, mutability: u64) -> TypeRef {
+        TypeRef {
+            type_id: self.type_id,
+            elements: self.elements,
+            indirection: self.indirection,
+            mutability: mutability & reference_mask(self.indirection),
+            nullability: self.nullability,
+            heap: self.heap,
+            alias: self.alias,
+        }
+    }
+
+    /// Returns a `TypeRef` with different reference nullability (see `TypeRef::nullability`)
+    pub fn with_nullability(&self, nullability: u64) -> TypeRef {
+        TypeRef {
+            nullability: nullability & reference_mask(self.indirection),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a `TypeRef` with different heap array references (see `TypeRef::heap`)
+    pub fn with_heap(&self, heap: u64) -> TypeRef {
+        TypeRef {
+            heap: heap & reference_mask(self.indirection),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a `TypeRef` whose outermost reference (if any) isn't to a heap array e.g. the
+    /// reference to a single value of a heap array
+    pub fn non_heap(&self) -> TypeRef {
+        self.with_heap(
+            self.heap & reference_mask(Indirection(self.indirection.0.saturating_sub(1))),
+        )
+    }
+
+    /// Returns a `TypeRef` whose outermost reference (if any) isn't nullable
+    pub fn non_null(&self) -> TypeRef {
+        self.with_nullability(
+            self.nullability & reference_mask(Indirection(self.indirection.0.saturating_sub(1))),
+        )
+    }
+
+    /// Returns a `TypeRef` with a different indirection. Inner references keep their mutability,
+    /// nullability and whether they refer to heap arrays
     pub fn with_indirection(&self, indirection: Indirection) -> TypeRef {
         TypeRef {
             type_id: self.type_id,
             elements: self.elements,
             indirection,
+            mutability: self.mutability & reference_mask(indirection),
+            nullability: self.nullability & reference_mask(indirection),
+            heap: self.heap & reference_mask(indirection),
+            alias: None,
         }
     }
 
-    /// Returns a `TypeRef` with one more indirection
+    /// Returns a `TypeRef` with one more (shared) indirection
     pub fn plus_one_indirect(&self) -> TypeRef {
+        self.plus_one_indirect_mutable(false)
+    }
+
+    /// Returns a `TypeRef` with one more indirection that is mutable if `mutable`
+    pub fn plus_one_indirect_mutable(&self, mutable: bool) -> TypeRef {
         TypeRef {
             type_id: self.type_id,
             elements: self.elements,
             indirection: Indirection(self.indirection.0 + 1),
+            mutability: self.mutability | ((mutable as u64) << self.indirection.0),
+            nullability: self.nullability,
+            heap: self.heap,
+            alias: None,
         }
     }
 
-    /// Returns a `TypeRef` with one less indirection
-    pub fn minus_one_indirect(&self) -> TypeRef {
+    /// Returns a `TypeRef` with one more indirection referring to a heap array of this type,
+    /// mutable if `mutable`
+    pub fn plus_one_indirect_heap(&self, mutable: bool) -> TypeRef {
+        let t = self.plus_one_indirect_mutable(mutable);
+        t.with_heap(t.heap | (1 << self.indirection.0))
+    }
+
+    /// Returns a `TypeRef` that is named by a type alias in error messages
+    pub fn with_alias(&self, alias: TypeAliasID) -> TypeRef {
         TypeRef {
-            type_id: self.type_id,
-            elements: self.elements,
-            indirection: Indirection(self.indirection.0 - 1),
+            alias: Some(alias),
+            ..self.clone()
         }
     }
 
+    /// Returns a `TypeRef` with one less indirection
+    pub fn minus_one_indirect(&self) -> TypeRef {
+        self.with_indirection(Indirection(self.indirection.0 - 1))
+    }
+
     /// Returns a `TypeRef` with no indirection
     pub fn immediate(&self) -> TypeRef {
-        TypeRef {
-            type_id: self.type_id,
-            elements: self.elements,
-            indirection: Indirection(0),
-        }
+        self.with_indirection(Indirection(0))
+    }
+
+    /// Returns whether the outermost level of indirection is a mutable reference
+    pub fn is_mutable_reference(&self) -> bool {
+        self.indirection.has_indirection() && self.is_mutable_at(self.indirection)
+    }
+
+    /// Returns whether the reference at an indirection (counting out from the value) is mutable
+    pub fn is_mutable_at(&self, indirection: Indirection) -> bool {
+        indirection.has_indirection() && (self.mutability >> (indirection.0 - 1)) & 1 == 1
+    }
+
+    /// Returns whether the outermost level of indirection is a reference to a heap array
+    pub fn is_heap_reference(&self) -> bool {
+        self.is_heap_at(self.indirection)
+    }
+
+    /// Returns whether the reference at an indirection (counting out from the value) refers to a
+    /// heap array
+    pub fn is_heap_at(&self, indirection: Indirection) -> bool {
+        indirection.has_indirection() && (self.heap >> (indirection.0 - 1)) & 1 == 1
+    }
+
+    /// Returns whether the outermost level of indirection is a nullable reference
+    pub fn is_nullable_reference(&self) -> bool {
+        self.is_nullable_at(self.indirection)
+    }
+
+    /// Returns whether the reference at an indirection (counting out from the value) is nullable
+    pub fn is_nullable_at(&self, indirection: Indirection) -> bool {
+        indirection.has_indirection() && (self.nullability >> (indirection.0 - 1)) & 1 == 1
+    }
+
+    /// Returns whether a value of this type can be used where `target` is expected i.e. every
+    /// reference that is mutable in `target` is mutable in this type
+    pub fn can_coerce_mutability_to(&self, target: &TypeRef) -> bool {
+        target.mutability & !self.mutability == 0
+    }
+
+    /// Returns whether a value of this type can be used where `target` is expected without
+    /// knowing anything about its value i.e. every reference that is nullable in this type is
+    /// nullable in `target`
+    pub fn can_coerce_nullability_to(&self, target: &TypeRef) -> bool {
+        self.nullability & !target.nullability == 0
     }
 }
 
@@ -28,4 +28,7 @@ pub trait Type {
         location: &LocalAddress,
         literal: &LiteralToken,
     ) -> Result<String, WErr>;
+
+    /// Returns the bytes of a value created from a literal, for use by the interpreter
+    fn literal_bytes(&self, literal: &LiteralToken) -> Result<Vec<u8>, WErr>;
 }
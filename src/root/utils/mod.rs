@@ -54,6 +54,25 @@ pub fn align<T: Copy + Sub<Output = T> + Rem<Output = T> + Add<Output = T>>(
     num + (alignment - (num % alignment)) % alignment
 }
 
+/// Removes terminal colour codes from text
+pub fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip to the end of the escape sequence
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
 /// Prints a warning
 pub fn warn(msg: &str) {
     cprintln!("\n<y,bold>Warning:</> <y>{}</>", msg);
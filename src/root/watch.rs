@@ -0,0 +1,56 @@
+use std::fs;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use color_print::cprintln;
+
+use crate::root::{build_or_check, execute, Args};
+
+/// How often source files are checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
+
+/// Builds and runs the input file, then rebuilds and reruns it from scratch whenever one of the
+/// files it uses changes. The screen is cleared before each rebuild so that only the latest output,
+/// or the latest error if the build failed, is visible
+pub fn watch(args: &Args, test: bool) -> Result<(), String> {
+    loop {
+        let mut files = Vec::new();
+        match build_or_check(args, test, &mut files) {
+            Ok(built) => {
+                // Snapshot before running so that changes made while the program runs are seen
+                let snapshot = modified_times(&files);
+                execute(args, built);
+                wait_for_change(&files, &snapshot);
+            }
+            Err(e) => {
+                println!("\n{e}");
+                let snapshot = modified_times(&files);
+                wait_for_change(&files, &snapshot);
+            }
+        }
+
+        // Clear the screen and move the cursor to the top left
+        print!("\x1B[2J\x1B[H");
+    }
+}
+
+/// Returns the modification time of each file, or `None` if it can't be read e.g. if it doesn't
+/// exist yet
+fn modified_times(files: &[String]) -> Vec<Option<SystemTime>> {
+    files
+        .iter()
+        .map(|f| fs::metadata(f).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+/// Blocks until the modification time of any of the files differs from `snapshot`
+fn wait_for_change(files: &[String], snapshot: &[Option<SystemTime>]) {
+    cprintln!(
+        "\n<s>Watching {} file{} for changes...</>",
+        files.len(),
+        if files.len() == 1 { "" } else { "s" }
+    );
+    while modified_times(files) == snapshot {
+        thread::sleep(POLL_INTERVAL);
+    }
+}